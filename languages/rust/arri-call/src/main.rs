@@ -0,0 +1,370 @@
+use std::{
+    collections::HashMap,
+    io::{IsTerminal, Read},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use arri_client::{
+    definition::{AppDefinition, DefinitionDiff, ProcedureDefinition},
+    dynamic::DynamicClient,
+    serde_json,
+    sse::{SseCloseReason, SseEvent, SseOptions},
+    ArriClientConfig, ArriError,
+};
+use clap::{Parser, Subcommand};
+
+/// Call the procedures of an Arri RPC server using its app definition
+#[derive(Debug, Parser)]
+#[command(name = "arri-call", version)]
+struct Cli {
+    /// Path to the app definition JSON file
+    #[arg(short, long, global = true, default_value = "__definition.json")]
+    definition: PathBuf,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List every procedure in the app definition
+    List,
+    /// Call a procedure and print the response
+    Call {
+        /// Name of the procedure, e.g. `users.getUser`
+        procedure: String,
+        /// Params as JSON. Read from stdin when omitted.
+        params: Option<String>,
+        /// URL of the server, e.g. `http://localhost:3000`
+        #[arg(short, long, env = "ARRI_BASE_URL")]
+        base_url: String,
+        /// Header to send with the request as `name: value`. Can be repeated.
+        #[arg(short = 'H', long = "header", value_parser = parse_header)]
+        headers: Vec<(String, String)>,
+        /// Number of times to reconnect to event streams after a failure
+        #[arg(long, default_value_t = 0)]
+        retries: u64,
+        /// Don't check params and responses against the app definition
+        #[arg(long)]
+        no_validate: bool,
+    },
+    /// Compare two app definitions and report the changes that break existing clients
+    Diff {
+        /// Definition of the deployed server
+        old: PathBuf,
+        /// Definition that will replace it
+        new: PathBuf,
+        /// Print the changes as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+fn parse_header(input: &str) -> Result<(String, String), String> {
+    match input.split_once(':') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("expected \"name: value\" but got \"{}\"", input)),
+    }
+}
+
+/// Exit with 4 for 4xx errors, 5 for 5xx errors and 1 for everything else
+/// (including connection errors, which have code 0).
+fn exit_code(err: &ArriError) -> u8 {
+    match err.code {
+        400..=499 => 4,
+        500..=599 => 5,
+        _ => 1,
+    }
+}
+
+fn list_procedures(definition: &AppDefinition) -> String {
+    let width = definition
+        .procedures
+        .keys()
+        .map(|name| name.len())
+        .max()
+        .unwrap_or_default();
+    let mut lines = Vec::new();
+    for (name, procedure) in &definition.procedures {
+        lines.push(format!("{:width$}  {}", name, describe(procedure)));
+    }
+    lines.join("\n")
+}
+
+fn describe(procedure: &ProcedureDefinition) -> String {
+    let mut parts = vec![
+        match procedure.transport.name() {
+            "http" => procedure.http_method().to_string(),
+            transport => transport.to_uppercase(),
+        },
+        procedure.path.clone(),
+        format!(
+            "({}) -> {}",
+            procedure.params.as_deref().unwrap_or_default(),
+            procedure.response.as_deref().unwrap_or("()")
+        ),
+    ];
+    if procedure.is_event_stream {
+        parts.push("[stream]".to_string());
+    }
+    if procedure.is_deprecated {
+        parts.push("[deprecated]".to_string());
+    }
+    parts.join(" ")
+}
+
+fn read_params(params: Option<String>) -> Result<serde_json::Value, String> {
+    let input = match params {
+        Some(params) => params,
+        None if std::io::stdin().is_terminal() => return Ok(serde_json::json!({})),
+        None => {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|err| format!("Unable to read params from stdin: {}", err))?;
+            input
+        }
+    };
+    if input.trim().is_empty() {
+        return Ok(serde_json::json!({}));
+    }
+    serde_json::from_str(&input).map_err(|err| format!("Params are not valid JSON: {}", err))
+}
+
+fn read_definition(path: &PathBuf) -> Result<AppDefinition, String> {
+    let input = std::fs::read_to_string(path)
+        .map_err(|err| format!("Unable to read {}: {}", path.display(), err))?;
+    AppDefinition::from_json_str(&input)
+        .map_err(|err| format!("Invalid app definition {}: {}", path.display(), err))
+}
+
+/// Exits with 3 when there are breaking changes
+fn diff(old: &PathBuf, new: &PathBuf, json: bool) -> ExitCode {
+    let (old, new) = match (read_definition(old), read_definition(new)) {
+        (Ok(old), Ok(new)) => (old, new),
+        (Err(message), _) | (_, Err(message)) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let result = DefinitionDiff::compare(&old, &new);
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&result.to_json()).unwrap_or_default()
+        );
+    } else {
+        for change in &result.changes {
+            println!("{}", change);
+        }
+    }
+    if result.is_breaking() {
+        return ExitCode::from(3);
+    }
+    ExitCode::SUCCESS
+}
+
+fn print_error(err: &ArriError) {
+    eprintln!(
+        "{}",
+        serde_json::to_string_pretty(&serde_json::json!({
+            "code": err.code,
+            "message": err.message,
+            "data": err.data,
+        }))
+        .unwrap_or_default()
+    );
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    if let Command::Diff { old, new, json } = &cli.command {
+        return diff(old, new, *json);
+    }
+    let definition = match read_definition(&cli.definition) {
+        Ok(definition) => definition,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let (procedure, params, base_url, headers, retries, no_validate) = match cli.command {
+        Command::List => {
+            println!("{}", list_procedures(&definition));
+            return ExitCode::SUCCESS;
+        }
+        Command::Call {
+            procedure,
+            params,
+            base_url,
+            headers,
+            retries,
+            no_validate,
+        } => (procedure, params, base_url, headers, retries, no_validate),
+        Command::Diff { .. } => unreachable!(),
+    };
+    let params = match read_params(params) {
+        Ok(params) => params,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let is_event_stream = definition
+        .procedures
+        .get(&procedure)
+        .is_some_and(|procedure| procedure.is_event_stream);
+    let client = DynamicClient::new(
+        ArriClientConfig {
+            http_client: arri_client::reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            // header names only need to live as long as the process
+            headers: headers
+                .into_iter()
+                .map(|(name, value)| (&*Box::leak(name.into_boxed_str()), value))
+                .collect::<HashMap<_, _>>(),
+        },
+        definition,
+    )
+    .validation(!no_validate);
+
+    if !is_event_stream {
+        return match client.call(&procedure, params).await {
+            Ok(response) => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&response).unwrap_or_default()
+                );
+                ExitCode::SUCCESS
+            }
+            Err(err) => {
+                print_error(&err);
+                ExitCode::from(exit_code(&err))
+            }
+        };
+    }
+
+    // print one message per line so that the output can be piped
+    let mut last_error: Option<ArriError> = None;
+    let mut close_reason: Option<SseCloseReason> = None;
+    let result = client
+        .call_stream(
+            &procedure,
+            params,
+            &mut |event, _| match event {
+                SseEvent::Message(message) => println!("{}", message),
+                SseEvent::Error(err) => {
+                    print_error(&err);
+                    last_error = Some(err);
+                }
+                SseEvent::Close(reason) => close_reason = Some(reason),
+                _ => {}
+            },
+            SseOptions {
+                max_retry_count: Some(retries),
+                ..Default::default()
+            },
+        )
+        .await;
+    if let Err(err) = result {
+        print_error(&err);
+        return ExitCode::from(exit_code(&err));
+    }
+    match close_reason {
+        Some(SseCloseReason::Done) | Some(SseCloseReason::Aborted) => ExitCode::SUCCESS,
+        _ => ExitCode::from(last_error.as_ref().map(exit_code).unwrap_or(1)),
+    }
+}
+
+#[cfg(test)]
+mod cli_tests {
+    use super::*;
+
+    const APP_DEFINITION: &str = include_str!("../../../../tests/test-files/AppDefinition.json");
+
+    #[test]
+    fn parse_args_test() {
+        let cli = Cli::try_parse_from([
+            "arri-call",
+            "call",
+            "books.getBook",
+            "{\"bookId\":\"1\"}",
+            "--base-url",
+            "http://localhost:3000",
+            "-H",
+            "Authorization: Bearer 1:2",
+            "-d",
+            "definition.json",
+        ])
+        .unwrap();
+        assert_eq!(cli.definition, PathBuf::from("definition.json"));
+        match cli.command {
+            Command::Call {
+                procedure,
+                params,
+                headers,
+                retries,
+                ..
+            } => {
+                assert_eq!(procedure, "books.getBook");
+                assert_eq!(params.as_deref(), Some("{\"bookId\":\"1\"}"));
+                assert_eq!(
+                    headers,
+                    vec![("Authorization".to_string(), "Bearer 1:2".to_string())]
+                );
+                assert_eq!(retries, 0);
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+        assert!(
+            Cli::try_parse_from(["arri-call", "call", "a", "--base-url", "b", "-H", "c"]).is_err()
+        );
+    }
+
+    #[test]
+    fn parse_diff_args_test() {
+        let cli =
+            Cli::try_parse_from(["arri-call", "diff", "old.json", "new.json", "--json"]).unwrap();
+        match cli.command {
+            Command::Diff { old, new, json } => {
+                assert_eq!(old, PathBuf::from("old.json"));
+                assert_eq!(new, PathBuf::from("new.json"));
+                assert!(json);
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+        assert!(Cli::try_parse_from(["arri-call", "diff", "old.json"]).is_err());
+    }
+
+    #[test]
+    fn exit_code_test() {
+        let err = |code| ArriError {
+            code,
+            message: String::new(),
+            stack: None,
+            data: None,
+        };
+        assert_eq!(exit_code(&err(0)), 1);
+        assert_eq!(exit_code(&err(404)), 4);
+        assert_eq!(exit_code(&err(503)), 5);
+    }
+
+    #[test]
+    fn list_procedures_test() {
+        let definition = AppDefinition::from_json_str(APP_DEFINITION).unwrap();
+        assert_eq!(
+            list_procedures(&definition),
+            [
+                "books.createBook        POST /books/create-book (Book) -> Book [deprecated]",
+                "books.createConnection  WS /books/create-connection (BookParams) -> Book",
+                "books.getBook           GET /books/get-book (BookParams) -> Book",
+                "books.watchBook         GET /books/watch-book (BookParams) -> Book [stream] [deprecated]",
+                "sendObject              POST /send-object (NestedObject) -> NestedObject",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,676 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+mod diff;
+mod hash;
+mod schema;
+mod validation;
+
+pub use diff::*;
+pub use hash::*;
+pub use schema::*;
+pub use validation::*;
+
+/// An Arri App Definition. See `specifications/arri_app_definition.md`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppDefinition {
+    pub schema_version: String,
+    pub info: Option<AppInfo>,
+    /// Procedures by name. Names of nested procedures are joined with `.`, e.g. `users.getUser`.
+    pub procedures: BTreeMap<String, ProcedureDefinition>,
+    pub definitions: BTreeMap<String, TypeDefinition>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AppInfo {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Sent in the `client-version` header
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcedureDefinition {
+    pub transport: Transport,
+    pub path: String,
+    pub method: Option<reqwest::Method>,
+    /// Name of the input type in `definitions`
+    pub params: Option<String>,
+    /// Name of the output type in `definitions`
+    pub response: Option<String>,
+    pub description: Option<String>,
+    pub is_event_stream: bool,
+    pub is_deprecated: bool,
+    pub deprecated_note: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Transport {
+    Http,
+    Ws,
+    Custom(String),
+}
+
+/// A problem with a definition. `path` is a JSON pointer to the offending value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefinitionError {
+    pub path: String,
+    pub message: String,
+}
+
+impl DefinitionError {
+    pub fn new(path: &str, message: impl Into<String>) -> Self {
+        Self {
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for DefinitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            return write!(f, "{}", self.message);
+        }
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for DefinitionError {}
+
+impl Transport {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "http" => Self::Http,
+            "ws" => Self::Ws,
+            _ => Self::Custom(name.to_string()),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Http => "http",
+            Self::Ws => "ws",
+            Self::Custom(name) => name.as_str(),
+        }
+    }
+}
+
+impl ProcedureDefinition {
+    /// The HTTP method used to call the procedure. Defaults to `POST`.
+    pub fn http_method(&self) -> reqwest::Method {
+        self.method.clone().unwrap_or(reqwest::Method::POST)
+    }
+
+    pub fn from_json(input: &serde_json::Value, path: &str) -> Result<Self, DefinitionError> {
+        let object = expect_object(input, path)?;
+        let key_path = |key: &str| format!("{}/{}", path, key);
+        let optional_string = |key: &str| -> Result<Option<String>, DefinitionError> {
+            match object.get(key) {
+                Some(value) => Ok(Some(expect_string(value, &key_path(key))?.to_string())),
+                None => Ok(None),
+            }
+        };
+        let optional_bool = |key: &str| -> Result<bool, DefinitionError> {
+            match object.get(key) {
+                Some(value) => expect_bool(value, &key_path(key)),
+                None => Ok(false),
+            }
+        };
+        let transport = match object.get("transport") {
+            Some(value) => Transport::from_name(expect_string(value, &key_path("transport"))?),
+            None => return Err(DefinitionError::new(path, "Missing \"transport\"")),
+        };
+        let procedure_path = match object.get("path") {
+            Some(value) => expect_string(value, &key_path("path"))?.to_string(),
+            None => return Err(DefinitionError::new(path, "Missing \"path\"")),
+        };
+        let method = match optional_string("method")? {
+            Some(name) => match name.as_str() {
+                "get" => Some(reqwest::Method::GET),
+                "post" => Some(reqwest::Method::POST),
+                "patch" => Some(reqwest::Method::PATCH),
+                "put" => Some(reqwest::Method::PUT),
+                "delete" => Some(reqwest::Method::DELETE),
+                _ => {
+                    return Err(DefinitionError::new(
+                        &key_path("method"),
+                        format!("Unsupported method \"{}\"", name),
+                    ))
+                }
+            },
+            None => None,
+        };
+        Ok(Self {
+            transport,
+            path: procedure_path,
+            method,
+            params: optional_string("params")?,
+            response: optional_string("response")?,
+            description: optional_string("description")?,
+            is_event_stream: optional_bool("isEventStream")?,
+            is_deprecated: optional_bool("isDeprecated")?,
+            deprecated_note: optional_string("deprecatedNote")?,
+        })
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut output = serde_json::Map::new();
+        output.insert("transport".to_string(), self.transport.name().into());
+        output.insert("path".to_string(), self.path.clone().into());
+        if let Some(method) = &self.method {
+            output.insert("method".to_string(), method.as_str().to_lowercase().into());
+        }
+        let optional = [
+            ("params", &self.params),
+            ("response", &self.response),
+            ("description", &self.description),
+        ];
+        for (key, value) in optional {
+            if let Some(value) = value {
+                output.insert(key.to_string(), value.clone().into());
+            }
+        }
+        if self.is_event_stream {
+            output.insert("isEventStream".to_string(), true.into());
+        }
+        if self.is_deprecated {
+            output.insert("isDeprecated".to_string(), true.into());
+        }
+        if let Some(note) = &self.deprecated_note {
+            output.insert("deprecatedNote".to_string(), note.clone().into());
+        }
+        serde_json::Value::Object(output)
+    }
+}
+
+impl AppDefinition {
+    /// Parse a definition. This only checks the document structure, use [`AppDefinition::validate`]
+    /// to check that it is consistent.
+    pub fn from_json(input: &serde_json::Value) -> Result<Self, DefinitionError> {
+        let object = expect_object(input, "")?;
+        let schema_version = match object.get("schemaVersion") {
+            Some(value) => expect_string(value, "/schemaVersion")?.to_string(),
+            None => return Err(DefinitionError::new("", "Missing \"schemaVersion\"")),
+        };
+        let info = match object.get("info") {
+            Some(value) => {
+                let info = expect_object(value, "/info")?;
+                let field = |key: &str| -> Result<Option<String>, DefinitionError> {
+                    match info.get(key) {
+                        Some(value) => Ok(Some(
+                            expect_string(value, &format!("/info/{}", key))?.to_string(),
+                        )),
+                        None => Ok(None),
+                    }
+                };
+                Some(AppInfo {
+                    name: field("name")?,
+                    description: field("description")?,
+                    version: field("version")?,
+                })
+            }
+            None => None,
+        };
+        let mut procedures = BTreeMap::new();
+        match object.get("procedures") {
+            Some(value) => {
+                for (name, procedure) in expect_object(value, "/procedures")? {
+                    let path = format!("/procedures/{}", escape_pointer(name));
+                    procedures.insert(
+                        name.clone(),
+                        ProcedureDefinition::from_json(procedure, &path)?,
+                    );
+                }
+            }
+            None => return Err(DefinitionError::new("", "Missing \"procedures\"")),
+        }
+        let mut definitions = BTreeMap::new();
+        match object.get("definitions") {
+            Some(value) => {
+                for (name, definition) in expect_object(value, "/definitions")? {
+                    let path = format!("/definitions/{}", escape_pointer(name));
+                    definitions.insert(name.clone(), TypeDefinition::from_json(definition, &path)?);
+                }
+            }
+            None => return Err(DefinitionError::new("", "Missing \"definitions\"")),
+        }
+        Ok(Self {
+            schema_version,
+            info,
+            procedures,
+            definitions,
+        })
+    }
+
+    pub fn from_json_str(input: &str) -> Result<Self, DefinitionError> {
+        match serde_json::from_str(input) {
+            Ok(value) => Self::from_json(&value),
+            Err(err) => Err(DefinitionError::new("", err.to_string())),
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut output = serde_json::Map::new();
+        output.insert(
+            "schemaVersion".to_string(),
+            self.schema_version.clone().into(),
+        );
+        if let Some(info) = &self.info {
+            let mut info_output = serde_json::Map::new();
+            let fields = [
+                ("name", &info.name),
+                ("description", &info.description),
+                ("version", &info.version),
+            ];
+            for (key, value) in fields {
+                if let Some(value) = value {
+                    info_output.insert(key.to_string(), value.clone().into());
+                }
+            }
+            output.insert("info".to_string(), serde_json::Value::Object(info_output));
+        }
+        output.insert(
+            "procedures".to_string(),
+            serde_json::Value::Object(
+                self.procedures
+                    .iter()
+                    .map(|(name, procedure)| (name.clone(), procedure.to_json()))
+                    .collect(),
+            ),
+        );
+        output.insert(
+            "definitions".to_string(),
+            serde_json::Value::Object(
+                self.definitions
+                    .iter()
+                    .map(|(name, definition)| (name.clone(), definition.to_json()))
+                    .collect(),
+            ),
+        );
+        serde_json::Value::Object(output)
+    }
+
+    /// Find the schema a `ref` points to. Refs are matched against definition keys first, then
+    /// against `metadata.id` anywhere in the definitions.
+    pub fn resolve_ref(&self, id: &str) -> Option<&TypeDefinition> {
+        if let Some(definition) = self.definitions.get(id) {
+            return Some(definition);
+        }
+        let mut ids = HashMap::new();
+        for definition in self.definitions.values() {
+            collect_ids(definition, &mut ids);
+        }
+        ids.get(id).copied()
+    }
+
+    /// Check `value` against the definition named `type_name`. Returns every violation.
+    pub fn validate_value(
+        &self,
+        type_name: &str,
+        value: &serde_json::Value,
+    ) -> Result<(), Vec<ValidationError>> {
+        let schema = match self.definitions.get(type_name) {
+            Some(schema) => schema,
+            None => {
+                return Err(vec![ValidationError::new(
+                    "",
+                    format!("Unknown type \"{}\"", type_name),
+                )])
+            }
+        };
+        let errors = ValueValidator::new(&self.definitions).validate(value, schema);
+        if errors.is_empty() {
+            return Ok(());
+        }
+        Err(errors)
+    }
+
+    /// Check that the definition is internally consistent. Returns every problem that was found.
+    pub fn validate(&self) -> Vec<DefinitionError> {
+        let mut errors = Vec::new();
+        let mut ids = HashMap::new();
+        for definition in self.definitions.values() {
+            collect_ids(definition, &mut ids);
+        }
+        let mut routes: HashMap<(String, String), &str> = HashMap::new();
+        for (name, procedure) in &self.procedures {
+            let path = format!("/procedures/{}", escape_pointer(name));
+            if name.is_empty() || name.split('.').any(|part| part.is_empty()) {
+                errors.push(DefinitionError::new(&path, "Invalid procedure name"));
+            }
+            let service_prefix = format!("{}.", name);
+            if self
+                .procedures
+                .keys()
+                .any(|other| other.starts_with(&service_prefix))
+            {
+                errors.push(DefinitionError::new(
+                    &path,
+                    format!("\"{}\" is both a procedure and a service", name),
+                ));
+            }
+            if !procedure.path.starts_with('/') {
+                errors.push(DefinitionError::new(
+                    &format!("{}/path", path),
+                    "Path must start with \"/\"",
+                ));
+            }
+            let route_method = match procedure.transport {
+                Transport::Http => procedure.http_method().to_string(),
+                _ => procedure.transport.name().to_string(),
+            };
+            let route = (route_method, procedure.path.clone());
+            if let Some(other) = routes.get(&route) {
+                errors.push(DefinitionError::new(
+                    &format!("{}/path", path),
+                    format!("{} {} is already used by \"{}\"", route.0, route.1, other),
+                ));
+            } else {
+                routes.insert(route, name);
+            }
+            for (key, type_name) in [
+                ("params", &procedure.params),
+                ("response", &procedure.response),
+            ] {
+                if let Some(type_name) = type_name {
+                    if !self.definitions.contains_key(type_name) {
+                        errors.push(DefinitionError::new(
+                            &format!("{}/{}", path, key),
+                            format!("Unknown type \"{}\"", type_name),
+                        ));
+                    }
+                }
+            }
+        }
+        for (name, definition) in &self.definitions {
+            let path = format!("/definitions/{}", escape_pointer(name));
+            if let Some(id) = definition.id() {
+                if id != name {
+                    errors.push(DefinitionError::new(
+                        &format!("{}/metadata/id", path),
+                        format!("Expected \"{}\" to match the definition name", id),
+                    ));
+                }
+            }
+            validate_schema(definition, &path, &ids, &self.definitions, &mut errors);
+        }
+        errors
+    }
+}
+
+fn collect_ids<'a>(definition: &'a TypeDefinition, ids: &mut HashMap<&'a str, &'a TypeDefinition>) {
+    if let Some(id) = definition.id() {
+        ids.entry(id).or_insert(definition);
+    }
+    match &definition.form {
+        SchemaForm::Elements(inner) | SchemaForm::Values(inner) => collect_ids(inner, ids),
+        SchemaForm::Properties(schema) => {
+            for property in schema
+                .properties
+                .values()
+                .chain(schema.optional_properties.values())
+            {
+                collect_ids(property, ids);
+            }
+        }
+        SchemaForm::Discriminator(schema) => {
+            for mapping in schema.mapping.values() {
+                collect_ids(mapping, ids);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn validate_schema(
+    definition: &TypeDefinition,
+    path: &str,
+    ids: &HashMap<&str, &TypeDefinition>,
+    definitions: &BTreeMap<String, TypeDefinition>,
+    errors: &mut Vec<DefinitionError>,
+) {
+    match &definition.form {
+        SchemaForm::Empty | SchemaForm::Type(_) => {}
+        SchemaForm::Enum(values) => {
+            if values.is_empty() {
+                errors.push(DefinitionError::new(
+                    &format!("{}/enum", path),
+                    "Enum must have at least one value",
+                ));
+            }
+            let mut seen = HashSet::new();
+            for (index, value) in values.iter().enumerate() {
+                if !seen.insert(value) {
+                    errors.push(DefinitionError::new(
+                        &format!("{}/enum/{}", path, index),
+                        format!("Duplicate enum value \"{}\"", value),
+                    ));
+                }
+            }
+        }
+        SchemaForm::Elements(inner) => validate_schema(
+            inner,
+            &format!("{}/elements", path),
+            ids,
+            definitions,
+            errors,
+        ),
+        SchemaForm::Values(inner) => {
+            validate_schema(inner, &format!("{}/values", path), ids, definitions, errors)
+        }
+        SchemaForm::Properties(schema) => {
+            for (key, property) in &schema.optional_properties {
+                if schema.properties.contains_key(key) {
+                    errors.push(DefinitionError::new(
+                        &format!("{}/optionalProperties/{}", path, escape_pointer(key)),
+                        format!("\"{}\" is also listed in properties", key),
+                    ));
+                }
+                validate_schema(
+                    property,
+                    &format!("{}/optionalProperties/{}", path, escape_pointer(key)),
+                    ids,
+                    definitions,
+                    errors,
+                );
+            }
+            for (key, property) in &schema.properties {
+                validate_schema(
+                    property,
+                    &format!("{}/properties/{}", path, escape_pointer(key)),
+                    ids,
+                    definitions,
+                    errors,
+                );
+            }
+        }
+        SchemaForm::Discriminator(schema) => {
+            for (tag, mapping) in &schema.mapping {
+                let mapping_path = format!("{}/mapping/{}", path, escape_pointer(tag));
+                match &mapping.form {
+                    SchemaForm::Properties(properties) => {
+                        if properties.properties.contains_key(&schema.discriminator)
+                            || properties
+                                .optional_properties
+                                .contains_key(&schema.discriminator)
+                        {
+                            errors.push(DefinitionError::new(
+                                &mapping_path,
+                                format!(
+                                    "Mapping cannot define the discriminator \"{}\"",
+                                    schema.discriminator
+                                ),
+                            ));
+                        }
+                    }
+                    _ => errors.push(DefinitionError::new(
+                        &mapping_path,
+                        "Mapping must be a properties schema",
+                    )),
+                }
+                if mapping.is_nullable {
+                    errors.push(DefinitionError::new(
+                        &mapping_path,
+                        "Mapping cannot be nullable",
+                    ));
+                }
+                validate_schema(mapping, &mapping_path, ids, definitions, errors);
+            }
+        }
+        SchemaForm::Ref(id) => {
+            if !definitions.contains_key(id) && !ids.contains_key(id.as_str()) {
+                errors.push(DefinitionError::new(
+                    &format!("{}/ref", path),
+                    format!("Unknown ref \"{}\"", id),
+                ));
+            }
+        }
+    }
+}
+
+fn expect_object<'a>(
+    input: &'a serde_json::Value,
+    path: &str,
+) -> Result<&'a serde_json::Map<String, serde_json::Value>, DefinitionError> {
+    match input {
+        serde_json::Value::Object(object) => Ok(object),
+        _ => Err(DefinitionError::new(path, "Expected an object")),
+    }
+}
+
+fn expect_string<'a>(input: &'a serde_json::Value, path: &str) -> Result<&'a str, DefinitionError> {
+    match input {
+        serde_json::Value::String(value) => Ok(value),
+        _ => Err(DefinitionError::new(path, "Expected a string")),
+    }
+}
+
+fn expect_bool(input: &serde_json::Value, path: &str) -> Result<bool, DefinitionError> {
+    match input {
+        serde_json::Value::Bool(value) => Ok(*value),
+        _ => Err(DefinitionError::new(path, "Expected a boolean")),
+    }
+}
+
+#[cfg(test)]
+mod definition_tests {
+    use super::*;
+
+    const APP_DEFINITION: &str = include_str!("../../../../tests/test-files/AppDefinition.json");
+
+    fn definition(input: serde_json::Value) -> AppDefinition {
+        AppDefinition::from_json(&input).unwrap()
+    }
+
+    #[test]
+    fn parse_test() {
+        let app = AppDefinition::from_json_str(APP_DEFINITION).unwrap();
+        assert_eq!(app.schema_version, "0.0.8");
+        assert_eq!(app.info.as_ref().unwrap().version.as_deref(), Some("20"));
+        assert!(app.validate().is_empty(), "{:?}", app.validate());
+
+        let get_book = &app.procedures["books.getBook"];
+        assert_eq!(get_book.transport, Transport::Http);
+        assert_eq!(get_book.http_method(), reqwest::Method::GET);
+        assert_eq!(get_book.params.as_deref(), Some("BookParams"));
+        let connection = &app.procedures["books.createConnection"];
+        assert_eq!(connection.transport, Transport::Ws);
+        assert_eq!(connection.method, None);
+        assert!(app.procedures["books.watchBook"].is_event_stream);
+
+        let book = &app.definitions["Book"];
+        assert_eq!(book.id(), Some("Book"));
+        match &book.form {
+            SchemaForm::Properties(schema) => {
+                assert_eq!(
+                    schema.properties["createdAt"].form,
+                    SchemaForm::Type(ScalarType::Timestamp)
+                );
+            }
+            form => panic!("Unexpected form {:?}", form),
+        }
+        assert!(app.resolve_ref("RecursiveObject").is_some());
+        assert_eq!(
+            app.resolve_ref("NestedObject").unwrap().id(),
+            Some("NestedObject")
+        );
+
+        let json: serde_json::Value = serde_json::from_str(APP_DEFINITION).unwrap();
+        assert_eq!(app.to_json(), json);
+    }
+
+    #[test]
+    fn parse_error_test() {
+        let err = AppDefinition::from_json(&serde_json::json!({
+            "schemaVersion": "0.0.8",
+            "procedures": {},
+            "definitions": {
+                "User": {
+                    "properties": {
+                        "tags": { "elements": { "type": "text" } }
+                    }
+                }
+            }
+        }))
+        .unwrap_err();
+        assert_eq!(err.path, "/definitions/User/properties/tags/elements/type");
+        assert_eq!(err.message, "Unknown type \"text\"");
+
+        let err = AppDefinition::from_json(&serde_json::json!({
+            "schemaVersion": "0.0.8",
+            "procedures": {},
+            "definitions": { "User": { "type": "string", "enum": ["A"] } }
+        }))
+        .unwrap_err();
+        assert_eq!(err.path, "/definitions/User");
+    }
+
+    #[test]
+    fn validate_test() {
+        let app = definition(serde_json::json!({
+            "schemaVersion": "0.0.8",
+            "procedures": {
+                "users": { "transport": "http", "path": "/users" },
+                "users.getUser": {
+                    "transport": "http",
+                    "path": "/users/get-user",
+                    "params": "UserParams",
+                    "response": "User"
+                },
+                "users.findUser": { "transport": "http", "path": "/users/get-user" },
+                "users.watchUser": { "transport": "http", "path": "users/watch-user" }
+            },
+            "definitions": {
+                "User": {
+                    "properties": {
+                        "role": { "enum": ["ADMIN", "ADMIN"] },
+                        "friends": { "elements": { "ref": "Friend" } },
+                        "event": {
+                            "discriminator": "type",
+                            "mapping": {
+                                "CREATED": { "properties": { "type": { "type": "string" } } },
+                                "DELETED": { "type": "string" }
+                            }
+                        }
+                    },
+                    "metadata": { "id": "Person" }
+                }
+            }
+        }));
+        let errors: Vec<String> = app.validate().iter().map(|err| err.to_string()).collect();
+        assert_eq!(
+            errors,
+            vec![
+                "/procedures/users: \"users\" is both a procedure and a service",
+                "/procedures/users.getUser/path: POST /users/get-user is already used by \"users.findUser\"",
+                "/procedures/users.getUser/params: Unknown type \"UserParams\"",
+                "/procedures/users.watchUser/path: Path must start with \"/\"",
+                "/definitions/User/metadata/id: Expected \"Person\" to match the definition name",
+                "/definitions/User/properties/event/mapping/CREATED: Mapping cannot define the discriminator \"type\"",
+                "/definitions/User/properties/event/mapping/DELETED: Mapping must be a properties schema",
+                "/definitions/User/properties/friends/elements/ref: Unknown ref \"Friend\"",
+                "/definitions/User/properties/role/enum/1: Duplicate enum value \"ADMIN\"",
+            ]
+        );
+    }
+}
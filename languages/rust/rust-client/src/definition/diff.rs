@@ -0,0 +1,850 @@
+use std::collections::{BTreeMap, HashSet};
+
+use super::{escape_pointer, AppDefinition, ProcedureDefinition, SchemaForm, TypeDefinition};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeSeverity {
+    /// Clients generated from the old definition keep working
+    Safe,
+    /// Clients generated from the old definition can fail against the new one
+    Breaking,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    ProcedureAdded,
+    ProcedureRemoved,
+    ProcedureDeprecated,
+    MethodChanged,
+    PathChanged,
+    TransportChanged,
+    EventStreamChanged,
+    ParamsChanged,
+    ResponseChanged,
+    TypeChanged,
+    NullableChanged,
+    FieldAdded,
+    FieldRemoved,
+    FieldRequired,
+    FieldOptional,
+    EnumValueAdded,
+    EnumValueRemoved,
+    DiscriminatorChanged,
+    MappingAdded,
+    MappingRemoved,
+}
+
+/// A difference between two app definitions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefinitionChange {
+    pub kind: ChangeKind,
+    pub severity: ChangeSeverity,
+    /// JSON pointer to the changed value. Points into the new definition unless the value was removed.
+    pub path: String,
+    pub message: String,
+}
+
+/// Every change between two app definitions, ordered by path
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DefinitionDiff {
+    pub changes: Vec<DefinitionChange>,
+}
+
+/// Which way a type travels. Params must still be accepted by the new server and responses must
+/// still be understood by old clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Direction {
+    Params,
+    Response,
+}
+
+impl ChangeSeverity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Safe => "safe",
+            Self::Breaking => "breaking",
+        }
+    }
+}
+
+impl ChangeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ProcedureAdded => "procedure_added",
+            Self::ProcedureRemoved => "procedure_removed",
+            Self::ProcedureDeprecated => "procedure_deprecated",
+            Self::MethodChanged => "method_changed",
+            Self::PathChanged => "path_changed",
+            Self::TransportChanged => "transport_changed",
+            Self::EventStreamChanged => "event_stream_changed",
+            Self::ParamsChanged => "params_changed",
+            Self::ResponseChanged => "response_changed",
+            Self::TypeChanged => "type_changed",
+            Self::NullableChanged => "nullable_changed",
+            Self::FieldAdded => "field_added",
+            Self::FieldRemoved => "field_removed",
+            Self::FieldRequired => "field_required",
+            Self::FieldOptional => "field_optional",
+            Self::EnumValueAdded => "enum_value_added",
+            Self::EnumValueRemoved => "enum_value_removed",
+            Self::DiscriminatorChanged => "discriminator_changed",
+            Self::MappingAdded => "mapping_added",
+            Self::MappingRemoved => "mapping_removed",
+        }
+    }
+}
+
+impl DefinitionChange {
+    pub fn is_breaking(&self) -> bool {
+        self.severity == ChangeSeverity::Breaking
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "kind": self.kind.name(),
+            "severity": self.severity.name(),
+            "path": self.path,
+            "message": self.message,
+        })
+    }
+}
+
+impl std::fmt::Display for DefinitionChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] {}: {}",
+            self.severity.name(),
+            self.path,
+            self.message
+        )
+    }
+}
+
+impl DefinitionDiff {
+    /// Compare the definition of a deployed server (`old`) with the one that will replace it (`new`)
+    pub fn compare(old: &AppDefinition, new: &AppDefinition) -> Self {
+        let mut context = DiffContext {
+            old,
+            new,
+            visited: HashSet::new(),
+            compared: HashSet::new(),
+            changes: Vec::new(),
+        };
+        for (name, old_procedure) in &old.procedures {
+            let path = format!("/procedures/{}", escape_pointer(name));
+            match new.procedures.get(name) {
+                Some(new_procedure) => {
+                    context.compare_procedure(name, &path, old_procedure, new_procedure)
+                }
+                None => context.push(
+                    ChangeKind::ProcedureRemoved,
+                    ChangeSeverity::Breaking,
+                    &path,
+                    format!("Procedure \"{}\" was removed", name),
+                ),
+            }
+        }
+        for name in new.procedures.keys() {
+            if !old.procedures.contains_key(name) {
+                context.push(
+                    ChangeKind::ProcedureAdded,
+                    ChangeSeverity::Safe,
+                    &format!("/procedures/{}", escape_pointer(name)),
+                    format!("Procedure \"{}\" was added", name),
+                );
+            }
+        }
+        // definitions that no procedure uses may still be received elsewhere, e.g. by webhooks
+        for name in old.definitions.keys() {
+            let is_used = [Direction::Params, Direction::Response]
+                .into_iter()
+                .any(|direction| context.compared.contains(&(name.as_str(), direction)));
+            if new.definitions.contains_key(name) && !is_used {
+                context.compare_named(name, name, Direction::Response);
+            }
+        }
+        let mut changes = context.changes;
+        changes.sort_by(|a, b| {
+            (&a.path, &a.message)
+                .cmp(&(&b.path, &b.message))
+                .then(b.severity.cmp(&a.severity))
+        });
+        // types shared by params and responses are only reported once, with the worst severity
+        changes.dedup_by(|a, b| a.path == b.path && a.message == b.message);
+        Self { changes }
+    }
+
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(|change| change.is_breaking())
+    }
+
+    pub fn breaking_changes(&self) -> impl Iterator<Item = &DefinitionChange> {
+        self.changes.iter().filter(|change| change.is_breaking())
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "breaking": self.is_breaking(),
+            "changes": self.changes.iter().map(|change| change.to_json()).collect::<Vec<_>>(),
+        })
+    }
+}
+
+struct DiffContext<'a> {
+    old: &'a AppDefinition,
+    new: &'a AppDefinition,
+    /// Pairs of types or refs that have already been compared, so that recursive types terminate
+    visited: HashSet<(String, String, Direction)>,
+    /// Definitions used by procedures, directly or through refs, and the direction they are used in
+    compared: HashSet<(&'a str, Direction)>,
+    changes: Vec<DefinitionChange>,
+}
+
+impl<'a> DiffContext<'a> {
+    fn push(
+        &mut self,
+        kind: ChangeKind,
+        severity: ChangeSeverity,
+        path: &str,
+        message: impl Into<String>,
+    ) {
+        self.changes.push(DefinitionChange {
+            kind,
+            severity,
+            path: path.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn compare_procedure(
+        &mut self,
+        name: &str,
+        path: &str,
+        old: &'a ProcedureDefinition,
+        new: &'a ProcedureDefinition,
+    ) {
+        if old.transport != new.transport {
+            self.push(
+                ChangeKind::TransportChanged,
+                ChangeSeverity::Breaking,
+                &format!("{}/transport", path),
+                format!(
+                    "Transport of \"{}\" changed from \"{}\" to \"{}\"",
+                    name,
+                    old.transport.name(),
+                    new.transport.name()
+                ),
+            );
+        }
+        if old.path != new.path {
+            self.push(
+                ChangeKind::PathChanged,
+                ChangeSeverity::Breaking,
+                &format!("{}/path", path),
+                format!(
+                    "Path of \"{}\" changed from \"{}\" to \"{}\"",
+                    name, old.path, new.path
+                ),
+            );
+        }
+        if old.http_method() != new.http_method() {
+            self.push(
+                ChangeKind::MethodChanged,
+                ChangeSeverity::Breaking,
+                &format!("{}/method", path),
+                format!(
+                    "Method of \"{}\" changed from {} to {}",
+                    name,
+                    old.http_method(),
+                    new.http_method()
+                ),
+            );
+        }
+        if old.is_event_stream != new.is_event_stream {
+            self.push(
+                ChangeKind::EventStreamChanged,
+                ChangeSeverity::Breaking,
+                &format!("{}/isEventStream", path),
+                match new.is_event_stream {
+                    true => format!("\"{}\" is now an event stream", name),
+                    false => format!("\"{}\" is no longer an event stream", name),
+                },
+            );
+        }
+        if !old.is_deprecated && new.is_deprecated {
+            self.push(
+                ChangeKind::ProcedureDeprecated,
+                ChangeSeverity::Safe,
+                &format!("{}/isDeprecated", path),
+                format!("\"{}\" was deprecated", name),
+            );
+        }
+        let types = [
+            (
+                "params",
+                ChangeKind::ParamsChanged,
+                Direction::Params,
+                &old.params,
+                &new.params,
+            ),
+            (
+                "response",
+                ChangeKind::ResponseChanged,
+                Direction::Response,
+                &old.response,
+                &new.response,
+            ),
+        ];
+        for (key, kind, direction, old_type, new_type) in types {
+            match (old_type, new_type) {
+                (Some(old_type), Some(new_type)) => {
+                    self.compare_named(old_type, new_type, direction);
+                }
+                (None, None) => {}
+                (old_type, new_type) => self.push(
+                    kind,
+                    ChangeSeverity::Breaking,
+                    &format!("{}/{}", path, key),
+                    format!(
+                        "{} of \"{}\" changed from {} to {}",
+                        if key == "params" {
+                            "Params"
+                        } else {
+                            "Response"
+                        },
+                        name,
+                        old_type.as_deref().unwrap_or("nothing"),
+                        new_type.as_deref().unwrap_or("nothing")
+                    ),
+                ),
+            }
+        }
+    }
+
+    fn compare_named(&mut self, old_type: &'a str, new_type: &'a str, direction: Direction) {
+        self.compared.insert((new_type, direction));
+        if !self
+            .visited
+            .insert((old_type.to_string(), new_type.to_string(), direction))
+        {
+            return;
+        }
+        let (old_schema, new_schema) = match (
+            self.old.definitions.get(old_type),
+            self.new.definitions.get(new_type),
+        ) {
+            (Some(old_schema), Some(new_schema)) => (old_schema, new_schema),
+            // unknown types are reported by `AppDefinition::validate`
+            _ => return,
+        };
+        let path = format!("/definitions/{}", escape_pointer(new_type));
+        self.compare_schema(&path, old_schema, new_schema, direction);
+    }
+
+    fn compare_schema(
+        &mut self,
+        path: &str,
+        old: &'a TypeDefinition,
+        new: &'a TypeDefinition,
+        direction: Direction,
+    ) {
+        // refs are only followed once per pair so that recursive types terminate
+        let ref_key = match (&old.form, &new.form) {
+            (SchemaForm::Ref(old_id), SchemaForm::Ref(new_id)) => {
+                Some((old_id.clone(), new_id.clone(), direction))
+            }
+            (SchemaForm::Ref(old_id), _) => Some((old_id.clone(), path.to_string(), direction)),
+            (_, SchemaForm::Ref(new_id)) => Some((path.to_string(), new_id.clone(), direction)),
+            _ => None,
+        };
+        let mut refs = Vec::new();
+        let resolved = (
+            resolve(self.old, old, &mut refs),
+            resolve(self.new, new, &mut refs),
+        );
+        for id in refs {
+            self.compared.insert((id, direction));
+        }
+        let ((old, old_nullable), (new, new_nullable)) = match resolved {
+            (Some(old), Some(new)) => (old, new),
+            // unresolved refs are reported by `AppDefinition::validate`
+            _ => return,
+        };
+        if old_nullable != new_nullable {
+            // old clients can't handle null responses and new servers may reject null params
+            let severity = match (direction, new_nullable) {
+                (Direction::Response, true) | (Direction::Params, false) => {
+                    ChangeSeverity::Breaking
+                }
+                _ => ChangeSeverity::Safe,
+            };
+            self.push(
+                ChangeKind::NullableChanged,
+                severity,
+                path,
+                match new_nullable {
+                    true => "Became nullable",
+                    false => "Is no longer nullable",
+                },
+            );
+        }
+        if let Some(key) = ref_key {
+            if !self.visited.insert(key) {
+                return;
+            }
+        }
+        match (&old.form, &new.form) {
+            (SchemaForm::Empty, SchemaForm::Empty) => {}
+            (SchemaForm::Type(old_type), SchemaForm::Type(new_type)) => {
+                if old_type != new_type {
+                    self.push(
+                        ChangeKind::TypeChanged,
+                        ChangeSeverity::Breaking,
+                        path,
+                        format!(
+                            "Type changed from {} to {}",
+                            old_type.name(),
+                            new_type.name()
+                        ),
+                    );
+                }
+            }
+            (SchemaForm::Enum(old_values), SchemaForm::Enum(new_values)) => {
+                for value in old_values {
+                    if !new_values.contains(value) {
+                        self.push(
+                            ChangeKind::EnumValueRemoved,
+                            ChangeSeverity::Breaking,
+                            path,
+                            format!("Enum value \"{}\" was removed", value),
+                        );
+                    }
+                }
+                for value in new_values {
+                    if !old_values.contains(value) {
+                        // old clients can't parse values they don't know about
+                        let severity = match direction {
+                            Direction::Response => ChangeSeverity::Breaking,
+                            Direction::Params => ChangeSeverity::Safe,
+                        };
+                        self.push(
+                            ChangeKind::EnumValueAdded,
+                            severity,
+                            path,
+                            format!("Enum value \"{}\" was added", value),
+                        );
+                    }
+                }
+            }
+            (SchemaForm::Elements(old_inner), SchemaForm::Elements(new_inner)) => self
+                .compare_schema(
+                    &format!("{}/elements", path),
+                    old_inner,
+                    new_inner,
+                    direction,
+                ),
+            (SchemaForm::Values(old_inner), SchemaForm::Values(new_inner)) => {
+                self.compare_schema(&format!("{}/values", path), old_inner, new_inner, direction)
+            }
+            (SchemaForm::Properties(old_schema), SchemaForm::Properties(new_schema)) => {
+                self.compare_properties(
+                    path,
+                    (&old_schema.properties, &old_schema.optional_properties),
+                    (&new_schema.properties, &new_schema.optional_properties),
+                    new_schema.is_strict,
+                    direction,
+                );
+            }
+            (SchemaForm::Discriminator(old_schema), SchemaForm::Discriminator(new_schema)) => {
+                if old_schema.discriminator != new_schema.discriminator {
+                    self.push(
+                        ChangeKind::DiscriminatorChanged,
+                        ChangeSeverity::Breaking,
+                        &format!("{}/discriminator", path),
+                        format!(
+                            "Discriminator changed from \"{}\" to \"{}\"",
+                            old_schema.discriminator, new_schema.discriminator
+                        ),
+                    );
+                }
+                for (tag, old_mapping) in &old_schema.mapping {
+                    let mapping_path = format!("{}/mapping/{}", path, escape_pointer(tag));
+                    match new_schema.mapping.get(tag) {
+                        Some(new_mapping) => {
+                            self.compare_schema(&mapping_path, old_mapping, new_mapping, direction)
+                        }
+                        None => {
+                            let severity = match direction {
+                                Direction::Params => ChangeSeverity::Breaking,
+                                Direction::Response => ChangeSeverity::Safe,
+                            };
+                            self.push(
+                                ChangeKind::MappingRemoved,
+                                severity,
+                                &mapping_path,
+                                format!("\"{}\" was removed", tag),
+                            );
+                        }
+                    }
+                }
+                for tag in new_schema.mapping.keys() {
+                    if !old_schema.mapping.contains_key(tag) {
+                        let severity = match direction {
+                            Direction::Params => ChangeSeverity::Safe,
+                            Direction::Response => ChangeSeverity::Breaking,
+                        };
+                        self.push(
+                            ChangeKind::MappingAdded,
+                            severity,
+                            &format!("{}/mapping/{}", path, escape_pointer(tag)),
+                            format!("\"{}\" was added", tag),
+                        );
+                    }
+                }
+            }
+            _ => self.push(
+                ChangeKind::TypeChanged,
+                ChangeSeverity::Breaking,
+                path,
+                format!(
+                    "Type changed from {} to {}",
+                    form_name(&old.form),
+                    form_name(&new.form)
+                ),
+            ),
+        }
+    }
+
+    fn compare_properties(
+        &mut self,
+        path: &str,
+        old: (
+            &'a BTreeMap<String, TypeDefinition>,
+            &'a BTreeMap<String, TypeDefinition>,
+        ),
+        new: (
+            &'a BTreeMap<String, TypeDefinition>,
+            &'a BTreeMap<String, TypeDefinition>,
+        ),
+        is_strict: bool,
+        direction: Direction,
+    ) {
+        let (old_required, old_optional) = old;
+        let (new_required, new_optional) = new;
+        for (key, old_property) in old_required.iter().chain(old_optional.iter()) {
+            let was_required = old_required.contains_key(key);
+            let (new_property, is_required) = match new_required.get(key) {
+                Some(property) => (property, true),
+                None => match new_optional.get(key) {
+                    Some(property) => (property, false),
+                    None => {
+                        // old clients rely on required response fields, and strict params
+                        // reject fields that old clients still send
+                        let is_breaking = match direction {
+                            Direction::Response => was_required,
+                            Direction::Params => is_strict,
+                        };
+                        self.push(
+                            ChangeKind::FieldRemoved,
+                            if is_breaking {
+                                ChangeSeverity::Breaking
+                            } else {
+                                ChangeSeverity::Safe
+                            },
+                            &format!("{}/properties/{}", path, escape_pointer(key)),
+                            format!("Field \"{}\" was removed", key),
+                        );
+                        continue;
+                    }
+                },
+            };
+            let property_path = format!(
+                "{}/{}/{}",
+                path,
+                if is_required {
+                    "properties"
+                } else {
+                    "optionalProperties"
+                },
+                escape_pointer(key)
+            );
+            match (was_required, is_required) {
+                (false, true) => {
+                    let severity = match direction {
+                        Direction::Params => ChangeSeverity::Breaking,
+                        Direction::Response => ChangeSeverity::Safe,
+                    };
+                    self.push(
+                        ChangeKind::FieldRequired,
+                        severity,
+                        &property_path,
+                        format!("Field \"{}\" is now required", key),
+                    );
+                }
+                (true, false) => {
+                    let severity = match direction {
+                        Direction::Params => ChangeSeverity::Safe,
+                        Direction::Response => ChangeSeverity::Breaking,
+                    };
+                    self.push(
+                        ChangeKind::FieldOptional,
+                        severity,
+                        &property_path,
+                        format!("Field \"{}\" is now optional", key),
+                    );
+                }
+                _ => {}
+            }
+            self.compare_schema(&property_path, old_property, new_property, direction);
+        }
+        for (key, is_required) in new_required
+            .keys()
+            .map(|key| (key, true))
+            .chain(new_optional.keys().map(|key| (key, false)))
+        {
+            if old_required.contains_key(key) || old_optional.contains_key(key) {
+                continue;
+            }
+            // old clients don't send new required params
+            let severity = match (direction, is_required) {
+                (Direction::Params, true) => ChangeSeverity::Breaking,
+                _ => ChangeSeverity::Safe,
+            };
+            self.push(
+                ChangeKind::FieldAdded,
+                severity,
+                &format!(
+                    "{}/{}/{}",
+                    path,
+                    if is_required {
+                        "properties"
+                    } else {
+                        "optionalProperties"
+                    },
+                    escape_pointer(key)
+                ),
+                format!(
+                    "{} field \"{}\" was added",
+                    if is_required { "Required" } else { "Optional" },
+                    key
+                ),
+            );
+        }
+    }
+}
+
+/// Follow refs to the schema they point to. Refs that are nullable make the result nullable.
+/// The ids of the refs that were followed are added to `refs`.
+fn resolve<'a>(
+    app: &'a AppDefinition,
+    schema: &'a TypeDefinition,
+    refs: &mut Vec<&'a str>,
+) -> Option<(&'a TypeDefinition, bool)> {
+    let mut target = schema;
+    let mut is_nullable = schema.is_nullable;
+    let mut visited = HashSet::new();
+    while let SchemaForm::Ref(id) = &target.form {
+        if !visited.insert(id.as_str()) {
+            return None;
+        }
+        refs.push(id);
+        target = app.resolve_ref(id)?;
+        is_nullable = is_nullable || target.is_nullable;
+    }
+    Some((target, is_nullable))
+}
+
+fn form_name(form: &SchemaForm) -> &'static str {
+    match form {
+        SchemaForm::Empty => "any",
+        SchemaForm::Type(scalar) => scalar.name(),
+        SchemaForm::Enum(_) => "enum",
+        SchemaForm::Elements(_) => "elements",
+        SchemaForm::Properties(_) => "properties",
+        SchemaForm::Values(_) => "values",
+        SchemaForm::Discriminator(_) => "discriminator",
+        SchemaForm::Ref(_) => "ref",
+    }
+}
+
+#[cfg(test)]
+mod diff_tests {
+    use super::*;
+
+    const APP_DEFINITION: &str = include_str!("../../../../../tests/test-files/AppDefinition.json");
+
+    fn diff(update: impl FnOnce(&mut serde_json::Value)) -> Vec<String> {
+        let old: serde_json::Value = serde_json::from_str(APP_DEFINITION).unwrap();
+        let mut new = old.clone();
+        update(&mut new);
+        DefinitionDiff::compare(
+            &AppDefinition::from_json(&old).unwrap(),
+            &AppDefinition::from_json(&new).unwrap(),
+        )
+        .changes
+        .iter()
+        .map(|change| change.to_string())
+        .collect()
+    }
+
+    #[test]
+    fn unchanged_test() {
+        assert!(diff(|_| {}).is_empty());
+    }
+
+    #[test]
+    fn procedure_changes_test() {
+        let changes = diff(|new| {
+            let procedures = new["procedures"].as_object_mut().unwrap();
+            procedures.remove("sendObject");
+            procedures["books.getBook"]["method"] = "post".into();
+            procedures["books.createBook"]
+                .as_object_mut()
+                .unwrap()
+                .remove("response");
+            procedures.insert(
+                "books.deleteBook".to_string(),
+                serde_json::json!({ "transport": "http", "path": "/books/delete-book" }),
+            );
+        });
+        assert_eq!(
+            changes,
+            vec![
+                "[breaking] /procedures/books.createBook/response: Response of \"books.createBook\" changed from Book to nothing",
+                "[safe] /procedures/books.deleteBook: Procedure \"books.deleteBook\" was added",
+                "[breaking] /procedures/books.getBook/method: Method of \"books.getBook\" changed from GET to POST",
+                "[breaking] /procedures/sendObject: Procedure \"sendObject\" was removed",
+            ]
+        );
+    }
+
+    #[test]
+    fn type_changes_test() {
+        let changes = diff(|new| {
+            let definitions = &mut new["definitions"];
+            definitions["BookParams"]["properties"]["limit"] =
+                serde_json::json!({ "type": "uint32" });
+            definitions["Book"]["properties"]["name"] = serde_json::json!({ "type": "int32" });
+            definitions["Book"]["optionalProperties"] =
+                serde_json::json!({ "subtitle": { "type": "string" } });
+            let every_type = &mut definitions["ObjectWithEveryType"]["properties"];
+            every_type["enum"]["enum"] = serde_json::json!(["FOO", "BAR"]);
+            every_type["discriminator"]["discriminator"] = "kind".into();
+        });
+        assert_eq!(
+            changes,
+            vec![
+                "[safe] /definitions/Book/optionalProperties/subtitle: Optional field \"subtitle\" was added",
+                "[breaking] /definitions/Book/properties/name: Type changed from string to int32",
+                "[breaking] /definitions/BookParams/properties/limit: Required field \"limit\" was added",
+                "[breaking] /definitions/ObjectWithEveryType/properties/discriminator/discriminator: Discriminator changed from \"typeName\" to \"kind\"",
+                "[breaking] /definitions/ObjectWithEveryType/properties/enum: Enum value \"BAZ\" was removed",
+            ]
+        );
+    }
+
+    #[test]
+    fn direction_test() {
+        let changes = diff(|new| {
+            let definitions = &mut new["definitions"];
+            definitions["Book"]["properties"]["pages"] = serde_json::json!({ "type": "uint32" });
+            definitions["NestedObject"]["properties"]["tag"] =
+                serde_json::json!({ "type": "string" });
+            definitions["RecursiveObject"]["properties"]["left"]["isNullable"] = false.into();
+        });
+        assert_eq!(
+            changes,
+            vec![
+                // Book is the params of books.createBook
+                "[breaking] /definitions/Book/properties/pages: Required field \"pages\" was added",
+                "[breaking] /definitions/NestedObject/properties/tag: Required field \"tag\" was added",
+                // unused definitions are compared as responses
+                "[safe] /definitions/RecursiveObject/properties/left: Is no longer nullable",
+            ]
+        );
+
+        // required fields can be added to responses and removed from params
+        let app = |params: serde_json::Value, response: serde_json::Value| {
+            AppDefinition::from_json(&serde_json::json!({
+                "schemaVersion": "0.0.8",
+                "procedures": {
+                    "getUser": {
+                        "transport": "http",
+                        "path": "/get-user",
+                        "params": "UserParams",
+                        "response": "User"
+                    }
+                },
+                "definitions": { "UserParams": params, "User": response }
+            }))
+            .unwrap()
+        };
+        let result = DefinitionDiff::compare(
+            &app(
+                serde_json::json!({ "properties": { "id": { "type": "string" } } }),
+                serde_json::json!({ "properties": { "id": { "type": "string" } } }),
+            ),
+            &app(
+                serde_json::json!({ "properties": {} }),
+                serde_json::json!({
+                    "properties": { "id": { "type": "string" }, "name": { "type": "string" } }
+                }),
+            ),
+        );
+        assert!(!result.is_breaking());
+        assert_eq!(result.changes.len(), 2);
+        let definition = AppDefinition::from_json_str(APP_DEFINITION).unwrap();
+        let mut new = definition.clone();
+        new.procedures.get_mut("books.getBook").unwrap().params = None;
+        new.definitions.remove("BookParams");
+        let result = DefinitionDiff::compare(&definition, &new);
+        assert!(result.is_breaking());
+        assert_eq!(
+            result.to_json()["changes"][0],
+            serde_json::json!({
+                "kind": "params_changed",
+                "severity": "breaking",
+                "path": "/procedures/books.getBook/params",
+                "message": "Params of \"books.getBook\" changed from BookParams to nothing",
+            })
+        );
+    }
+
+    #[test]
+    fn ref_test() {
+        let app = |values: serde_json::Value| {
+            AppDefinition::from_json(&serde_json::json!({
+                "schemaVersion": "0.0.8",
+                "procedures": {
+                    "getUsers": {
+                        "transport": "http",
+                        "path": "/get-users",
+                        "params": "UserParams",
+                        "response": "UserList"
+                    }
+                },
+                "definitions": {
+                    "UserParams": { "properties": { "role": { "ref": "Role" } } },
+                    "UserList": { "properties": { "total": { "type": "uint32" } } },
+                    "Role": { "enum": values }
+                }
+            }))
+            .unwrap()
+        };
+        // types that are only used by params through a ref aren't compared again as responses
+        let result = DefinitionDiff::compare(
+            &app(serde_json::json!(["ADMIN", "USER"])),
+            &app(serde_json::json!(["ADMIN", "USER", "GUEST"])),
+        );
+        assert_eq!(
+            result
+                .changes
+                .iter()
+                .map(|change| change.to_string())
+                .collect::<Vec<_>>(),
+            vec!["[safe] /definitions/UserParams/properties/role: Enum value \"GUEST\" was added"]
+        );
+        assert!(!result.is_breaking());
+    }
+}
@@ -0,0 +1,231 @@
+use serde_json::{Map, Value};
+
+/// The keys of a procedure that affect how it is called
+const PROCEDURE_KEYS: [&str; 6] = [
+    "transport",
+    "path",
+    "method",
+    "params",
+    "response",
+    "isEventStream",
+];
+
+/// Hash of the parts of an app definition that affect the wire format.
+/// Descriptions, deprecation notes and other metadata are ignored.
+///
+/// The Rust generator embeds this hash in generated clients as
+/// `ArriProcedureRegistry::DEFINITION_HASH`, so both must stay in sync.
+pub fn definition_hash(definition: &Value) -> String {
+    let mut procedures = Map::new();
+    if let Some(input) = definition.get("procedures").and_then(Value::as_object) {
+        for (name, procedure) in input {
+            let mut fields = Map::new();
+            for key in PROCEDURE_KEYS {
+                if let Some(value) = procedure.get(key) {
+                    fields.insert(key.to_string(), value.clone());
+                }
+            }
+            procedures.insert(name.clone(), Value::Object(fields));
+        }
+    }
+    let mut definitions = Map::new();
+    if let Some(input) = definition.get("definitions").and_then(Value::as_object) {
+        for (name, schema) in input {
+            definitions.insert(name.clone(), fingerprint(schema));
+        }
+    }
+    let mut fingerprint = Map::new();
+    fingerprint.insert("definitions".to_string(), Value::Object(definitions));
+    fingerprint.insert("procedures".to_string(), Value::Object(procedures));
+    fnv1a(&canonical_json(&Value::Object(fingerprint)))
+}
+
+/// Hash of a single type definition, ignoring its metadata and the metadata of nested schemas
+pub fn type_definition_hash(schema: &Value) -> String {
+    fnv1a(&canonical_json(&fingerprint(schema)))
+}
+
+/// A copy of the schema without `metadata`.
+/// Only schema positions are visited, so a property called "metadata" is kept.
+fn fingerprint(schema: &Value) -> Value {
+    let Some(input) = schema.as_object() else {
+        return schema.clone();
+    };
+    let mut output = Map::new();
+    for (key, value) in input {
+        let value = match key.as_str() {
+            "metadata" => continue,
+            "elements" | "values" => fingerprint(value),
+            "properties" | "optionalProperties" | "mapping" => match value.as_object() {
+                Some(fields) => Value::Object(
+                    fields
+                        .iter()
+                        .map(|(name, field)| (name.clone(), fingerprint(field)))
+                        .collect(),
+                ),
+                None => value.clone(),
+            },
+            _ => value.clone(),
+        };
+        output.insert(key.clone(), value);
+    }
+    Value::Object(output)
+}
+
+/// JSON with sorted keys and no whitespace, the same as `canonicalJson` in the Rust generator.
+/// Keys are sorted by UTF-16 code units like JavaScript's `sort()`, and floats are written
+/// without a trailing `.0` like `JSON.stringify()`.
+fn canonical_json(value: &Value) -> String {
+    let mut output = String::new();
+    write_canonical_json(value, &mut output);
+    output
+}
+
+fn write_canonical_json(value: &Value, output: &mut String) {
+    match value {
+        Value::Array(items) => {
+            output.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                write_canonical_json(item, output);
+            }
+            output.push(']');
+        }
+        Value::Object(fields) => {
+            let mut keys = fields.keys().collect::<Vec<_>>();
+            keys.sort_by(|a, b| a.encode_utf16().cmp(b.encode_utf16()));
+            output.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                output.push_str(&Value::String(key.clone()).to_string());
+                output.push(':');
+                write_canonical_json(&fields[key], output);
+            }
+            output.push('}');
+        }
+        Value::Number(number) if number.is_f64() => {
+            output.push_str(&number.as_f64().unwrap_or_default().to_string())
+        }
+        _ => output.push_str(&value.to_string()),
+    }
+}
+
+/// 64 bit FNV-1a of the UTF-8 bytes as 16 hex characters
+fn fnv1a(input: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in input.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod hash_tests {
+    use super::*;
+
+    #[test]
+    fn fnv1a_test() {
+        assert_eq!(fnv1a(""), "cbf29ce484222325");
+        assert_eq!(fnv1a("a"), "af63dc4c8601ec8c");
+    }
+
+    #[test]
+    fn canonical_json_test() {
+        let value = serde_json::json!({
+            "b": [1, 1.0, 1.5, null, true],
+            "a": { "｡": "y\n\"", "😀": "x" },
+        });
+        // "😀" is sorted first since JavaScript compares UTF-16 code units
+        assert_eq!(
+            canonical_json(&value),
+            r#"{"a":{"😀":"x","｡":"y\n\""},"b":[1,1,1.5,null,true]}"#
+        );
+    }
+
+    #[test]
+    fn metadata_test() {
+        let schema = serde_json::json!({
+            "properties": {
+                "metadata": { "type": "string", "metadata": { "description": "A" } },
+                "tags": { "elements": { "type": "string" } },
+            },
+            "metadata": { "id": "Post" },
+        });
+        let documented = serde_json::json!({
+            "properties": {
+                "metadata": { "type": "string", "metadata": { "description": "B" } },
+                "tags": { "elements": { "type": "string", "metadata": { "isDeprecated": true } } },
+            },
+            "metadata": { "id": "Post", "description": "A post" },
+        });
+        assert_eq!(
+            type_definition_hash(&schema),
+            type_definition_hash(&documented)
+        );
+        let changed = serde_json::json!({
+            "properties": {
+                "metadata": { "type": "string" },
+                "tags": { "elements": { "type": "int32" } },
+            },
+        });
+        assert_ne!(
+            type_definition_hash(&schema),
+            type_definition_hash(&changed)
+        );
+        let without_property = serde_json::json!({
+            "properties": { "tags": { "elements": { "type": "string" } } },
+        });
+        assert_ne!(
+            type_definition_hash(&schema),
+            type_definition_hash(&without_property)
+        );
+    }
+
+    #[test]
+    fn definition_hash_test() {
+        let input: Value = serde_json::from_str(include_str!(
+            "../../../../../tests/test-files/AppDefinition.json"
+        ))
+        .unwrap();
+        let mut documented = input.clone();
+        documented["procedures"]["sendObject"]["description"] = "Send an object".into();
+        documented["info"]["version"] = "21".into();
+        assert_eq!(definition_hash(&input), definition_hash(&documented));
+        let mut changed = input.clone();
+        changed["procedures"]["sendObject"]["method"] = "put".into();
+        assert_ne!(definition_hash(&input), definition_hash(&changed));
+    }
+
+    /// Hashes computed by the Rust generator, which generated clients embed
+    #[test]
+    fn generator_hash_test() {
+        let input: Value = serde_json::from_str(include_str!(
+            "../../../../../tests/test-files/AppDefinition.json"
+        ))
+        .unwrap();
+        assert_eq!(definition_hash(&input), "6eb85c4403cb6d1a");
+        let expected = [
+            ("Book", "6fa33869aa3824cb"),
+            ("BookParams", "5561f69d0c26d5c3"),
+            ("EmptyObject", "9922b3393c03b9e8"),
+            ("NestedObject", "2354ca52029d1dc8"),
+            ("ObjectWithEveryType", "e3a8ccaa24b13ee1"),
+            ("ObjectWithOptionalFields", "7afde2bf1dcd9f18"),
+            ("ObjectWithNullableFields", "203cf3c05f9d7f5e"),
+            ("RecursiveObject", "b1f6e570845c4951"),
+        ];
+        for (name, hash) in expected {
+            assert_eq!(
+                type_definition_hash(&input["definitions"][name]),
+                hash,
+                "{}",
+                name
+            );
+        }
+    }
+}
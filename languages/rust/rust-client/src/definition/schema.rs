@@ -0,0 +1,367 @@
+use std::collections::BTreeMap;
+
+use super::{expect_bool, expect_object, expect_string, DefinitionError};
+
+/// An Arri Type Definition. See `specifications/arri_type_definition.md`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeDefinition {
+    pub form: SchemaForm,
+    pub is_nullable: bool,
+    pub metadata: Option<TypeMetadata>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaForm {
+    /// Accepts any value
+    Empty,
+    Type(ScalarType),
+    Enum(Vec<String>),
+    Elements(Box<TypeDefinition>),
+    Properties(PropertiesSchema),
+    Values(Box<TypeDefinition>),
+    Discriminator(DiscriminatorSchema),
+    /// The id of a properties or discriminator schema
+    Ref(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScalarType {
+    Boolean,
+    String,
+    Timestamp,
+    Float32,
+    Float64,
+    Int8,
+    Uint8,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Int64,
+    Uint64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PropertiesSchema {
+    pub properties: BTreeMap<String, TypeDefinition>,
+    pub optional_properties: BTreeMap<String, TypeDefinition>,
+    /// Reject properties that aren't listed in `properties` or `optional_properties`
+    pub is_strict: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscriminatorSchema {
+    /// The name of the tag property
+    pub discriminator: String,
+    /// Properties schemas by tag value
+    pub mapping: BTreeMap<String, TypeDefinition>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TypeMetadata {
+    pub id: Option<String>,
+    pub description: Option<String>,
+    pub is_deprecated: bool,
+    pub deprecated_note: Option<String>,
+    /// Any other metadata keys
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+const FORM_KEYS: [&str; 7] = [
+    "type",
+    "enum",
+    "elements",
+    "properties",
+    "values",
+    "discriminator",
+    "ref",
+];
+
+impl ScalarType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "boolean" => Some(Self::Boolean),
+            "string" => Some(Self::String),
+            "timestamp" => Some(Self::Timestamp),
+            "float32" => Some(Self::Float32),
+            "float64" => Some(Self::Float64),
+            "int8" => Some(Self::Int8),
+            "uint8" => Some(Self::Uint8),
+            "int16" => Some(Self::Int16),
+            "uint16" => Some(Self::Uint16),
+            "int32" => Some(Self::Int32),
+            "uint32" => Some(Self::Uint32),
+            "int64" => Some(Self::Int64),
+            "uint64" => Some(Self::Uint64),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Boolean => "boolean",
+            Self::String => "string",
+            Self::Timestamp => "timestamp",
+            Self::Float32 => "float32",
+            Self::Float64 => "float64",
+            Self::Int8 => "int8",
+            Self::Uint8 => "uint8",
+            Self::Int16 => "int16",
+            Self::Uint16 => "uint16",
+            Self::Int32 => "int32",
+            Self::Uint32 => "uint32",
+            Self::Int64 => "int64",
+            Self::Uint64 => "uint64",
+        }
+    }
+}
+
+impl TypeDefinition {
+    pub fn new(form: SchemaForm) -> Self {
+        Self {
+            form,
+            is_nullable: false,
+            metadata: None,
+        }
+    }
+
+    /// `metadata.id` if it is set
+    pub fn id(&self) -> Option<&str> {
+        self.metadata.as_ref()?.id.as_deref()
+    }
+
+    pub fn is_deprecated(&self) -> bool {
+        self.metadata
+            .as_ref()
+            .is_some_and(|metadata| metadata.is_deprecated)
+    }
+
+    /// `path` is the JSON pointer of `input` used in error messages
+    pub fn from_json(input: &serde_json::Value, path: &str) -> Result<Self, DefinitionError> {
+        let object = expect_object(input, path)?;
+        let forms: Vec<&str> = FORM_KEYS
+            .iter()
+            .copied()
+            .filter(|key| object.contains_key(*key))
+            .collect();
+        if forms.len() > 1 {
+            return Err(DefinitionError::new(
+                path,
+                format!("Schema has more than one form: {}", forms.join(", ")),
+            ));
+        }
+        let child_path = |key: &str| format!("{}/{}", path, escape_pointer(key));
+        let form = if object.contains_key("optionalProperties") && forms.is_empty() {
+            SchemaForm::Properties(parse_properties(object, path)?)
+        } else {
+            match forms.first().copied() {
+                None => SchemaForm::Empty,
+                Some("type") => {
+                    let name = expect_string(&object["type"], &child_path("type"))?;
+                    match ScalarType::from_name(name) {
+                        Some(scalar) => SchemaForm::Type(scalar),
+                        None => {
+                            return Err(DefinitionError::new(
+                                &child_path("type"),
+                                format!("Unknown type \"{}\"", name),
+                            ))
+                        }
+                    }
+                }
+                Some("enum") => {
+                    let enum_path = child_path("enum");
+                    let values = match &object["enum"] {
+                        serde_json::Value::Array(values) => values,
+                        _ => return Err(DefinitionError::new(&enum_path, "Expected an array")),
+                    };
+                    let mut result = Vec::new();
+                    for (index, value) in values.iter().enumerate() {
+                        result.push(
+                            expect_string(value, &format!("{}/{}", enum_path, index))?.to_string(),
+                        );
+                    }
+                    SchemaForm::Enum(result)
+                }
+                Some("elements") => SchemaForm::Elements(Box::new(Self::from_json(
+                    &object["elements"],
+                    &child_path("elements"),
+                )?)),
+                Some("properties") => SchemaForm::Properties(parse_properties(object, path)?),
+                Some("values") => SchemaForm::Values(Box::new(Self::from_json(
+                    &object["values"],
+                    &child_path("values"),
+                )?)),
+                Some("discriminator") => {
+                    let discriminator =
+                        expect_string(&object["discriminator"], &child_path("discriminator"))?;
+                    let mapping = match object.get("mapping") {
+                        Some(mapping) => parse_schema_map(mapping, &child_path("mapping"))?,
+                        None => {
+                            return Err(DefinitionError::new(
+                                path,
+                                "Discriminator schema is missing \"mapping\"",
+                            ))
+                        }
+                    };
+                    SchemaForm::Discriminator(DiscriminatorSchema {
+                        discriminator: discriminator.to_string(),
+                        mapping,
+                    })
+                }
+                Some(_) => {
+                    SchemaForm::Ref(expect_string(&object["ref"], &child_path("ref"))?.to_string())
+                }
+            }
+        };
+        let is_nullable = match object.get("isNullable") {
+            Some(value) => expect_bool(value, &child_path("isNullable"))?,
+            None => false,
+        };
+        let metadata = match object.get("metadata") {
+            Some(value) => Some(TypeMetadata::from_json(value, &child_path("metadata"))?),
+            None => None,
+        };
+        Ok(Self {
+            form,
+            is_nullable,
+            metadata,
+        })
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut output = serde_json::Map::new();
+        match &self.form {
+            SchemaForm::Empty => {}
+            SchemaForm::Type(scalar) => {
+                output.insert("type".to_string(), scalar.name().into());
+            }
+            SchemaForm::Enum(values) => {
+                output.insert("enum".to_string(), values.clone().into());
+            }
+            SchemaForm::Elements(elements) => {
+                output.insert("elements".to_string(), elements.to_json());
+            }
+            SchemaForm::Properties(schema) => {
+                output.insert(
+                    "properties".to_string(),
+                    schema_map_to_json(&schema.properties),
+                );
+                if !schema.optional_properties.is_empty() {
+                    output.insert(
+                        "optionalProperties".to_string(),
+                        schema_map_to_json(&schema.optional_properties),
+                    );
+                }
+                if schema.is_strict {
+                    output.insert("isStrict".to_string(), true.into());
+                }
+            }
+            SchemaForm::Values(values) => {
+                output.insert("values".to_string(), values.to_json());
+            }
+            SchemaForm::Discriminator(schema) => {
+                output.insert(
+                    "discriminator".to_string(),
+                    schema.discriminator.clone().into(),
+                );
+                output.insert("mapping".to_string(), schema_map_to_json(&schema.mapping));
+            }
+            SchemaForm::Ref(id) => {
+                output.insert("ref".to_string(), id.clone().into());
+            }
+        }
+        if self.is_nullable {
+            output.insert("isNullable".to_string(), true.into());
+        }
+        if let Some(metadata) = &self.metadata {
+            output.insert("metadata".to_string(), metadata.to_json());
+        }
+        serde_json::Value::Object(output)
+    }
+}
+
+impl TypeMetadata {
+    pub fn from_json(input: &serde_json::Value, path: &str) -> Result<Self, DefinitionError> {
+        let object = expect_object(input, path)?;
+        let mut metadata = Self::default();
+        for (key, value) in object {
+            let key_path = format!("{}/{}", path, escape_pointer(key));
+            match key.as_str() {
+                "id" => metadata.id = Some(expect_string(value, &key_path)?.to_string()),
+                "description" => {
+                    metadata.description = Some(expect_string(value, &key_path)?.to_string())
+                }
+                "isDeprecated" => metadata.is_deprecated = expect_bool(value, &key_path)?,
+                "deprecatedNote" => {
+                    metadata.deprecated_note = Some(expect_string(value, &key_path)?.to_string())
+                }
+                _ => {
+                    metadata.extra.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        Ok(metadata)
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut output = self.extra.clone();
+        if let Some(id) = &self.id {
+            output.insert("id".to_string(), id.clone().into());
+        }
+        if let Some(description) = &self.description {
+            output.insert("description".to_string(), description.clone().into());
+        }
+        if self.is_deprecated {
+            output.insert("isDeprecated".to_string(), true.into());
+        }
+        if let Some(note) = &self.deprecated_note {
+            output.insert("deprecatedNote".to_string(), note.clone().into());
+        }
+        serde_json::Value::Object(output)
+    }
+}
+
+fn parse_properties(
+    object: &serde_json::Map<String, serde_json::Value>,
+    path: &str,
+) -> Result<PropertiesSchema, DefinitionError> {
+    let mut schema = PropertiesSchema::default();
+    if let Some(properties) = object.get("properties") {
+        schema.properties = parse_schema_map(properties, &format!("{}/properties", path))?;
+    }
+    if let Some(properties) = object.get("optionalProperties") {
+        schema.optional_properties =
+            parse_schema_map(properties, &format!("{}/optionalProperties", path))?;
+    }
+    if let Some(is_strict) = object.get("isStrict") {
+        schema.is_strict = expect_bool(is_strict, &format!("{}/isStrict", path))?;
+    }
+    Ok(schema)
+}
+
+fn parse_schema_map(
+    input: &serde_json::Value,
+    path: &str,
+) -> Result<BTreeMap<String, TypeDefinition>, DefinitionError> {
+    let mut result = BTreeMap::new();
+    for (key, value) in expect_object(input, path)? {
+        let schema =
+            TypeDefinition::from_json(value, &format!("{}/{}", path, escape_pointer(key)))?;
+        result.insert(key.clone(), schema);
+    }
+    Ok(result)
+}
+
+fn schema_map_to_json(input: &BTreeMap<String, TypeDefinition>) -> serde_json::Value {
+    serde_json::Value::Object(
+        input
+            .iter()
+            .map(|(key, schema)| (key.clone(), schema.to_json()))
+            .collect(),
+    )
+}
+
+/// Escape a key for use as a JSON pointer segment
+pub fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
@@ -0,0 +1,441 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::{collect_ids, escape_pointer, ScalarType, SchemaForm, TypeDefinition};
+
+/// A value that doesn't match its type definition. `path` is a JSON pointer to the offending value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub path: String,
+    pub message: String,
+}
+
+impl ValidationError {
+    pub fn new(path: &str, message: impl Into<String>) -> Self {
+        Self {
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            return write!(f, "{}", self.message);
+        }
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Checks JSON values against type definitions.
+///
+/// Refs are resolved against the definitions passed to [`ValueValidator::new`] and against any
+/// `metadata.id` in them or in the schema being validated.
+pub struct ValueValidator<'a> {
+    definitions: &'a BTreeMap<String, TypeDefinition>,
+    ids: HashMap<&'a str, &'a TypeDefinition>,
+}
+
+impl<'a> ValueValidator<'a> {
+    pub fn new(definitions: &'a BTreeMap<String, TypeDefinition>) -> Self {
+        let mut ids = HashMap::new();
+        for definition in definitions.values() {
+            collect_ids(definition, &mut ids);
+        }
+        Self { definitions, ids }
+    }
+
+    /// Returns every violation in `value`. An empty result means the value is valid.
+    pub fn validate(
+        &self,
+        value: &serde_json::Value,
+        schema: &'a TypeDefinition,
+    ) -> Vec<ValidationError> {
+        let mut ids = self.ids.clone();
+        collect_ids(schema, &mut ids);
+        let mut context = Context {
+            definitions: self.definitions,
+            ids,
+            errors: Vec::new(),
+        };
+        context.validate(value, schema, "", None);
+        context.errors
+    }
+}
+
+struct Context<'a> {
+    definitions: &'a BTreeMap<String, TypeDefinition>,
+    ids: HashMap<&'a str, &'a TypeDefinition>,
+    errors: Vec<ValidationError>,
+}
+
+impl<'a> Context<'a> {
+    fn error(&mut self, path: &str, message: impl Into<String>) {
+        self.errors.push(ValidationError::new(path, message));
+    }
+
+    fn resolve(&self, id: &str) -> Option<&'a TypeDefinition> {
+        match self.definitions.get(id) {
+            Some(definition) => Some(definition),
+            None => self.ids.get(id).copied(),
+        }
+    }
+
+    /// `discriminator` is the tag property that a discriminator mapping is allowed to skip
+    fn validate(
+        &mut self,
+        value: &serde_json::Value,
+        schema: &'a TypeDefinition,
+        path: &str,
+        discriminator: Option<&str>,
+    ) {
+        // follow refs until reaching a schema that consumes the value
+        let mut schema = schema;
+        let mut is_nullable = schema.is_nullable;
+        let mut visited = HashSet::new();
+        while let SchemaForm::Ref(id) = &schema.form {
+            if !visited.insert(id.as_str()) {
+                self.error(path, format!("Circular ref \"{}\"", id));
+                return;
+            }
+            match self.resolve(id) {
+                Some(target) => {
+                    schema = target;
+                    is_nullable = is_nullable || target.is_nullable;
+                }
+                None => {
+                    self.error(path, format!("Unknown ref \"{}\"", id));
+                    return;
+                }
+            }
+        }
+        if is_nullable && value.is_null() {
+            return;
+        }
+        match &schema.form {
+            SchemaForm::Empty => {}
+            SchemaForm::Type(scalar) => self.validate_scalar(value, *scalar, path),
+            SchemaForm::Enum(values) => match value {
+                serde_json::Value::String(input) if values.contains(input) => {}
+                _ => self.error(
+                    path,
+                    format!(
+                        "Expected one of {}",
+                        values
+                            .iter()
+                            .map(|value| format!("\"{}\"", value))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                ),
+            },
+            SchemaForm::Elements(inner) => match value {
+                serde_json::Value::Array(items) => {
+                    for (index, item) in items.iter().enumerate() {
+                        self.validate(item, inner, &format!("{}/{}", path, index), None);
+                    }
+                }
+                _ => self.error(path, "Expected an array"),
+            },
+            SchemaForm::Values(inner) => match value {
+                serde_json::Value::Object(object) => {
+                    for (key, item) in object {
+                        self.validate(
+                            item,
+                            inner,
+                            &format!("{}/{}", path, escape_pointer(key)),
+                            None,
+                        );
+                    }
+                }
+                _ => self.error(path, "Expected an object"),
+            },
+            SchemaForm::Properties(properties) => {
+                let object = match value {
+                    serde_json::Value::Object(object) => object,
+                    _ => return self.error(path, "Expected an object"),
+                };
+                for (key, property) in &properties.properties {
+                    match object.get(key) {
+                        Some(item) => self.validate(
+                            item,
+                            property,
+                            &format!("{}/{}", path, escape_pointer(key)),
+                            None,
+                        ),
+                        None => self.error(path, format!("Missing property \"{}\"", key)),
+                    }
+                }
+                for (key, property) in &properties.optional_properties {
+                    if let Some(item) = object.get(key) {
+                        self.validate(
+                            item,
+                            property,
+                            &format!("{}/{}", path, escape_pointer(key)),
+                            None,
+                        );
+                    }
+                }
+                if properties.is_strict {
+                    for key in object.keys() {
+                        if !properties.properties.contains_key(key)
+                            && !properties.optional_properties.contains_key(key)
+                            && discriminator != Some(key.as_str())
+                        {
+                            self.error(
+                                &format!("{}/{}", path, escape_pointer(key)),
+                                "Unexpected property",
+                            );
+                        }
+                    }
+                }
+            }
+            SchemaForm::Discriminator(union) => {
+                let object = match value {
+                    serde_json::Value::Object(object) => object,
+                    _ => return self.error(path, "Expected an object"),
+                };
+                let tag_path = format!("{}/{}", path, escape_pointer(&union.discriminator));
+                let tag = match object.get(&union.discriminator) {
+                    Some(serde_json::Value::String(tag)) => tag,
+                    Some(_) => return self.error(&tag_path, "Expected a string"),
+                    None => {
+                        return self.error(
+                            path,
+                            format!("Missing property \"{}\"", union.discriminator),
+                        )
+                    }
+                };
+                match union.mapping.get(tag) {
+                    Some(mapping) => {
+                        self.validate(value, mapping, path, Some(union.discriminator.as_str()))
+                    }
+                    None => self.error(
+                        &tag_path,
+                        format!(
+                            "Expected one of {}",
+                            union
+                                .mapping
+                                .keys()
+                                .map(|key| format!("\"{}\"", key))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    ),
+                }
+            }
+            SchemaForm::Ref(_) => unreachable!(),
+        }
+    }
+
+    fn validate_scalar(&mut self, value: &serde_json::Value, scalar: ScalarType, path: &str) {
+        let is_valid = match scalar {
+            ScalarType::Boolean => value.is_boolean(),
+            ScalarType::String => value.is_string(),
+            ScalarType::Timestamp => value
+                .as_str()
+                .is_some_and(|input| chrono::DateTime::parse_from_rfc3339(input).is_ok()),
+            ScalarType::Float32 | ScalarType::Float64 => value.is_number(),
+            ScalarType::Int8 => is_whole_number(value, i8::MIN as f64, i8::MAX as f64),
+            ScalarType::Uint8 => is_whole_number(value, 0.0, u8::MAX as f64),
+            ScalarType::Int16 => is_whole_number(value, i16::MIN as f64, i16::MAX as f64),
+            ScalarType::Uint16 => is_whole_number(value, 0.0, u16::MAX as f64),
+            ScalarType::Int32 => is_whole_number(value, i32::MIN as f64, i32::MAX as f64),
+            ScalarType::Uint32 => is_whole_number(value, 0.0, u32::MAX as f64),
+            // 64-bit integers are sent as strings so they don't lose precision
+            ScalarType::Int64 => value
+                .as_str()
+                .is_some_and(|input| input.parse::<i64>().is_ok()),
+            ScalarType::Uint64 => value
+                .as_str()
+                .is_some_and(|input| input.parse::<u64>().is_ok()),
+        };
+        if is_valid {
+            return;
+        }
+        let message = match scalar {
+            ScalarType::Boolean => "Expected a boolean".to_string(),
+            ScalarType::String => "Expected a string".to_string(),
+            ScalarType::Timestamp => "Expected an RFC 3339 timestamp".to_string(),
+            ScalarType::Float32 | ScalarType::Float64 => "Expected a number".to_string(),
+            ScalarType::Int8 => integer_message(i8::MIN, i8::MAX),
+            ScalarType::Uint8 => integer_message(u8::MIN, u8::MAX),
+            ScalarType::Int16 => integer_message(i16::MIN, i16::MAX),
+            ScalarType::Uint16 => integer_message(u16::MIN, u16::MAX),
+            ScalarType::Int32 => integer_message(i32::MIN, i32::MAX),
+            ScalarType::Uint32 => integer_message(u32::MIN, u32::MAX),
+            ScalarType::Int64 => format!(
+                "Expected a string containing a whole number between {} and {}",
+                i64::MIN,
+                i64::MAX
+            ),
+            ScalarType::Uint64 => format!(
+                "Expected a string containing a whole number between {} and {}",
+                u64::MIN,
+                u64::MAX
+            ),
+        };
+        self.error(path, message);
+    }
+}
+
+fn is_whole_number(value: &serde_json::Value, min: f64, max: f64) -> bool {
+    match value.as_f64() {
+        Some(number) => number.fract() == 0.0 && number >= min && number <= max,
+        None => false,
+    }
+}
+
+fn integer_message(min: impl std::fmt::Display, max: impl std::fmt::Display) -> String {
+    format!("Expected a whole number between {} and {}", min, max)
+}
+
+#[cfg(test)]
+mod validation_tests {
+    use crate::definition::AppDefinition;
+
+    const APP_DEFINITION: &str = include_str!("../../../../../tests/test-files/AppDefinition.json");
+
+    fn errors(type_name: &str, value: serde_json::Value) -> Vec<String> {
+        let app = AppDefinition::from_json_str(APP_DEFINITION).unwrap();
+        match app.validate_value(type_name, &value) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.iter().map(|err| err.to_string()).collect(),
+        }
+    }
+
+    fn every_type() -> serde_json::Value {
+        serde_json::json!({
+            "string": "",
+            "boolean": false,
+            "timestamp": "2001-01-01T16:00:00.000Z",
+            "float32": 1.5,
+            "float64": 1.5,
+            "int8": 1,
+            "uint8": 1,
+            "int16": 10,
+            "uint16": 10,
+            "int32": 100,
+            "uint32": 100,
+            "int64": "-9223372036854775808",
+            "uint64": "18446744073709551615",
+            "enum": "BAZ",
+            "object": { "id": "1", "content": "hello world" },
+            "array": [true, false],
+            "record": { "A": true },
+            "discriminator": { "typeName": "B", "id": "1", "name": "John" },
+            "any": { "anything": [1, "2"] }
+        })
+    }
+
+    #[test]
+    fn valid_test() {
+        assert!(errors("ObjectWithEveryType", every_type()).is_empty());
+        assert!(errors(
+            "RecursiveObject",
+            serde_json::json!({
+                "left": { "left": null, "right": null },
+                "right": {
+                    "left": null,
+                    "right": { "left": null, "right": null }
+                }
+            })
+        )
+        .is_empty());
+        assert!(errors(
+            "ObjectWithNullableFields",
+            serde_json::json!({
+                "string": null, "boolean": null, "timestamp": null, "float32": null,
+                "float64": null, "int8": null, "uint8": null, "int16": null, "uint16": null,
+                "int32": null, "uint32": null, "int64": null, "uint64": null, "enum": null,
+                "object": null, "array": null, "record": null, "discriminator": null, "any": null
+            })
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn invalid_test() {
+        let mut input = every_type();
+        input["timestamp"] = "2001-01-01 16:00".into();
+        input["int8"] = 128.into();
+        input["uint32"] = 1.5.into();
+        input["int64"] = 10.into();
+        input["uint64"] = "-1".into();
+        input["enum"] = "foo".into();
+        input["object"] = serde_json::json!({ "id": 1 });
+        input["array"] = serde_json::json!([true, null]);
+        input["discriminator"] = serde_json::json!({ "typeName": "D" });
+        input.as_object_mut().unwrap().remove("string");
+        assert_eq!(
+            errors("ObjectWithEveryType", input),
+            vec![
+                "/array/1: Expected a boolean",
+                "/discriminator/typeName: Expected one of \"A\", \"B\", \"C\"",
+                "/enum: Expected one of \"FOO\", \"BAR\", \"BAZ\"",
+                "/int64: Expected a string containing a whole number between -9223372036854775808 and 9223372036854775807",
+                "/int8: Expected a whole number between -128 and 127",
+                "/object: Missing property \"content\"",
+                "/object/id: Expected a string",
+                "Missing property \"string\"",
+                "/timestamp: Expected an RFC 3339 timestamp",
+                "/uint32: Expected a whole number between 0 and 4294967295",
+                "/uint64: Expected a string containing a whole number between 0 and 18446744073709551615",
+            ]
+        );
+    }
+
+    #[test]
+    fn recursive_test() {
+        assert_eq!(
+            errors(
+                "RecursiveObject",
+                serde_json::json!({
+                    "left": { "left": null, "right": { "left": 1, "right": null } },
+                    "right": null
+                })
+            ),
+            vec!["/left/right/left: Expected an object"]
+        );
+        assert_eq!(
+            errors("Unknown", serde_json::json!({})),
+            vec!["Unknown type \"Unknown\""]
+        );
+    }
+
+    #[test]
+    fn strict_test() {
+        let app = AppDefinition::from_json(&serde_json::json!({
+            "schemaVersion": "0.0.8",
+            "procedures": {},
+            "definitions": {
+                "Event": {
+                    "discriminator": "type",
+                    "mapping": {
+                        "CREATED": {
+                            "properties": { "id": { "type": "string" } },
+                            "isStrict": true
+                        }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        assert!(app
+            .validate_value(
+                "Event",
+                &serde_json::json!({ "type": "CREATED", "id": "1" })
+            )
+            .is_ok());
+        let errors = app
+            .validate_value(
+                "Event",
+                &serde_json::json!({ "type": "CREATED", "id": "1", "name": "" }),
+            )
+            .unwrap_err();
+        assert_eq!(errors[0].path, "/name");
+        assert_eq!(errors[0].message, "Unexpected property");
+    }
+}
@@ -0,0 +1,116 @@
+//! Reports calls to deprecated procedures at runtime.
+//!
+//! Procedures that are deprecated in the app definition are reported whenever they are called.
+//! Servers can also mark a procedure as deprecated by responding with the `deprecation` and
+//! `sunset` headers of RFC 9745 and RFC 8594.
+use crate::listener::Listener;
+
+pub const DEPRECATION_HEADER: &str = "deprecation";
+pub const SUNSET_HEADER: &str = "sunset";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeprecationSource {
+    /// The procedure is deprecated in the app definition that the client was generated from
+    Definition,
+    /// The server responded with a `deprecation` or `sunset` header
+    Server,
+}
+
+/// A call to a deprecated procedure
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeprecationNotice {
+    /// Path of the procedure, e.g. `/books/create-book`
+    pub path: String,
+    pub source: DeprecationSource,
+    /// Value of the `deprecation` header, e.g. `@1735689600`
+    pub deprecation: Option<String>,
+    /// Value of the `sunset` header, e.g. `Thu, 01 Jan 2026 00:00:00 GMT`
+    pub sunset: Option<String>,
+}
+
+impl DeprecationNotice {
+    pub fn from_definition(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            source: DeprecationSource::Definition,
+            deprecation: None,
+            sunset: None,
+        }
+    }
+
+    /// Read the deprecation headers of a response. None if neither header was sent.
+    pub fn from_headers(path: &str, headers: &reqwest::header::HeaderMap) -> Option<Self> {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+        let deprecation = header(DEPRECATION_HEADER);
+        let sunset = header(SUNSET_HEADER);
+        if deprecation.is_none() && sunset.is_none() {
+            return None;
+        }
+        Some(Self {
+            path: path.to_string(),
+            source: DeprecationSource::Server,
+            deprecation,
+            sunset,
+        })
+    }
+}
+
+/// The deprecation handler of a client
+pub type DeprecationListener = Listener<DeprecationNotice>;
+
+impl Listener<DeprecationNotice> {
+    /// Called by generated clients before sending a request to a deprecated procedure
+    pub fn procedure_called(&self, path: &str) {
+        self.notify(&DeprecationNotice::from_definition(path));
+    }
+
+    /// Reporter for the responses of the procedure at `path`
+    pub fn reporter(&self, path: &str) -> DeprecationReporter {
+        DeprecationReporter {
+            path: path.to_string(),
+            listener: self.clone(),
+        }
+    }
+}
+
+/// Reports the deprecation headers of event stream responses and websocket handshakes.
+/// Created by generated clients with `DeprecationListener::reporter()`.
+#[derive(Clone)]
+pub struct DeprecationReporter {
+    path: String,
+    listener: DeprecationListener,
+}
+
+impl DeprecationReporter {
+    pub fn report_headers(&self, headers: &reqwest::header::HeaderMap) {
+        if let Some(notice) = DeprecationNotice::from_headers(&self.path, headers) {
+            self.listener.notify(&notice);
+        }
+    }
+}
+
+#[cfg(test)]
+mod deprecation_tests {
+    use super::*;
+
+    #[test]
+    fn from_headers_test() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        assert_eq!(DeprecationNotice::from_headers("/a", &headers), None);
+        headers.insert("sunset", "Thu, 01 Jan 2026 00:00:00 GMT".parse().unwrap());
+        assert_eq!(
+            DeprecationNotice::from_headers("/a", &headers),
+            Some(DeprecationNotice {
+                path: "/a".to_string(),
+                source: DeprecationSource::Server,
+                deprecation: None,
+                sunset: Some("Thu, 01 Jan 2026 00:00:00 GMT".to_string()),
+            })
+        );
+    }
+}
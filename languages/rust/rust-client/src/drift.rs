@@ -0,0 +1,364 @@
+use crate::{
+    definition::{definition_hash, type_definition_hash, AppDefinition, DefinitionError},
+    parsed_arri_request,
+    procedure::{ArriProcedureRegistry, ProcedureInfo, ProcedureTransport},
+    ArriError, ArriParsedRequestOptions, EmptyArriModel, InternalArriClientConfig,
+};
+
+/// Where Arri servers serve their app definition unless configured otherwise
+pub const DEFAULT_DEFINITION_PATH: &str = "/__definition";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MismatchKind {
+    ProcedureMissing,
+    ProcedureChanged,
+    TypeMissing,
+    TypeChanged,
+    /// The client was generated without definition hashes, so its types can't be checked
+    HashesMissing,
+}
+
+/// A procedure or type of the client that doesn't match the server
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefinitionMismatch {
+    pub kind: MismatchKind,
+    /// Name of the procedure or type
+    pub name: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for DefinitionMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name, self.message)
+    }
+}
+
+/// The result of comparing a generated client with the app definition of a running server
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefinitionDrift {
+    /// Hash of the definition the client was generated from
+    pub client_hash: &'static str,
+    /// Hash of the definition served by the server
+    pub server_hash: String,
+    pub mismatches: Vec<DefinitionMismatch>,
+}
+
+impl DefinitionDrift {
+    /// Compare the procedures and types of the registry `R` with a server's app definition
+    pub fn compare<R: ArriProcedureRegistry + ?Sized>(
+        server: &serde_json::Value,
+    ) -> Result<Self, DefinitionError> {
+        let definition = AppDefinition::from_json(server)?;
+        let mut mismatches = Vec::new();
+        for procedure in R::PROCEDURES {
+            compare_procedure(procedure, &definition, &mut mismatches);
+        }
+        if R::DEFINITION_HASH.is_empty() {
+            mismatches.push(DefinitionMismatch {
+                kind: MismatchKind::HashesMissing,
+                name: std::any::type_name::<R>().to_string(),
+                message: "Client was generated without definition hashes. Regenerate it to check its types".to_string(),
+            });
+        }
+        for (name, hash) in R::TYPE_HASHES {
+            let Some(schema) = server.get("definitions").and_then(|defs| defs.get(*name)) else {
+                mismatches.push(DefinitionMismatch {
+                    kind: MismatchKind::TypeMissing,
+                    name: name.to_string(),
+                    message: "Type doesn't exist on the server".to_string(),
+                });
+                continue;
+            };
+            if type_definition_hash(schema) != *hash {
+                mismatches.push(DefinitionMismatch {
+                    kind: MismatchKind::TypeChanged,
+                    name: name.to_string(),
+                    message: "Type doesn't match the server".to_string(),
+                });
+            }
+        }
+        Ok(Self {
+            client_hash: R::DEFINITION_HASH,
+            server_hash: definition_hash(server),
+            mismatches,
+        })
+    }
+
+    /// True when the client was generated from the same definition that the server serves.
+    /// A client can be out of date without having mismatches, e.g. when the server added procedures.
+    pub fn is_identical(&self) -> bool {
+        self.client_hash == self.server_hash
+    }
+
+    /// True when a procedure or type of the client doesn't match the server
+    pub fn has_mismatches(&self) -> bool {
+        !self.mismatches.is_empty()
+    }
+}
+
+fn compare_procedure(
+    procedure: &ProcedureInfo,
+    definition: &AppDefinition,
+    mismatches: &mut Vec<DefinitionMismatch>,
+) {
+    let mut push = |kind: MismatchKind, message: String| {
+        mismatches.push(DefinitionMismatch {
+            kind,
+            name: procedure.name.to_string(),
+            message,
+        })
+    };
+    let Some(server) = definition.procedures.get(procedure.name) else {
+        push(
+            MismatchKind::ProcedureMissing,
+            "Procedure doesn't exist on the server".to_string(),
+        );
+        return;
+    };
+    let transport = match procedure.transport {
+        ProcedureTransport::Http => "http",
+        ProcedureTransport::Ws => "ws",
+    };
+    if transport != server.transport.name() {
+        push(
+            MismatchKind::ProcedureChanged,
+            format!(
+                "Transport is \"{}\" on the client but \"{}\" on the server",
+                transport,
+                server.transport.name()
+            ),
+        );
+        return;
+    }
+    if procedure.path != server.path {
+        push(
+            MismatchKind::ProcedureChanged,
+            format!(
+                "Path is \"{}\" on the client but \"{}\" on the server",
+                procedure.path, server.path
+            ),
+        );
+    }
+    if let Some(method) = &procedure.method {
+        if *method != server.http_method() {
+            push(
+                MismatchKind::ProcedureChanged,
+                format!(
+                    "Method is {} on the client but {} on the server",
+                    method,
+                    server.http_method()
+                ),
+            );
+        }
+    }
+    if procedure.is_event_stream != server.is_event_stream {
+        push(
+            MismatchKind::ProcedureChanged,
+            match server.is_event_stream {
+                true => "Procedure is an event stream on the server".to_string(),
+                false => "Procedure is no longer an event stream on the server".to_string(),
+            },
+        );
+    }
+    for (label, client_type, server_type) in [
+        ("Params", procedure.params, server.params.as_deref()),
+        ("Response", procedure.response, server.response.as_deref()),
+    ] {
+        if client_type != server_type {
+            push(
+                MismatchKind::ProcedureChanged,
+                format!(
+                    "{} type is {} on the client but {} on the server",
+                    label,
+                    client_type.unwrap_or("empty"),
+                    server_type.unwrap_or("empty")
+                ),
+            );
+        }
+    }
+}
+
+/// Fetch the app definition at `path` and compare it with the registry `R`
+pub async fn check_definition<R: ArriProcedureRegistry + ?Sized>(
+    config: &InternalArriClientConfig,
+    path: &str,
+) -> Result<DefinitionDrift, ArriError> {
+    let body = parsed_arri_request(
+        ArriParsedRequestOptions {
+            http_client: &config.http_client,
+            url: format!("{}{}", &config.base_url, path),
+            method: reqwest::Method::GET,
+            headers: config.headers.clone(),
+            client_version: String::new(),
+        },
+        None::<EmptyArriModel>,
+        |body| body,
+    )
+    .await?;
+    let error = |message: String| ArriError {
+        code: 0,
+        message,
+        stack: None,
+        data: None,
+    };
+    let server = serde_json::from_str::<serde_json::Value>(&body)
+        .map_err(|err| error(format!("Server definition is not valid JSON: {}", err)))?;
+    DefinitionDrift::compare::<R>(&server)
+        .map_err(|err| error(format!("Invalid server definition: {}", err)))
+}
+
+#[cfg(test)]
+mod drift_tests {
+    use super::*;
+    use crate::{loopback::ArriLoopback, procedure::ArriProcedureClient, ArriClientConfig};
+
+    const APP_DEFINITION: &str = include_str!("../../../../tests/test-files/AppDefinition.json");
+
+    struct TestClient {
+        _config: InternalArriClientConfig,
+    }
+
+    impl ArriProcedureClient for TestClient {
+        fn procedure_config(&self) -> &InternalArriClientConfig {
+            &self._config
+        }
+    }
+
+    impl ArriProcedureRegistry for TestClient {
+        const DEFINITION_HASH: &'static str = "6eb85c4403cb6d1a";
+        const PROCEDURES: &'static [ProcedureInfo] = &[
+            ProcedureInfo {
+                name: "books.getBook",
+                path: "/books/get-book",
+                method: Some(reqwest::Method::GET),
+                transport: ProcedureTransport::Http,
+                is_event_stream: false,
+                is_deprecated: false,
+                description: None,
+                params: Some("BookParams"),
+                response: Some("Book"),
+            },
+            ProcedureInfo {
+                name: "books.createConnection",
+                path: "/books/create-connection",
+                method: None,
+                transport: ProcedureTransport::Ws,
+                is_event_stream: false,
+                is_deprecated: false,
+                description: None,
+                params: Some("BookParams"),
+                response: Some("Book"),
+            },
+        ];
+        const TYPE_HASHES: &'static [(&'static str, &'static str)] = &[
+            ("Book", "6fa33869aa3824cb"),
+            ("BookParams", "5561f69d0c26d5c3"),
+        ];
+    }
+
+    /// A client generated before definition hashes were embedded
+    struct UnhashedClient;
+
+    impl ArriProcedureRegistry for UnhashedClient {
+        const PROCEDURES: &'static [ProcedureInfo] = TestClient::PROCEDURES;
+    }
+
+    fn server_definition() -> serde_json::Value {
+        serde_json::from_str(APP_DEFINITION).unwrap()
+    }
+
+    #[test]
+    fn compare_test() {
+        let mut server = server_definition();
+        let drift = DefinitionDrift::compare::<TestClient>(&server).unwrap();
+        assert!(drift.is_identical());
+        assert!(!drift.has_mismatches());
+
+        // documentation changes and unrelated procedures don't cause mismatches
+        server["definitions"]["Book"]["metadata"]["description"] = "A book".into();
+        server["procedures"]["sendObject"]["method"] = "put".into();
+        let drift = DefinitionDrift::compare::<TestClient>(&server).unwrap();
+        assert!(!drift.is_identical());
+        assert!(!drift.has_mismatches());
+
+        server["procedures"]["books.getBook"]["method"] = "post".into();
+        server["procedures"]["books.getBook"]["response"] = "NestedObject".into();
+        server["procedures"]
+            .as_object_mut()
+            .unwrap()
+            .remove("books.createConnection");
+        server["definitions"]["Book"]["properties"]["title"] =
+            serde_json::json!({ "type": "int32" });
+        let drift = DefinitionDrift::compare::<TestClient>(&server).unwrap();
+        assert_eq!(
+            drift
+                .mismatches
+                .iter()
+                .map(|mismatch| (mismatch.kind, mismatch.to_string()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    MismatchKind::ProcedureChanged,
+                    "books.getBook: Method is GET on the client but POST on the server".to_string()
+                ),
+                (
+                    MismatchKind::ProcedureChanged,
+                    "books.getBook: Response type is Book on the client but NestedObject on the server"
+                        .to_string()
+                ),
+                (
+                    MismatchKind::ProcedureMissing,
+                    "books.createConnection: Procedure doesn't exist on the server".to_string()
+                ),
+                (
+                    MismatchKind::TypeChanged,
+                    "Book: Type doesn't match the server".to_string()
+                ),
+            ]
+        );
+
+        server["definitions"]
+            .as_object_mut()
+            .unwrap()
+            .remove("BookParams");
+        let drift = DefinitionDrift::compare::<TestClient>(&server).unwrap();
+        assert_eq!(
+            drift.mismatches.last().map(|mismatch| mismatch.kind),
+            Some(MismatchKind::TypeMissing)
+        );
+    }
+
+    #[test]
+    fn hashes_missing_test() {
+        let mut server = server_definition();
+        server["definitions"]["Book"]["properties"]["title"] =
+            serde_json::json!({ "type": "int32" });
+        let drift = DefinitionDrift::compare::<UnhashedClient>(&server).unwrap();
+        assert!(!drift.is_identical());
+        assert_eq!(
+            drift
+                .mismatches
+                .iter()
+                .map(|mismatch| mismatch.kind)
+                .collect::<Vec<_>>(),
+            vec![MismatchKind::HashesMissing]
+        );
+    }
+
+    #[tokio::test]
+    async fn check_definition_test() {
+        let loopback = ArriLoopback::new().handle("/__definition", |_: serde_json::Value| async {
+            Ok(server_definition())
+        });
+        let client = TestClient {
+            _config: InternalArriClientConfig::from(ArriClientConfig::loopback(&loopback)),
+        };
+        let drift = client.check_definition().await.unwrap();
+        assert!(drift.is_identical());
+        let err = client
+            .check_definition_at("/api/__definition")
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, 404);
+    }
+}
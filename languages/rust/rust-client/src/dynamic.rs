@@ -0,0 +1,430 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    definition::{AppDefinition, ProcedureDefinition, Transport, ValidationError},
+    parsed_arri_request,
+    sse::{
+        parsed_arri_sse_request, ArriParsedSseRequestOptions, SseController, SseEvent,
+        SseHandlerOutput, SseOptions,
+    },
+    ArriClientConfig, ArriError, ArriModel, ArriParsedRequestOptions, InternalArriClientConfig,
+};
+
+/// Calls the procedures of an app definition by name without generating a client.
+///
+/// Params and responses are plain JSON values. Params are checked against the definition before
+/// they are sent and responses are checked when they are received, unless validation is turned off
+/// with [`DynamicClient::validation`].
+#[derive(Clone)]
+pub struct DynamicClient {
+    definition: Arc<AppDefinition>,
+    validate: bool,
+    _config: InternalArriClientConfig,
+}
+
+impl DynamicClient {
+    pub fn new(config: ArriClientConfig, definition: AppDefinition) -> Self {
+        Self {
+            definition: Arc::new(definition),
+            validate: true,
+            _config: InternalArriClientConfig::from(config),
+        }
+    }
+
+    /// Defaults to `true`
+    pub fn validation(mut self, enabled: bool) -> Self {
+        self.validate = enabled;
+        self
+    }
+
+    pub fn definition(&self) -> &AppDefinition {
+        &self.definition
+    }
+
+    pub fn update_headers(&self, headers: HashMap<&'static str, String>) {
+        let mut unwrapped_headers = self._config.headers.write().unwrap();
+        *unwrapped_headers = headers;
+    }
+
+    /// Call the unary procedure `name`, e.g. `users.getUser`.
+    /// Procedures without a response resolve to `serde_json::Value::Null`.
+    pub async fn call(
+        &self,
+        name: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, ArriError> {
+        let procedure = self.http_procedure(name)?;
+        if procedure.is_event_stream {
+            return Err(client_error(format!(
+                "\"{}\" is an event stream procedure and can't be called as a unary procedure",
+                name
+            )));
+        }
+        let params = self.check_params(name, procedure, params)?;
+        let body = parsed_arri_request(
+            ArriParsedRequestOptions {
+                http_client: &self._config.http_client,
+                url: format!("{}{}", &self._config.base_url, procedure.path),
+                method: procedure.http_method(),
+                headers: self._config.headers.clone(),
+                client_version: self.client_version(),
+            },
+            params,
+            |body| body,
+        )
+        .await?;
+        let response_type = match &procedure.response {
+            Some(response_type) => response_type,
+            None => return Ok(serde_json::Value::Null),
+        };
+        let response = match serde_json::from_str::<serde_json::Value>(&body) {
+            Ok(response) => response,
+            Err(err) => {
+                return Err(ArriError {
+                    code: 0,
+                    message: format!("Expected \"{}\" to respond with JSON", name),
+                    stack: None,
+                    data: Some(serde_json::Value::String(err.to_string())),
+                })
+            }
+        };
+        self.check_response(name, response_type, &response)?;
+        Ok(response)
+    }
+
+    /// Call the event stream procedure `name`. Messages that don't match the response type are
+    /// passed to the handler as `SseEvent::Error` with code 0.
+    pub async fn call_stream<OnEvent, OnEventOutput>(
+        &self,
+        name: &str,
+        params: serde_json::Value,
+        on_event: &mut OnEvent,
+        options: SseOptions<serde_json::Value, serde_json::Value>,
+    ) -> Result<(), ArriError>
+    where
+        OnEvent: FnMut(SseEvent<serde_json::Value>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        let procedure = self.http_procedure(name)?;
+        if !procedure.is_event_stream {
+            return Err(client_error(format!(
+                "\"{}\" is not an event stream procedure",
+                name
+            )));
+        }
+        let params = self.check_params(name, procedure, params)?;
+        let mut handler = |event: SseEvent<serde_json::Value>, controller: &mut SseController| {
+            let event = match (event, &procedure.response) {
+                (SseEvent::Message(message), Some(response_type)) => {
+                    match self.check_response(name, response_type, &message) {
+                        Ok(()) => SseEvent::Message(message),
+                        Err(err) => SseEvent::Error(err),
+                    }
+                }
+                (event, _) => event,
+            };
+            on_event(event, controller)
+        };
+        parsed_arri_sse_request(
+            ArriParsedSseRequestOptions {
+                client: &self._config.http_client,
+                client_version: self.client_version(),
+                url: format!("{}{}", &self._config.base_url, procedure.path),
+                method: procedure.http_method(),
+                headers: self._config.headers.clone(),
+                deprecation: None,
+                version_mismatch: None,
+                options,
+            },
+            params,
+            &mut handler,
+        )
+        .await;
+        Ok(())
+    }
+
+    fn client_version(&self) -> String {
+        self.definition
+            .info
+            .as_ref()
+            .and_then(|info| info.version.clone())
+            .unwrap_or_default()
+    }
+
+    fn http_procedure(&self, name: &str) -> Result<&ProcedureDefinition, ArriError> {
+        match self.definition.procedures.get(name) {
+            Some(procedure) if procedure.transport == Transport::Http => Ok(procedure),
+            Some(procedure) => Err(client_error(format!(
+                "\"{}\" uses the \"{}\" transport which isn't supported",
+                name,
+                procedure.transport.name()
+            ))),
+            None => Err(client_error(format!("Unknown procedure \"{}\"", name))),
+        }
+    }
+
+    /// Params are sent as query params for `GET` requests and as JSON otherwise.
+    /// Procedures without params don't send any.
+    fn check_params(
+        &self,
+        name: &str,
+        procedure: &ProcedureDefinition,
+        params: serde_json::Value,
+    ) -> Result<Option<serde_json::Value>, ArriError> {
+        let params_type = match &procedure.params {
+            Some(params_type) => params_type,
+            None => return Ok(None),
+        };
+        if self.validate {
+            if let Err(errors) = self.definition.validate_value(params_type, &params) {
+                return Err(ArriError {
+                    code: 400,
+                    message: format!("Invalid params for \"{}\"", name),
+                    stack: None,
+                    data: Some(validation_errors_to_json(&errors)),
+                });
+            }
+        }
+        Ok(Some(params))
+    }
+
+    fn check_response(
+        &self,
+        name: &str,
+        response_type: &str,
+        response: &serde_json::Value,
+    ) -> Result<(), ArriError> {
+        if !self.validate {
+            return Ok(());
+        }
+        match self.definition.validate_value(response_type, response) {
+            Ok(()) => Ok(()),
+            Err(errors) => Err(ArriError {
+                code: 0,
+                message: format!(
+                    "Response of \"{}\" doesn't match \"{}\"",
+                    name, response_type
+                ),
+                stack: None,
+                data: Some(validation_errors_to_json(&errors)),
+            }),
+        }
+    }
+}
+
+fn client_error(message: String) -> ArriError {
+    ArriError {
+        code: 0,
+        message,
+        stack: None,
+        data: None,
+    }
+}
+
+fn validation_errors_to_json(errors: &[ValidationError]) -> serde_json::Value {
+    serde_json::Value::Array(
+        errors
+            .iter()
+            .map(|err| {
+                serde_json::json!({
+                    "path": err.path,
+                    "message": err.message,
+                })
+            })
+            .collect(),
+    )
+}
+
+impl ArriModel for serde_json::Value {
+    fn new() -> Self {
+        serde_json::Value::Null
+    }
+
+    fn from_json(input: serde_json::Value) -> Self {
+        input
+    }
+
+    fn from_json_string(input: String) -> Self {
+        serde_json::from_str(input.as_str()).unwrap_or_default()
+    }
+
+    fn to_json_string(&self) -> String {
+        self.to_string()
+    }
+
+    fn to_query_params_string(&self) -> String {
+        let mut query_parts: Vec<String> = Vec::new();
+        if let serde_json::Value::Object(object) = self {
+            for (key, value) in object {
+                match value {
+                    serde_json::Value::String(value) => {
+                        query_parts.push(format!("{}={}", key, value))
+                    }
+                    serde_json::Value::Array(_) | serde_json::Value::Object(_) => println!(
+                        "[WARNING] cannot serialize nested values to query params. Skipping field at /{}.",
+                        key
+                    ),
+                    _ => query_parts.push(format!("{}={}", key, value)),
+                }
+            }
+        }
+        query_parts.join("&")
+    }
+}
+
+#[cfg(test)]
+mod dynamic_tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+    use crate::{loopback::ArriLoopback, sse::SseCloseReason};
+
+    const APP_DEFINITION: &str = include_str!("../../../../tests/test-files/AppDefinition.json");
+
+    fn book(id: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "name": "Dune",
+            "createdAt": "2001-01-01T16:00:00.000Z",
+            "updatedAt": "2001-01-01T16:00:00.000Z"
+        })
+    }
+
+    fn client(loopback: &ArriLoopback) -> DynamicClient {
+        DynamicClient::new(
+            ArriClientConfig::loopback(loopback),
+            AppDefinition::from_json_str(APP_DEFINITION).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn call_test() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let handler_calls = calls.clone();
+        let loopback = ArriLoopback::new()
+            .handle("/books/get-book", move |params: serde_json::Value| {
+                handler_calls.fetch_add(1, Ordering::SeqCst);
+                async move { Ok(book(params["bookId"].as_str().unwrap_or_default())) }
+            })
+            .handle("/books/create-book", |_: serde_json::Value| async move {
+                Ok(serde_json::json!({ "id": 1 }))
+            });
+        let client = client(&loopback);
+        let response = client
+            .call("books.getBook", serde_json::json!({ "bookId": "1" }))
+            .await
+            .unwrap();
+        assert_eq!(response, book("1"));
+
+        let err = client
+            .call("books.getBook", serde_json::json!({ "id": "1" }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, 400);
+        assert_eq!(
+            err.data,
+            Some(serde_json::json!([
+                { "path": "", "message": "Missing property \"bookId\"" }
+            ]))
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let err = client
+            .call("books.createBook", book("2"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, 0);
+        assert_eq!(
+            err.message,
+            "Response of \"books.createBook\" doesn't match \"Book\""
+        );
+
+        let client = client.validation(false);
+        let response = client.call("books.createBook", book("2")).await.unwrap();
+        assert_eq!(response, serde_json::json!({ "id": 1 }));
+    }
+
+    #[tokio::test]
+    async fn unsupported_call_test() {
+        let client = client(&ArriLoopback::new());
+        let messages = [
+            (
+                "books.unknown",
+                "Unknown procedure \"books.unknown\"",
+            ),
+            (
+                "books.createConnection",
+                "\"books.createConnection\" uses the \"ws\" transport which isn't supported",
+            ),
+            (
+                "books.watchBook",
+                "\"books.watchBook\" is an event stream procedure and can't be called as a unary procedure",
+            ),
+        ];
+        for (name, message) in messages {
+            let err = client
+                .call(name, serde_json::json!({ "bookId": "1" }))
+                .await
+                .unwrap_err();
+            assert_eq!(err.code, 0);
+            assert_eq!(err.message, message);
+        }
+    }
+
+    #[tokio::test]
+    async fn call_stream_test() {
+        let loopback = ArriLoopback::new().handle_stream(
+            "/books/watch-book",
+            |params: serde_json::Value, stream| async move {
+                let id = params["bookId"].as_str().unwrap_or_default().to_string();
+                stream.send(book(&id)).await?;
+                stream.send(serde_json::json!({ "id": id })).await?;
+                Ok(())
+            },
+        );
+        let client = client(&loopback);
+        let mut messages = Vec::new();
+        let mut errors = Vec::new();
+        let mut closed = false;
+        client
+            .call_stream(
+                "books.watchBook",
+                serde_json::json!({ "bookId": "1" }),
+                &mut |event, _| match event {
+                    SseEvent::Message(message) => messages.push(message),
+                    SseEvent::Error(err) => errors.push(err.message),
+                    SseEvent::Close(SseCloseReason::Done) => closed = true,
+                    _ => {}
+                },
+                SseOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(messages, vec![book("1")]);
+        assert_eq!(
+            errors,
+            vec!["Response of \"books.watchBook\" doesn't match \"Book\""]
+        );
+        assert!(closed);
+    }
+
+    #[test]
+    fn query_params_test() {
+        let params = serde_json::json!({
+            "bookId": "1",
+            "limit": 10,
+            "deleted": false,
+            "cursor": null,
+            "tags": ["a"]
+        });
+        assert_eq!(
+            params.to_query_params_string(),
+            "bookId=1&cursor=null&deleted=false&limit=10"
+        );
+    }
+}
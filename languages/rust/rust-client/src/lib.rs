@@ -1,5 +1,18 @@
+pub mod definition;
+pub mod deprecation;
+pub mod drift;
+pub mod dynamic;
+pub mod listener;
+pub mod loopback;
+#[cfg(any(test, feature = "testing"))]
+pub mod mock;
+pub mod procedure;
 pub mod sse;
+#[cfg(unix)]
+mod unix_socket;
 pub mod utils;
+pub mod version;
+pub mod ws;
 pub use chrono::{self};
 pub use reqwest::{self, StatusCode};
 pub use serde_json::{self};
@@ -20,6 +33,9 @@ pub struct InternalArriClientConfig {
     pub http_client: reqwest::Client,
     pub base_url: String,
     pub headers: Arc<RwLock<HashMap<&'static str, String>>>,
+    pub procedure_options: procedure::ArriProcedureOptions,
+    pub version_mismatch: version::VersionMismatchListener,
+    pub deprecation: deprecation::DeprecationListener,
 }
 
 pub trait ArriClientService {
@@ -33,6 +49,23 @@ impl InternalArriClientConfig {
             http_client: config.http_client,
             base_url: config.base_url,
             headers: Arc::new(RwLock::new(config.headers)),
+            procedure_options: Default::default(),
+            version_mismatch: Default::default(),
+            deprecation: Default::default(),
+        }
+    }
+
+    /// Config for a service of this client.
+    /// Headers are copied so that they can be updated separately,
+    /// procedure options and the version mismatch and deprecation handlers are shared.
+    pub fn child(&self) -> Self {
+        Self {
+            http_client: self.http_client.clone(),
+            base_url: self.base_url.clone(),
+            headers: Arc::new(RwLock::new(self.headers.read().unwrap().clone())),
+            procedure_options: self.procedure_options.clone(),
+            version_mismatch: self.version_mismatch.clone(),
+            deprecation: self.deprecation.clone(),
         }
     }
 }
@@ -53,7 +86,7 @@ pub struct ArriParsedRequestOptions<'a> {
     pub client_version: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ArriError {
     pub code: u16,
     pub message: String,
@@ -61,6 +94,28 @@ pub struct ArriError {
     pub data: Option<serde_json::Value>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArriErrorKind {
+    /// No response was received or the response couldn't be used. These errors have code 0.
+    Client,
+    /// The server doesn't accept the version sent in the `client-version` header
+    VersionMismatch(version::VersionMismatch),
+    /// Any other error returned by the server
+    Server,
+}
+
+impl ArriError {
+    pub fn kind(&self) -> ArriErrorKind {
+        if let Some(mismatch) = version::VersionMismatch::from_error(self) {
+            return ArriErrorKind::VersionMismatch(mismatch);
+        }
+        match self.code {
+            0 => ArriErrorKind::Client,
+            _ => ArriErrorKind::Server,
+        }
+    }
+}
+
 trait ArriRequestErrorMethods {
     fn from_response_data(status: u16, body: String) -> Self;
 }
@@ -108,10 +163,7 @@ impl ArriModel for ArriError {
                     Some(serde_json::Value::String(stack_val)) => Some(stack_val.to_owned()),
                     _ => None,
                 };
-                let data = match val.get("data") {
-                    Some(data_val) => Some(data_val.to_owned()),
-                    _ => None,
-                };
+                let data = val.get("data").map(|data_val| data_val.to_owned());
 
                 Self {
                     code,
@@ -143,26 +195,21 @@ impl ArriModel for ArriError {
             )
             .as_str(),
         );
-        match &self.stack {
-            Some(val) => {
-                result.push_str(",\"stack\":");
-                result.push_str(
-                    format!("\"{}\"", val.replace("\n", "\\n").replace("\"", "\\\"")).as_str(),
-                );
-            }
-            _ => {}
+        if let Some(val) = &self.stack {
+            result.push_str(",\"stack\":");
+            result.push_str(
+                format!("\"{}\"", val.replace("\n", "\\n").replace("\"", "\\\"")).as_str(),
+            );
         }
-        match &self.data {
-            Some(val) => {
-                result.push_str(",\"data\":");
-                result.push_str(
-                    serde_json::to_string(val)
-                        .unwrap_or("null".to_string())
-                        .as_str(),
-                )
-            }
-            _ => {}
+        if let Some(val) = &self.data {
+            result.push_str(",\"data\":");
+            result.push_str(
+                serde_json::to_string(val)
+                    .unwrap_or("null".to_string())
+                    .as_str(),
+            )
         }
+        result.push('}');
         result
     }
 
@@ -170,20 +217,14 @@ impl ArriModel for ArriError {
         let mut query_parts: Vec<String> = Vec::new();
         query_parts.push(format!("code={}", &self.code));
         query_parts.push(format!("message={}", &self.message));
-        match &self.stack {
-            Some(stack) => {
-                query_parts.push(format!("stack={}", stack));
-            }
-            _ => {}
+        if let Some(stack) = &self.stack {
+            query_parts.push(format!("stack={}", stack));
         }
-        match &self.data {
-            Some(data) => {
-                query_parts.push(format!(
-                    "data={}",
-                    serde_json::to_string(data).unwrap_or("null".to_string())
-                ));
-            }
-            _ => {}
+        if let Some(data) = &self.data {
+            query_parts.push(format!(
+                "data={}",
+                serde_json::to_string(data).unwrap_or("null".to_string())
+            ));
         }
         query_parts.join("&")
     }
@@ -193,6 +234,10 @@ pub async fn arri_request<'a>(
     opts: ArriRequestOptions<'a>,
     params: Option<impl ArriModel>,
 ) -> Result<reqwest::Response, ArriError> {
+    if loopback::is_loopback_url(&opts.url) {
+        let params = params.map(|params| params.to_json_string());
+        return Ok(loopback::loopback_response(&opts.url, params).await);
+    }
     let response: Result<reqwest::Response, reqwest::Error>;
     let mut headers: HashMap<&str, String> = HashMap::new();
     {
@@ -230,9 +275,8 @@ pub async fn arri_request<'a>(
     match opts.method {
         reqwest::Method::GET => {
             let mut final_url = opts.url.clone();
-            match params {
-                Some(val) => final_url = format!("{final_url}?{}", val.to_query_params_string()),
-                None => {}
+            if let Some(val) = params {
+                final_url = format!("{final_url}?{}", val.to_query_params_string());
             }
             response = opts
                 .http_client
@@ -300,15 +344,13 @@ pub async fn arri_request<'a>(
         _ => panic!("Unsupported method"),
     };
     match response {
-        Ok(res) => return Ok(res),
-        Err(err) => {
-            return Err(ArriError {
-                code: err.status().unwrap_or(StatusCode::default()).as_u16(),
-                message: format!("Error requesting \"{}\"", opts.url),
-                stack: None,
-                data: None,
-            })
-        }
+        Ok(res) => Ok(res),
+        Err(err) => Err(ArriError {
+            code: err.status().map(|status| status.as_u16()).unwrap_or(0),
+            message: format!("Error requesting \"{}\"", opts.url),
+            stack: None,
+            data: None,
+        }),
     }
 }
 
@@ -358,6 +400,32 @@ pub async fn parsed_arri_request<'a, TResponse>(
     params: Option<impl ArriModel>,
     parser: fn(body: String) -> TResponse,
 ) -> Result<TResponse, ArriError> {
+    send_parsed_request(opts, params, parser, |_| {}).await
+}
+
+/// Same as `parsed_arri_request`. `on_headers` is called with the headers of the response
+/// when one is received, whether or not it was successful.
+/// Loopback handlers with the same params and response types are called without serializing.
+pub(crate) async fn parsed_arri_request_with_headers<'a, TResponse: 'static>(
+    opts: ArriParsedRequestOptions<'a>,
+    params: Option<impl ArriModel + Send + 'static>,
+    parser: fn(body: String) -> TResponse,
+    on_headers: impl FnOnce(&reqwest::header::HeaderMap),
+) -> Result<TResponse, ArriError> {
+    if loopback::is_loopback_url(&opts.url) {
+        return loopback::loopback_request(&opts.url, params, parser).await;
+    }
+    send_parsed_request(opts, params, parser, on_headers).await
+}
+
+/// Loopback requests are converted through JSON by `arri_request`
+async fn send_parsed_request<'a, TResponse>(
+    opts: ArriParsedRequestOptions<'a>,
+    params: Option<impl ArriModel>,
+    parser: fn(body: String) -> TResponse,
+    on_headers: impl FnOnce(&reqwest::header::HeaderMap),
+) -> Result<TResponse, ArriError> {
+    let client_version = opts.client_version.clone();
     let result = arri_request(
         ArriRequestOptions {
             method: opts.method,
@@ -369,28 +437,32 @@ pub async fn parsed_arri_request<'a, TResponse>(
         params,
     )
     .await;
-    if result.is_err() {
-        return Err(result.unwrap_err());
-    }
-    let response = result.unwrap();
+    let response = result?;
     let status = response.status().as_u16();
+    let headers = response.headers().clone();
+    on_headers(&headers);
     let body: Result<String, reqwest::Error> = response.text().await;
-    if status >= 300 || status < 200 {
-        return Err(ArriError::from_response_data(
+    if !(200..300).contains(&status) {
+        return Err(version::error_from_response(
             status,
             body.unwrap_or_default(),
+            &client_version,
+            |name| {
+                headers
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+                    .map(|value| value.to_string())
+            },
         ));
     }
     match body {
-        Ok(text) => return Ok(parser(text)),
-        Err(err) => {
-            return Err(ArriError {
-                code: status,
-                message: "Expected server to return plaintext".to_string(),
-                stack: None,
-                data: Some(serde_json::Value::String(err.to_string())),
-            })
-        }
+        Ok(text) => Ok(parser(text)),
+        Err(err) => Err(ArriError {
+            code: status,
+            message: "Expected server to return plaintext".to_string(),
+            stack: None,
+            data: Some(serde_json::Value::String(err.to_string())),
+        }),
     }
 }
 
@@ -461,3 +533,58 @@ fn status_message_from_status_code(status_code: u16) -> String {
         _ => String::from("Unknown Error"),
     }
 }
+
+#[cfg(test)]
+mod arri_error_tests {
+    use super::*;
+
+    #[test]
+    fn json_round_trip_test() {
+        let error = ArriError {
+            code: 400,
+            message: "Invalid \"id\"\non line 2".to_string(),
+            stack: Some("at handler\nat router".to_string()),
+            data: Some(serde_json::json!({ "field": "id", "values": [1, 2] })),
+        };
+        let json = error.to_json_string();
+        // the output must be valid JSON
+        assert!(serde_json::from_str::<serde_json::Value>(&json).is_ok());
+        let parsed = ArriError::from_json_string(json);
+        assert_eq!(parsed.code, error.code);
+        assert_eq!(parsed.message, error.message);
+        assert_eq!(parsed.stack, error.stack);
+        assert_eq!(parsed.data, error.data);
+
+        let minimal = ArriError {
+            code: 500,
+            message: "".to_string(),
+            stack: None,
+            data: None,
+        };
+        assert_eq!(minimal.to_json_string(), r#"{"code":500,"message":""}"#);
+    }
+
+    #[tokio::test]
+    async fn connection_error_code_test() {
+        // nothing is listening on the port once the listener is dropped
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let client = reqwest::Client::new();
+        let err = arri_request(
+            ArriRequestOptions {
+                http_client: &client,
+                url,
+                method: reqwest::Method::GET,
+                headers: Arc::new(RwLock::new(HashMap::new())),
+                client_version: String::new(),
+            },
+            None::<EmptyArriModel>,
+        )
+        .await
+        .unwrap_err();
+        // no response was received so there is no status to report
+        assert_eq!(err.code, 0);
+        assert_eq!(err.kind(), ArriErrorKind::Client);
+    }
+}
@@ -0,0 +1,67 @@
+use std::sync::{Arc, RwLock};
+
+pub type Handler<T> = Arc<dyn Fn(&T) + Send + Sync>;
+
+/// A replaceable handler for events of type `T`.
+/// Clones share the same handler, so a generated client and all of its services use one.
+pub struct Listener<T> {
+    handler: Arc<RwLock<Option<Handler<T>>>>,
+}
+
+impl<T> Clone for Listener<T> {
+    fn clone(&self) -> Self {
+        Self {
+            handler: self.handler.clone(),
+        }
+    }
+}
+
+impl<T> Default for Listener<T> {
+    fn default() -> Self {
+        Self {
+            handler: Arc::new(RwLock::new(None)),
+        }
+    }
+}
+
+impl<T> Listener<T> {
+    pub fn set(&self, handler: impl Fn(&T) + Send + Sync + 'static) {
+        *self.handler.write().unwrap() = Some(Arc::new(handler));
+    }
+
+    pub fn remove(&self) {
+        *self.handler.write().unwrap() = None;
+    }
+
+    pub fn notify(&self, event: &T) {
+        // the lock is released before calling the handler so that it can replace itself
+        let handler = self.handler.read().unwrap().clone();
+        if let Some(handler) = handler {
+            handler(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod listener_tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[test]
+    fn shared_handler_test() {
+        let listener = Listener::<u32>::default();
+        let child = listener.clone();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        listener.notify(&1);
+        let sink = received.clone();
+        child.set(move |event| sink.lock().unwrap().push(*event));
+        listener.notify(&2);
+        let inner = listener.clone();
+        // handlers can replace themselves without deadlocking
+        listener.set(move |_| inner.remove());
+        child.notify(&3);
+        child.notify(&4);
+        assert_eq!(*received.lock().unwrap(), vec![2]);
+    }
+}
@@ -0,0 +1,700 @@
+//! Call Rust procedure handlers in the same process without going through HTTP.
+//!
+//! Handlers are registered by procedure path on an `ArriLoopback`. A client created with
+//! `ArriClientConfig::loopback()` sends every request to those handlers instead of the network.
+//! Params and responses are passed through as-is when the client and the handler use the same
+//! types and are converted through JSON otherwise.
+use std::{
+    any::Any,
+    collections::HashMap,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, OnceLock, RwLock, Weak,
+    },
+};
+
+use tokio::sync::mpsc;
+
+use crate::{
+    sse::{SseCloseReason, SseController, SseEvent, SseHandlerOutput},
+    ArriClientConfig, ArriError, ArriModel,
+};
+
+const LOOPBACK_SCHEME: &str = "arri-loopback://";
+/// Number of messages a stream handler can send before it has to wait for the client
+const STREAM_CAPACITY: usize = 64;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+type UnaryHandler = Arc<
+    dyn Fn(Box<dyn LoopbackPayload>) -> BoxFuture<Result<Box<dyn LoopbackPayload>, ArriError>>
+        + Send
+        + Sync,
+>;
+type StreamHandler = Arc<
+    dyn Fn(
+            Box<dyn LoopbackPayload>,
+            mpsc::Sender<LoopbackEvent>,
+        ) -> BoxFuture<Result<(), ArriError>>
+        + Send
+        + Sync,
+>;
+
+/// Every live loopback by id. Clients only hold the loopback url so the handlers are looked
+/// up here on every call.
+fn registry() -> &'static Mutex<HashMap<u64, Weak<Handlers>>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<u64, Weak<Handlers>>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Default)]
+struct Handlers {
+    unary: RwLock<HashMap<String, UnaryHandler>>,
+    streams: RwLock<HashMap<String, StreamHandler>>,
+}
+
+/// A set of procedure handlers that clients can call in-process.
+///
+/// Clients created from the loopback stop working once every clone of it has been dropped.
+///
+/// ```ignore
+/// let loopback = ArriLoopback::new()
+///     .handle("/books/get-book", |params: BookParams| async move {
+///         Ok(Book { id: params.book_id, ..Book::new() })
+///     })
+///     .handle_stream("/books/watch-book", |params: BookParams, stream| async move {
+///         stream.send(Book { id: params.book_id, ..Book::new() }).await?;
+///         Ok(())
+///     });
+/// let client = ExampleClient::create(ArriClientConfig::loopback(&loopback));
+/// ```
+#[derive(Clone)]
+pub struct ArriLoopback {
+    id: u64,
+    handlers: Arc<Handlers>,
+}
+
+impl Default for ArriLoopback {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArriLoopback {
+    pub fn new() -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+        let handlers = Arc::new(Handlers::default());
+        let mut registry = registry().lock().unwrap();
+        registry.retain(|_, handlers| handlers.strong_count() > 0);
+        registry.insert(id, Arc::downgrade(&handlers));
+        Self { id, handlers }
+    }
+
+    /// Base url of clients that call this loopback
+    pub fn base_url(&self) -> String {
+        format!("{}{}", LOOPBACK_SCHEME, self.id)
+    }
+
+    /// Register the handler of a regular procedure. Errors returned by the handler are
+    /// received by the client as if the server had responded with them.
+    pub fn handle<P, R, F, Fut>(self, path: &str, handler: F) -> Self
+    where
+        P: ArriModel + Send + 'static,
+        R: ArriModel + Send + 'static,
+        F: Fn(P) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, ArriError>> + Send + 'static,
+    {
+        let handler: UnaryHandler = Arc::new(move |params| {
+            let result = handler(from_payload::<P>(params));
+            Box::pin(async move {
+                let response = result.await?;
+                Ok(Box::new(response) as Box<dyn LoopbackPayload>)
+            })
+        });
+        self.handlers
+            .unary
+            .write()
+            .unwrap()
+            .insert(path.to_string(), handler);
+        self
+    }
+
+    /// Register the handler of an event stream procedure. The stream ends with a `done` event
+    /// once the handler returns. Returning an error sends it as an `error` event and closes the
+    /// stream with `SseCloseReason::ServerError`.
+    pub fn handle_stream<P, R, F, Fut>(self, path: &str, handler: F) -> Self
+    where
+        P: ArriModel + Send + 'static,
+        R: ArriModel + Send + 'static,
+        F: Fn(P, LoopbackStream<R>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), ArriError>> + Send + 'static,
+    {
+        let handler: StreamHandler = Arc::new(move |params, events| {
+            Box::pin(handler(
+                from_payload::<P>(params),
+                LoopbackStream {
+                    events,
+                    _types: PhantomData,
+                },
+            ))
+        });
+        self.handlers
+            .streams
+            .write()
+            .unwrap()
+            .insert(path.to_string(), handler);
+        self
+    }
+}
+
+impl ArriClientConfig {
+    /// Config for a client that calls the handlers of `loopback` instead of a server.
+    /// Websocket procedures are not supported.
+    pub fn loopback(loopback: &ArriLoopback) -> Self {
+        Self {
+            http_client: reqwest::Client::new(),
+            base_url: loopback.base_url(),
+            headers: HashMap::new(),
+        }
+    }
+}
+
+/// Used by stream handlers to send messages to the client
+pub struct LoopbackStream<R> {
+    events: mpsc::Sender<LoopbackEvent>,
+    _types: PhantomData<fn(R)>,
+}
+
+impl<R: ArriModel + Send + 'static> LoopbackStream<R> {
+    /// Fails once the client has stopped listening
+    pub async fn send(&self, message: R) -> Result<(), ArriError> {
+        self.events
+            .send(LoopbackEvent::Message(Box::new(message)))
+            .await
+            .map_err(|_| disconnected_error())
+    }
+
+    /// Send an `error` event without ending the stream
+    pub async fn error(&self, error: ArriError) -> Result<(), ArriError> {
+        self.events
+            .send(LoopbackEvent::Error(error))
+            .await
+            .map_err(|_| disconnected_error())
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.events.is_closed()
+    }
+}
+
+enum LoopbackEvent {
+    Message(Box<dyn LoopbackPayload>),
+    Error(ArriError),
+}
+
+/// Params or a response moving between a client and a handler
+trait LoopbackPayload: Send {
+    fn as_any(&self) -> &dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+    fn to_json_string(&self) -> String;
+}
+
+impl<M: ArriModel + Send + 'static> LoopbackPayload for M {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+    fn to_json_string(&self) -> String {
+        ArriModel::to_json_string(self)
+    }
+}
+
+/// Params that have already been serialized
+struct JsonPayload(String);
+
+impl LoopbackPayload for JsonPayload {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+    fn to_json_string(&self) -> String {
+        self.0.clone()
+    }
+}
+
+/// Take the value out of the payload when it has the expected type.
+/// Otherwise return its JSON.
+fn downcast<M: 'static>(payload: Box<dyn LoopbackPayload>) -> Result<M, String> {
+    if !payload.as_any().is::<M>() {
+        return Err(payload.to_json_string());
+    }
+    match payload.into_any().downcast::<M>() {
+        Ok(value) => Ok(*value),
+        Err(_) => unreachable!(),
+    }
+}
+
+fn from_payload<M: ArriModel + 'static>(payload: Box<dyn LoopbackPayload>) -> M {
+    downcast::<M>(payload).unwrap_or_else(M::from_json_string)
+}
+
+fn params_payload<P: ArriModel + Send + 'static>(params: Option<P>) -> Box<dyn LoopbackPayload> {
+    match params {
+        Some(params) => Box::new(params),
+        None => Box::new(JsonPayload(String::new())),
+    }
+}
+
+pub(crate) fn is_loopback_url(url: &str) -> bool {
+    url.starts_with(LOOPBACK_SCHEME)
+}
+
+/// Split a loopback url into its handlers and the procedure path
+fn resolve(url: &str) -> Result<(Arc<Handlers>, String), ArriError> {
+    let rest = url.strip_prefix(LOOPBACK_SCHEME).unwrap_or(url);
+    let (id, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, "/"),
+    };
+    let path = path.split('?').next().unwrap_or_default().to_string();
+    id.parse::<u64>()
+        .ok()
+        .and_then(|id| registry().lock().unwrap().get(&id).and_then(Weak::upgrade))
+        .map(|handlers| (handlers, path))
+        .ok_or_else(|| ArriError {
+            code: 503,
+            message: format!("No loopback is available at \"{}\"", url),
+            stack: None,
+            data: None,
+        })
+}
+
+fn not_found_error(path: &str) -> ArriError {
+    ArriError {
+        code: 404,
+        message: format!("No loopback handler is registered for \"{}\"", path),
+        stack: None,
+        data: None,
+    }
+}
+
+fn disconnected_error() -> ArriError {
+    ArriError {
+        code: 0,
+        message: "Client disconnected".to_string(),
+        stack: None,
+        data: None,
+    }
+}
+
+async fn call_handler(
+    url: &str,
+    params: Box<dyn LoopbackPayload>,
+) -> Result<Box<dyn LoopbackPayload>, ArriError> {
+    let (handlers, path) = resolve(url)?;
+    let handler = handlers.unary.read().unwrap().get(&path).cloned();
+    match handler {
+        Some(handler) => handler(params).await,
+        None => Err(not_found_error(&path)),
+    }
+}
+
+/// Used by generated clients through `parsed_arri_request_with_headers`. The response is only parsed when the handler returned a
+/// different type than `TResponse`.
+pub(crate) async fn loopback_request<TResponse: 'static>(
+    url: &str,
+    params: Option<impl ArriModel + Send + 'static>,
+    parser: fn(body: String) -> TResponse,
+) -> Result<TResponse, ArriError> {
+    let response = call_handler(url, params_payload(params)).await?;
+    Ok(downcast::<TResponse>(response).unwrap_or_else(parser))
+}
+
+/// Used by `arri_request`, which needs an HTTP response
+pub(crate) async fn loopback_response(url: &str, params: Option<String>) -> reqwest::Response {
+    let params = Box::new(JsonPayload(params.unwrap_or_default()));
+    let (status, body) = match call_handler(url, params).await {
+        Ok(response) => (200, response.to_json_string()),
+        Err(err) => (
+            if err.code >= 400 { err.code } else { 500 },
+            ArriModel::to_json_string(&err),
+        ),
+    };
+    let mut response = http::Response::new(body);
+    *response.status_mut() =
+        http::StatusCode::from_u16(status).unwrap_or(http::StatusCode::INTERNAL_SERVER_ERROR);
+    response.headers_mut().insert(
+        http::header::CONTENT_TYPE,
+        http::HeaderValue::from_static("application/json"),
+    );
+    reqwest::Response::from(response)
+}
+
+/// Used by `parsed_arri_sse_request`. Emits `Open` straight away followed by every message the
+/// handler sends. The stream can't drop so it is never reconnected.
+pub(crate) async fn loopback_sse_request<P, T, OnEvent, OnEventOutput>(
+    url: &str,
+    params: Option<P>,
+    on_event: &mut OnEvent,
+) where
+    P: ArriModel + Send + 'static,
+    T: ArriModel + 'static,
+    OnEvent: FnMut(SseEvent<T>, &mut SseController) -> OnEventOutput,
+    OnEventOutput: SseHandlerOutput,
+{
+    let mut controller = SseController::new();
+    let handler = resolve(url).and_then(|(handlers, path)| {
+        let handler = handlers.streams.read().unwrap().get(&path).cloned();
+        handler.ok_or_else(|| not_found_error(&path))
+    });
+    let handler = match handler {
+        Ok(handler) => handler,
+        Err(err) => {
+            on_event(SseEvent::Error(err), &mut controller)
+                .into_future()
+                .await;
+            on_event(
+                SseEvent::Close(SseCloseReason::ServerError),
+                &mut controller,
+            )
+            .into_future()
+            .await;
+            return;
+        }
+    };
+    let (sender, mut receiver) = mpsc::channel(STREAM_CAPACITY);
+    let task = tokio::spawn(handler(params_payload(params), sender));
+    on_event(SseEvent::Open, &mut controller)
+        .into_future()
+        .await;
+    loop {
+        if controller.is_aborted() {
+            // the handler's next send fails so that it can stop on its own
+            drop(receiver);
+            on_event(SseEvent::Close(SseCloseReason::Aborted), &mut controller)
+                .into_future()
+                .await;
+            return;
+        }
+        let event = match receiver.recv().await {
+            Some(LoopbackEvent::Message(message)) => SseEvent::Message(from_payload::<T>(message)),
+            Some(LoopbackEvent::Error(err)) => SseEvent::Error(err),
+            None => break,
+        };
+        on_event(event, &mut controller).into_future().await;
+    }
+    let error = match task.await {
+        Ok(Ok(())) => None,
+        Ok(Err(err)) => Some(err),
+        Err(_) => Some(ArriError {
+            code: 500,
+            message: "Loopback handler panicked".to_string(),
+            stack: None,
+            data: None,
+        }),
+    };
+    let reason = match error {
+        Some(err) => {
+            on_event(SseEvent::Error(err), &mut controller)
+                .into_future()
+                .await;
+            SseCloseReason::ServerError
+        }
+        None => SseCloseReason::Done,
+    };
+    on_event(SseEvent::Close(reason), &mut controller)
+        .into_future()
+        .await;
+}
+
+#[cfg(test)]
+mod loopback_tests {
+    use std::{sync::atomic::AtomicBool, time::Duration};
+
+    use super::*;
+    use crate::{
+        arri_request, parsed_arri_request, parsed_arri_request_with_headers,
+        sse::{parsed_arri_sse_request, ArriParsedSseRequestOptions, SseOptions},
+        ArriParsedRequestOptions, ArriRequestOptions, EmptyArriModel, InternalArriClientConfig,
+    };
+
+    /// Panics when serialized so that tests fail if the typed path isn't taken
+    #[derive(Debug, Clone, PartialEq)]
+    struct Unserializable(u16);
+
+    impl ArriModel for Unserializable {
+        fn new() -> Self {
+            Self(0)
+        }
+        fn from_json(_: serde_json::Value) -> Self {
+            panic!("should not be parsed")
+        }
+        fn from_json_string(_: String) -> Self {
+            panic!("should not be parsed")
+        }
+        fn to_json_string(&self) -> String {
+            panic!("should not be serialized")
+        }
+        fn to_query_params_string(&self) -> String {
+            panic!("should not be serialized")
+        }
+    }
+
+    fn request_options<'a>(
+        config: &'a InternalArriClientConfig,
+        path: &str,
+    ) -> ArriParsedRequestOptions<'a> {
+        ArriParsedRequestOptions {
+            http_client: &config.http_client,
+            url: format!("{}{}", config.base_url, path),
+            method: reqwest::Method::POST,
+            headers: config.headers.clone(),
+            client_version: String::new(),
+        }
+    }
+
+    fn error(code: u16, message: &str) -> ArriError {
+        ArriError {
+            code,
+            message: message.to_string(),
+            stack: None,
+            data: None,
+        }
+    }
+
+    fn test_loopback() -> ArriLoopback {
+        ArriLoopback::new()
+            .handle("/typed", |params: Unserializable| async move {
+                Ok(Unserializable(params.0 + 1))
+            })
+            .handle("/json", |params: ArriError| async move {
+                Ok(error(params.code * 2, &params.message))
+            })
+            .handle("/fail", |_: EmptyArriModel| async move {
+                Err::<EmptyArriModel, _>(error(401, "Unauthorized"))
+            })
+            .handle("/empty", |_: EmptyArriModel| async move {
+                Ok(EmptyArriModel {})
+            })
+    }
+
+    #[tokio::test]
+    async fn unary_test() {
+        let loopback = test_loopback();
+        let config = InternalArriClientConfig::from(ArriClientConfig::loopback(&loopback));
+
+        // same types on both sides are never serialized
+        let result = parsed_arri_request_with_headers(
+            request_options(&config, "/typed"),
+            Some(Unserializable(1)),
+            Unserializable::from_json_string,
+            |_| {},
+        )
+        .await;
+        assert_eq!(result.unwrap(), Unserializable(2));
+
+        // not `Send`, which `parsed_arri_request` doesn't require
+        #[derive(Clone)]
+        struct Params(std::rc::Rc<u16>);
+        impl ArriModel for Params {
+            fn new() -> Self {
+                Self(std::rc::Rc::new(0))
+            }
+            fn from_json(_: serde_json::Value) -> Self {
+                Self::new()
+            }
+            fn from_json_string(_: String) -> Self {
+                Self::new()
+            }
+            fn to_json_string(&self) -> String {
+                format!(r#"{{"code":{},"message":"params"}}"#, self.0)
+            }
+            fn to_query_params_string(&self) -> String {
+                String::new()
+            }
+        }
+        // different types are converted through JSON
+        let result = parsed_arri_request(
+            request_options(&config, "/json"),
+            Some(Params(std::rc::Rc::new(4))),
+            |body| serde_json::from_str::<serde_json::Value>(&body).unwrap(),
+        )
+        .await;
+        assert_eq!(
+            result.unwrap(),
+            serde_json::json!({ "code": 8, "message": "params" })
+        );
+
+        let result = parsed_arri_request(
+            request_options(&config, "/empty"),
+            None::<EmptyArriModel>,
+            |_| {},
+        )
+        .await;
+        assert!(result.is_ok());
+
+        let result = parsed_arri_request(
+            request_options(&config, "/fail"),
+            None::<EmptyArriModel>,
+            |_| {},
+        )
+        .await;
+        assert_eq!(result.unwrap_err().code, 401);
+
+        let result = parsed_arri_request(
+            request_options(&config, "/missing"),
+            None::<EmptyArriModel>,
+            |_| {},
+        )
+        .await;
+        assert_eq!(result.unwrap_err().code, 404);
+    }
+
+    #[tokio::test]
+    async fn raw_response_test() {
+        let loopback = test_loopback();
+        let config = InternalArriClientConfig::from(ArriClientConfig::loopback(&loopback));
+        let send = |path: &str, params: Option<ArriError>| {
+            let options = ArriRequestOptions {
+                http_client: &config.http_client,
+                url: format!("{}{}", config.base_url, path),
+                method: reqwest::Method::POST,
+                headers: config.headers.clone(),
+                client_version: String::new(),
+            };
+            arri_request(options, params)
+        };
+        let response = send("/json", Some(error(3, "hi"))).await.unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+        assert_eq!(
+            response.text().await.unwrap(),
+            r#"{"code":6,"message":"hi"}"#
+        );
+        let response = send("/fail", None).await.unwrap();
+        assert_eq!(response.status().as_u16(), 401);
+        assert_eq!(
+            response.text().await.unwrap(),
+            r#"{"code":401,"message":"Unauthorized"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn dropped_loopback_test() {
+        let config = InternalArriClientConfig::from(ArriClientConfig::loopback(&test_loopback()));
+        let result = parsed_arri_request(
+            request_options(&config, "/empty"),
+            None::<EmptyArriModel>,
+            |_| {},
+        )
+        .await;
+        assert_eq!(result.unwrap_err().code, 503);
+    }
+
+    async fn listen(loopback: &ArriLoopback, path: &str, stop_after: usize) -> Vec<String> {
+        let client = reqwest::Client::new();
+        let mut events = Vec::new();
+        parsed_arri_sse_request(
+            ArriParsedSseRequestOptions {
+                client: &client,
+                client_version: String::new(),
+                url: format!("{}{}", loopback.base_url(), path),
+                method: reqwest::Method::POST,
+                headers: Arc::new(RwLock::new(HashMap::new())),
+                deprecation: None,
+                version_mismatch: None,
+                options: SseOptions::default(),
+            },
+            Some(Unserializable(3)),
+            &mut |event: SseEvent<Unserializable>, controller| {
+                events.push(match event {
+                    SseEvent::Message(message) => format!("message {}", message.0),
+                    SseEvent::Error(err) => format!("error {}", err.code),
+                    SseEvent::Open => "open".to_string(),
+                    SseEvent::Close(reason) => format!("close {:?}", reason),
+                    _ => "other".to_string(),
+                });
+                if events.len() == stop_after {
+                    controller.abort();
+                }
+            },
+        )
+        .await;
+        events
+    }
+
+    #[tokio::test]
+    async fn event_stream_test() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let handler_finished = finished.clone();
+        let loopback = ArriLoopback::new()
+            .handle_stream(
+                "/count",
+                |params: Unserializable, stream: LoopbackStream<Unserializable>| async move {
+                    for i in 0..params.0 {
+                        stream.send(Unserializable(i)).await?;
+                    }
+                    stream.error(error(409, "Conflict")).await?;
+                    Ok(())
+                },
+            )
+            .handle_stream(
+                "/fail",
+                |_: Unserializable, _: LoopbackStream<Unserializable>| async move {
+                    Err(error(500, "Failed"))
+                },
+            )
+            .handle_stream(
+                "/forever",
+                move |_: Unserializable, stream: LoopbackStream<Unserializable>| {
+                    let finished = handler_finished.clone();
+                    async move {
+                        let mut i = 0;
+                        while stream.send(Unserializable(i)).await.is_ok() {
+                            i += 1;
+                        }
+                        finished.store(true, Ordering::SeqCst);
+                        Ok(())
+                    }
+                },
+            );
+        assert_eq!(
+            listen(&loopback, "/count", 0).await,
+            vec![
+                "open",
+                "message 0",
+                "message 1",
+                "message 2",
+                "error 409",
+                "close Done"
+            ]
+        );
+        assert_eq!(
+            listen(&loopback, "/fail", 0).await,
+            vec!["open", "error 500", "close ServerError"]
+        );
+        assert_eq!(
+            listen(&loopback, "/missing", 0).await,
+            vec!["error 404", "close ServerError"]
+        );
+        assert_eq!(
+            listen(&loopback, "/forever", 3).await,
+            vec!["open", "message 0", "message 1", "close Aborted"]
+        );
+        // the handler stops once the client has stopped listening
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while !finished.load(Ordering::SeqCst) {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+}
@@ -0,0 +1,487 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Ready,
+    sync::{Arc, Mutex, RwLock},
+};
+
+use futures_util::{SinkExt, StreamExt};
+use tokio_tungstenite::{
+    tungstenite::{protocol::Role, Message},
+    WebSocketStream,
+};
+
+use crate::{
+    sse::{SseCloseReason, SseController, SseEvent, SseHandlerOutput},
+    ws::{ArriParsedWsRequestOptions, WsConnection, WsOptions},
+    ArriError, ArriModel,
+};
+
+type OnceResponse<P, R> = Box<dyn FnOnce(&P) -> Result<R, ArriError> + Send>;
+type Response<P, R> = Arc<dyn Fn(&P) -> Result<R, ArriError> + Send + Sync>;
+
+struct MockProcedureState<P, R> {
+    once: VecDeque<OnceResponse<P, R>>,
+    default: Option<Response<P, R>>,
+    calls: Vec<P>,
+}
+
+/// Stands in for a unary procedure in generated mock services.
+///
+/// Queued `returns_once` responses are used first, then the `returns` or `returns_with` response.
+/// Calls without a response panic. Clones share the same expectations and recorded calls.
+pub struct MockProcedure<P, R> {
+    name: &'static str,
+    state: Arc<Mutex<MockProcedureState<P, R>>>,
+}
+
+impl<P, R> MockProcedure<P, R> {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            state: Arc::new(Mutex::new(MockProcedureState {
+                once: VecDeque::new(),
+                default: None,
+                calls: Vec::new(),
+            })),
+        }
+    }
+
+    /// Respond to every call with `result`
+    pub fn returns(&self, result: Result<R, ArriError>) -> &Self
+    where
+        R: Clone + Send + Sync + 'static,
+    {
+        self.returns_with(move |_| result.clone())
+    }
+
+    /// Respond to every call with the output of `response`
+    pub fn returns_with<F>(&self, response: F) -> &Self
+    where
+        F: Fn(&P) -> Result<R, ArriError> + Send + Sync + 'static,
+    {
+        self.state.lock().unwrap().default = Some(Arc::new(response));
+        self
+    }
+
+    /// Respond to the next call with `result`. Can be called multiple times to queue responses.
+    pub fn returns_once(&self, result: Result<R, ArriError>) -> &Self
+    where
+        R: Send + 'static,
+    {
+        self.state
+            .lock()
+            .unwrap()
+            .once
+            .push_back(Box::new(move |_| result));
+        self
+    }
+
+    /// The params of every call so far
+    pub fn calls(&self) -> Vec<P>
+    where
+        P: Clone,
+    {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    pub fn call_count(&self) -> usize {
+        self.state.lock().unwrap().calls.len()
+    }
+
+    /// Remove all responses and recorded calls
+    pub fn reset(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.once.clear();
+        state.default = None;
+        state.calls.clear();
+    }
+
+    pub fn call(&self, params: P) -> Ready<Result<R, ArriError>> {
+        // the lock is released before running the response so that it can use the mock
+        let (once, default) = {
+            let mut state = self.state.lock().unwrap();
+            (state.once.pop_front(), state.default.clone())
+        };
+        let result = match (once, default) {
+            (Some(response), _) => response(&params),
+            (None, Some(response)) => response(&params),
+            (None, None) => panic!("Unexpected call to \"{}\"", self.name),
+        };
+        self.state.lock().unwrap().calls.push(params);
+        std::future::ready(result)
+    }
+}
+
+impl<P, R> Clone for MockProcedure<P, R> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name,
+            state: self.state.clone(),
+        }
+    }
+}
+
+struct MockEventStreamState<P, T> {
+    once: VecDeque<Vec<SseEvent<T>>>,
+    default: Option<Vec<SseEvent<T>>>,
+    calls: Vec<P>,
+}
+
+/// Stands in for an event stream procedure in generated mock services.
+///
+/// Each call sends the scripted events to the handler in order. If the handler aborts, the rest
+/// of the script is skipped and the handler receives `SseEvent::Close(SseCloseReason::Aborted)`,
+/// the same as with a real event stream. Queued `emits_once` scripts are used first, then the `emits` script.
+/// Calls without a script panic. Clones share the same scripts and recorded calls.
+pub struct MockEventStream<P, T> {
+    name: &'static str,
+    state: Arc<Mutex<MockEventStreamState<P, T>>>,
+}
+
+impl<P, T: Clone> MockEventStream<P, T> {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            state: Arc::new(Mutex::new(MockEventStreamState {
+                once: VecDeque::new(),
+                default: None,
+                calls: Vec::new(),
+            })),
+        }
+    }
+
+    /// Send `events` on every call
+    pub fn emits(&self, events: Vec<SseEvent<T>>) -> &Self {
+        self.state.lock().unwrap().default = Some(events);
+        self
+    }
+
+    /// Send `events` on the next call. Can be called multiple times to queue scripts.
+    pub fn emits_once(&self, events: Vec<SseEvent<T>>) -> &Self {
+        self.state.lock().unwrap().once.push_back(events);
+        self
+    }
+
+    /// The params of every call so far
+    pub fn calls(&self) -> Vec<P>
+    where
+        P: Clone,
+    {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    pub fn call_count(&self) -> usize {
+        self.state.lock().unwrap().calls.len()
+    }
+
+    /// Remove all scripts and recorded calls
+    pub fn reset(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.once.clear();
+        state.default = None;
+        state.calls.clear();
+    }
+
+    pub async fn call<OnEvent, OnEventOutput>(&self, params: P, on_event: &mut OnEvent)
+    where
+        OnEvent: FnMut(SseEvent<T>, &mut SseController) -> OnEventOutput,
+        OnEventOutput: SseHandlerOutput,
+    {
+        let events = {
+            let mut state = self.state.lock().unwrap();
+            let events = match state.once.pop_front() {
+                Some(events) => events,
+                None => match &state.default {
+                    Some(events) => events.clone(),
+                    None => panic!("Unexpected call to \"{}\"", self.name),
+                },
+            };
+            state.calls.push(params);
+            events
+        };
+        let mut controller = SseController::new();
+        for event in events {
+            let is_close = matches!(event, SseEvent::Close(_));
+            on_event(event, &mut controller).into_future().await;
+            if controller.is_aborted() {
+                if !is_close {
+                    on_event(SseEvent::Close(SseCloseReason::Aborted), &mut controller)
+                        .into_future()
+                        .await;
+                }
+                break;
+            }
+        }
+    }
+}
+
+impl<P, T> Clone for MockEventStream<P, T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name,
+            state: self.state.clone(),
+        }
+    }
+}
+
+type WsReply<P, T> = Arc<dyn Fn(&P) -> Vec<Result<T, ArriError>> + Send + Sync>;
+
+struct MockWsProcedureState<P, T> {
+    once: VecDeque<Vec<Result<T, ArriError>>>,
+    default: Option<Vec<Result<T, ArriError>>>,
+    reply: Option<WsReply<P, T>>,
+    received: Vec<P>,
+    call_count: usize,
+}
+
+/// Stands in for a websocket procedure in generated mock services.
+///
+/// Each call opens an in-memory websocket to a task that sends the scripted messages
+/// (`Err` values are sent as `error` events) and then answers every message from the client
+/// with the output of `replies_with`. Queued `sends_once` scripts are used first, then the
+/// `sends` script. Calls without a script panic. There is no server to reconnect to, so
+/// reconnects fail. Clones share the same scripts and recorded messages.
+pub struct MockWsProcedure<P, T> {
+    name: &'static str,
+    state: Arc<Mutex<MockWsProcedureState<P, T>>>,
+}
+
+impl<P, T> MockWsProcedure<P, T>
+where
+    P: ArriModel + Send + 'static,
+    T: ArriModel + Clone + Send + 'static,
+{
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            state: Arc::new(Mutex::new(MockWsProcedureState {
+                once: VecDeque::new(),
+                default: None,
+                reply: None,
+                received: Vec::new(),
+                call_count: 0,
+            })),
+        }
+    }
+
+    /// Send `messages` after every connection is opened
+    pub fn sends(&self, messages: Vec<Result<T, ArriError>>) -> &Self {
+        self.state.lock().unwrap().default = Some(messages);
+        self
+    }
+
+    /// Send `messages` after the next connection is opened. Can be called multiple times to queue scripts.
+    pub fn sends_once(&self, messages: Vec<Result<T, ArriError>>) -> &Self {
+        self.state.lock().unwrap().once.push_back(messages);
+        self
+    }
+
+    /// Answer every message from the client with the output of `reply`
+    pub fn replies_with<F>(&self, reply: F) -> &Self
+    where
+        F: Fn(&P) -> Vec<Result<T, ArriError>> + Send + Sync + 'static,
+    {
+        self.state.lock().unwrap().reply = Some(Arc::new(reply));
+        self
+    }
+
+    /// Every message the client has sent so far
+    pub fn received(&self) -> Vec<P>
+    where
+        P: Clone,
+    {
+        self.state.lock().unwrap().received.clone()
+    }
+
+    pub fn call_count(&self) -> usize {
+        self.state.lock().unwrap().call_count
+    }
+
+    /// Remove all scripts, replies and recorded messages
+    pub fn reset(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.once.clear();
+        state.default = None;
+        state.reply = None;
+        state.received.clear();
+        state.call_count = 0;
+    }
+
+    pub async fn call(&self, options: WsOptions) -> Result<WsConnection<P, T>, ArriError> {
+        let messages = {
+            let mut state = self.state.lock().unwrap();
+            let messages = match state.once.pop_front() {
+                Some(messages) => messages,
+                None => match &state.default {
+                    Some(messages) => messages.clone(),
+                    None => panic!("Unexpected call to \"{}\"", self.name),
+                },
+            };
+            state.call_count += 1;
+            messages
+        };
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let state = self.state.clone();
+        tokio::spawn(async move {
+            let mut socket = WebSocketStream::from_raw_socket(server, Role::Server, None).await;
+            for message in messages {
+                if socket.send(ws_frame(message)).await.is_err() {
+                    return;
+                }
+            }
+            while let Some(Ok(frame)) = socket.next().await {
+                let Message::Text(text) = frame else {
+                    continue;
+                };
+                let message = P::from_json_string(text.to_string());
+                // the lock is released before calling `reply` so that it can use the mock
+                let reply = state.lock().unwrap().reply.clone();
+                let replies = reply.map(|reply| reply(&message)).unwrap_or_default();
+                state.lock().unwrap().received.push(message);
+                for reply in replies {
+                    if socket.send(ws_frame(reply)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        let socket = WebSocketStream::from_raw_socket(client, Role::Client, None).await;
+        Ok(WsConnection::from_socket(
+            ArriParsedWsRequestOptions {
+                // not a valid websocket url, so reconnect attempts fail straight away
+                url: self.name.to_string(),
+                headers: Arc::new(RwLock::new(HashMap::new())),
+                client_version: String::new(),
+                deprecation: None,
+                version_mismatch: None,
+                options,
+            },
+            socket,
+        ))
+    }
+}
+
+impl<P, T> Clone for MockWsProcedure<P, T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name,
+            state: self.state.clone(),
+        }
+    }
+}
+
+fn ws_frame<T: ArriModel>(message: Result<T, ArriError>) -> Message {
+    match message {
+        Ok(message) => Message::text(format!(
+            "event: message\ndata: {}",
+            message.to_json_string()
+        )),
+        Err(err) => Message::text(format!("event: error\ndata: {}", err.to_json_string())),
+    }
+}
+
+#[cfg(test)]
+mod mock_tests {
+    use super::*;
+    use crate::{ArriModel, EmptyArriModel};
+
+    fn error(code: u16) -> ArriError {
+        ArriError {
+            code,
+            message: "".to_string(),
+            stack: None,
+            data: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn procedure_test() {
+        let mock = MockProcedure::<ArriError, u16>::new("get_code");
+        mock.returns_once(Err(error(500)))
+            .returns_with(|params| Ok(params.code + 1));
+        let shared = mock.clone();
+        assert_eq!(shared.call(error(1)).await.unwrap_err().code, 500);
+        assert_eq!(shared.call(error(2)).await.unwrap(), 3);
+        assert_eq!(shared.call(error(3)).await.unwrap(), 4);
+        let codes: Vec<u16> = mock.calls().iter().map(|params| params.code).collect();
+        assert_eq!(codes, vec![1, 2, 3]);
+
+        mock.reset();
+        mock.returns(Ok(10));
+        assert_eq!(mock.call(error(1)).await.unwrap(), 10);
+        assert_eq!(mock.call_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn reentrant_response_test() {
+        let mock = MockProcedure::<EmptyArriModel, usize>::new("count_calls");
+        let inner = mock.clone();
+        // responses can read the mock without deadlocking
+        mock.returns_with(move |_| Ok(inner.call_count()));
+        assert_eq!(mock.call(EmptyArriModel::new()).await.unwrap(), 0);
+        assert_eq!(mock.call(EmptyArriModel::new()).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Unexpected call to \"get_code\"")]
+    async fn unexpected_call_test() {
+        let mock = MockProcedure::<EmptyArriModel, ()>::new("get_code");
+        let _ = mock.call(EmptyArriModel::new()).await;
+    }
+
+    #[tokio::test]
+    async fn event_stream_test() {
+        let mock = MockEventStream::<EmptyArriModel, u16>::new("watch_codes");
+        mock.emits(vec![
+            SseEvent::Open,
+            SseEvent::Message(1),
+            SseEvent::Message(2),
+            SseEvent::Close(SseCloseReason::Done),
+        ]);
+        let mut codes = Vec::new();
+        let mut closed = false;
+        mock.call(EmptyArriModel::new(), &mut |event, _| match event {
+            SseEvent::Message(code) => codes.push(code),
+            SseEvent::Close(_) => closed = true,
+            _ => {}
+        })
+        .await;
+        assert_eq!(codes, vec![1, 2]);
+        assert!(closed);
+
+        // aborting stops the script and closes the stream
+        let mut codes = Vec::new();
+        let mut close_reasons = Vec::new();
+        mock.call(
+            EmptyArriModel::new(),
+            &mut |event, controller| match event {
+                SseEvent::Message(code) => {
+                    codes.push(code);
+                    controller.abort();
+                }
+                SseEvent::Close(reason) => close_reasons.push(reason),
+                _ => {}
+            },
+        )
+        .await;
+        assert_eq!(codes, vec![1]);
+        assert_eq!(close_reasons, vec![SseCloseReason::Aborted]);
+        assert_eq!(mock.call_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn ws_procedure_test() {
+        let mock = MockWsProcedure::<ArriError, ArriError>::new("create_connection");
+        mock.sends(vec![Ok(error(1)), Err(error(500))])
+            .replies_with(|message| vec![Ok(error(message.code + 1))]);
+        let mut connection = mock.clone().call(WsOptions::default()).await.unwrap();
+        assert_eq!(connection.receive().await.unwrap().unwrap().code, 1);
+        assert_eq!(connection.receive().await.unwrap().unwrap_err().code, 500);
+        connection.send(&error(10)).await.unwrap();
+        assert_eq!(connection.receive().await.unwrap().unwrap().code, 11);
+        connection.close().await;
+        let codes: Vec<u16> = mock.received().iter().map(|message| message.code).collect();
+        assert_eq!(codes, vec![10]);
+        assert_eq!(mock.call_count(), 1);
+    }
+}
@@ -0,0 +1,749 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, RwLock},
+};
+
+use crate::{
+    deprecation::DeprecationNotice,
+    drift::{self, DefinitionDrift, DEFAULT_DEFINITION_PATH},
+    parsed_arri_request_with_headers,
+    sse::{ReconnectionPolicy, SseFailure, SseFailureKind},
+    version::VersionMismatch,
+    ArriError, ArriModel, ArriParsedRequestOptions, EmptyArriModel, InternalArriClientConfig,
+};
+
+/// Describes a single procedure of an Arri server.
+///
+/// Generated clients implement this trait on a unit struct for every http procedure,
+/// e.g. `ExampleClientBooksGetBookProcedure`, so procedures can be passed around as types.
+pub trait ArriProcedure: Send + Sync + 'static {
+    /// `EmptyArriModel` when the procedure doesn't take any params
+    type Params: ArriModel + Clone + Send + Sync + 'static;
+    /// `()` when the procedure doesn't return anything.
+    /// For event stream procedures this is the type of each message.
+    type Response: Send + 'static;
+    const PATH: &'static str;
+    const METHOD: reqwest::Method;
+    const IS_EVENT_STREAM: bool;
+    const IS_DEPRECATED: bool;
+    const CLIENT_VERSION: &'static str;
+
+    fn parse_response(body: String) -> Self::Response;
+}
+
+/// Describes a websocket procedure of an Arri server.
+///
+/// Generated clients implement this trait on a unit struct for every websocket procedure,
+/// e.g. `ExampleClientBooksCreateConnectionProcedure`.
+pub trait ArriWsProcedure: Send + Sync + 'static {
+    /// Messages sent to the server. `EmptyArriModel` when the procedure doesn't define them.
+    type Params: ArriModel + Send + 'static;
+    /// Messages received from the server. `EmptyArriModel` when the procedure doesn't define them.
+    type Response: ArriModel + Send + 'static;
+    const PATH: &'static str;
+    const IS_DEPRECATED: bool;
+    const CLIENT_VERSION: &'static str;
+}
+
+pub type ProcedureResult<P> = Result<<P as ArriProcedure>::Response, ArriError>;
+
+pub type ProcedureFuture<P> = Pin<Box<dyn Future<Output = ProcedureResult<P>> + Send>>;
+
+pub type ProcedureMiddleware<P> =
+    Arc<dyn Fn(<P as ArriProcedure>::Params, ProcedureNext<P>) -> ProcedureFuture<P> + Send + Sync>;
+
+pub type ProcedureMock<P> =
+    Arc<dyn Fn(<P as ArriProcedure>::Params) -> ProcedureResult<P> + Send + Sync>;
+
+/// Options that apply to every unary call of the procedure `P`.
+/// Event stream procedures ignore these options.
+pub struct ProcedureOptions<P: ArriProcedure> {
+    /// Run in order around the request. Each middleware decides whether to call the next one.
+    pub middleware: Vec<ProcedureMiddleware<P>>,
+    /// Decides if and when a failed request is sent again.
+    /// Defaults to None, meaning failed requests are never retried.
+    pub retry: Option<Arc<dyn ReconnectionPolicy>>,
+    /// Replaces the request to the server. Middleware and retries still apply.
+    pub mock: Option<ProcedureMock<P>>,
+}
+
+impl<P: ArriProcedure> Default for ProcedureOptions<P> {
+    fn default() -> Self {
+        Self {
+            middleware: Vec::new(),
+            retry: None,
+            mock: None,
+        }
+    }
+}
+
+impl<P: ArriProcedure> Clone for ProcedureOptions<P> {
+    fn clone(&self) -> Self {
+        Self {
+            middleware: self.middleware.clone(),
+            retry: self.retry.clone(),
+            mock: self.mock.clone(),
+        }
+    }
+}
+
+impl<P: ArriProcedure> ProcedureOptions<P> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ```
+    /// use arri_client::procedure::{ArriProcedure, ProcedureOptions};
+    ///
+    /// fn logged<P: ArriProcedure>() -> ProcedureOptions<P> {
+    ///     ProcedureOptions::new().middleware(|params, next| async move {
+    ///         println!("calling {}", P::PATH);
+    ///         next.run(params).await
+    ///     })
+    /// }
+    /// ```
+    pub fn middleware<F, Fut>(mut self, middleware: F) -> Self
+    where
+        F: Fn(P::Params, ProcedureNext<P>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ProcedureResult<P>> + Send + 'static,
+    {
+        self.middleware.push(Arc::new(move |params, next| {
+            Box::pin(middleware(params, next))
+        }));
+        self
+    }
+
+    pub fn retry(mut self, policy: impl ReconnectionPolicy + 'static) -> Self {
+        self.retry = Some(Arc::new(policy));
+        self
+    }
+
+    pub fn mock<F>(mut self, mock: F) -> Self
+    where
+        F: Fn(P::Params) -> ProcedureResult<P> + Send + Sync + 'static,
+    {
+        self.mock = Some(Arc::new(mock));
+        self
+    }
+}
+
+/// The rest of the middleware chain, followed by the request itself
+pub struct ProcedureNext<P: ArriProcedure> {
+    config: InternalArriClientConfig,
+    options: Arc<ProcedureOptions<P>>,
+    index: usize,
+}
+
+impl<P: ArriProcedure> ProcedureNext<P> {
+    pub fn run(self, params: P::Params) -> ProcedureFuture<P> {
+        Box::pin(async move {
+            match self.options.middleware.get(self.index).cloned() {
+                Some(middleware) => {
+                    let next = Self {
+                        config: self.config,
+                        options: self.options,
+                        index: self.index + 1,
+                    };
+                    middleware(params, next).await
+                }
+                None => send_with_retry(&self.config, &self.options, params).await,
+            }
+        })
+    }
+}
+
+/// Procedure options of a client, keyed by procedure type.
+/// Shared by a generated client and all of its services.
+#[derive(Clone, Default)]
+pub struct ArriProcedureOptions {
+    options: Arc<RwLock<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>>,
+}
+
+impl ArriProcedureOptions {
+    pub fn set<P: ArriProcedure>(&self, options: ProcedureOptions<P>) {
+        self.options
+            .write()
+            .unwrap()
+            .insert(TypeId::of::<P>(), Arc::new(options));
+    }
+
+    pub fn get<P: ArriProcedure>(&self) -> Option<ProcedureOptions<P>> {
+        self.options
+            .read()
+            .unwrap()
+            .get(&TypeId::of::<P>())
+            .and_then(|options| options.downcast_ref::<ProcedureOptions<P>>())
+            .cloned()
+    }
+
+    pub fn remove<P: ArriProcedure>(&self) {
+        self.options.write().unwrap().remove(&TypeId::of::<P>());
+    }
+}
+
+/// Call the unary procedure `P`, applying any options configured for it
+pub async fn arri_procedure_request<P: ArriProcedure>(
+    config: &InternalArriClientConfig,
+    params: P::Params,
+) -> ProcedureResult<P> {
+    if P::IS_EVENT_STREAM {
+        return Err(ArriError {
+            code: 0,
+            message: format!(
+                "\"{}\" is an event stream procedure and can't be called as a unary procedure",
+                P::PATH
+            ),
+            stack: None,
+            data: None,
+        });
+    }
+    if P::IS_DEPRECATED {
+        config.deprecation.procedure_called(P::PATH);
+    }
+    match config.procedure_options.get::<P>() {
+        Some(options) => {
+            ProcedureNext {
+                config: config.clone(),
+                options: Arc::new(options),
+                index: 0,
+            }
+            .run(params)
+            .await
+        }
+        None => send_procedure_request::<P>(config, params).await,
+    }
+}
+
+async fn send_with_retry<P: ArriProcedure>(
+    config: &InternalArriClientConfig,
+    options: &ProcedureOptions<P>,
+    params: P::Params,
+) -> ProcedureResult<P> {
+    let mut attempt = 0;
+    loop {
+        let result = match &options.mock {
+            Some(mock) => mock(params.clone()),
+            None => send_procedure_request::<P>(config, params.clone()).await,
+        };
+        let err = match (result, &options.retry) {
+            (Err(err), Some(_)) => err,
+            (result, _) => return result,
+        };
+        attempt += 1;
+        let failure = SseFailure {
+            kind: match err.code {
+                400..=599 => SseFailureKind::Status(err.code),
+                _ => SseFailureKind::Connection,
+            },
+            attempt,
+            server_retry: None,
+        };
+        match options
+            .retry
+            .as_ref()
+            .and_then(|policy| policy.next_delay(&failure))
+        {
+            Some(delay) => tokio::time::sleep(delay).await,
+            None => return Err(err),
+        }
+    }
+}
+
+async fn send_procedure_request<P: ArriProcedure>(
+    config: &InternalArriClientConfig,
+    params: P::Params,
+) -> ProcedureResult<P> {
+    let params = if TypeId::of::<P::Params>() == TypeId::of::<EmptyArriModel>() {
+        None
+    } else {
+        Some(params)
+    };
+    let result = parsed_arri_request_with_headers(
+        ArriParsedRequestOptions {
+            http_client: &config.http_client,
+            url: format!("{}{}", &config.base_url, P::PATH),
+            method: P::METHOD,
+            headers: config.headers.clone(),
+            client_version: P::CLIENT_VERSION.to_string(),
+        },
+        params,
+        P::parse_response,
+        |headers| {
+            if let Some(notice) = DeprecationNotice::from_headers(P::PATH, headers) {
+                config.deprecation.notify(&notice);
+            }
+        },
+    )
+    .await;
+    if let Err(err) = &result {
+        config.version_mismatch.notify_error(err);
+    }
+    result
+}
+
+/// Implemented by generated clients and services
+pub trait ArriProcedureClient {
+    fn procedure_config(&self) -> &InternalArriClientConfig;
+
+    /// Call any unary procedure by its type.
+    ///
+    /// ```ignore
+    /// let book = client.call::<ExampleClientBooksGetBookProcedure>(params).await?;
+    /// ```
+    fn call<P: ArriProcedure>(
+        &self,
+        params: P::Params,
+    ) -> impl Future<Output = ProcedureResult<P>> + Send {
+        let config = self.procedure_config().clone();
+        async move { arri_procedure_request::<P>(&config, params).await }
+    }
+
+    /// Replace the options of the procedure `P` for this client and all of its services
+    fn configure_procedure<P: ArriProcedure>(&self, options: ProcedureOptions<P>) {
+        self.procedure_config().procedure_options.set(options);
+    }
+
+    fn reset_procedure<P: ArriProcedure>(&self) {
+        self.procedure_config().procedure_options.remove::<P>();
+    }
+
+    /// Called whenever the server rejects the client version of a procedure call, event stream
+    /// or websocket handshake, e.g. to prompt for an app update. Replaces the previous handler of
+    /// the client and its services.
+    fn on_version_mismatch(&self, handler: impl Fn(&VersionMismatch) + Send + Sync + 'static) {
+        self.procedure_config().version_mismatch.set(handler);
+    }
+
+    /// Called whenever a procedure that is deprecated in the app definition is called,
+    /// and whenever the server responds to a call, opens an event stream or accepts a websocket
+    /// handshake with a `deprecation` or `sunset` header.
+    /// Replaces the previous handler of the client and its services.
+    fn on_deprecation(&self, handler: impl Fn(&DeprecationNotice) + Send + Sync + 'static) {
+        self.procedure_config().deprecation.set(handler);
+    }
+
+    /// Compare the client with the app definition served at `/__definition`.
+    /// Nothing is checked unless this is called, e.g. once at startup.
+    ///
+    /// ```ignore
+    /// let drift = client.check_definition().await?;
+    /// for mismatch in &drift.mismatches {
+    ///     eprintln!("{}", mismatch);
+    /// }
+    /// ```
+    fn check_definition(&self) -> impl Future<Output = Result<DefinitionDrift, ArriError>> + Send
+    where
+        Self: ArriProcedureRegistry,
+    {
+        self.check_definition_at(DEFAULT_DEFINITION_PATH)
+    }
+
+    /// Same as `check_definition` for servers that serve their app definition at another path
+    fn check_definition_at(
+        &self,
+        path: &str,
+    ) -> impl Future<Output = Result<DefinitionDrift, ArriError>> + Send
+    where
+        Self: ArriProcedureRegistry,
+    {
+        let config = self.procedure_config().clone();
+        let path = path.to_string();
+        async move { drift::check_definition::<Self>(&config, &path).await }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcedureTransport {
+    Http,
+    Ws,
+}
+
+/// Describes a procedure of a generated client at runtime
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcedureInfo {
+    /// The name of the procedure in the app definition, e.g. `books.getBook`
+    pub name: &'static str,
+    pub path: &'static str,
+    /// None for websocket procedures
+    pub method: Option<reqwest::Method>,
+    pub transport: ProcedureTransport,
+    pub is_event_stream: bool,
+    pub is_deprecated: bool,
+    pub description: Option<&'static str>,
+    /// The name of the params type in the app definition
+    pub params: Option<&'static str>,
+    /// The name of the response type in the app definition
+    pub response: Option<&'static str>,
+}
+
+/// Implemented by generated clients to list their procedures at runtime
+pub trait ArriProcedureRegistry {
+    /// Hash of the app definition the client was generated from.
+    /// See `definition::definition_hash`. Empty for clients generated before hashes were
+    /// embedded, which `DefinitionDrift` reports as `MismatchKind::HashesMissing`.
+    const DEFINITION_HASH: &'static str = "";
+    /// Every procedure of the client, including the procedures of its services
+    const PROCEDURES: &'static [ProcedureInfo];
+    /// Hash of every type in the app definition by name.
+    /// See `definition::type_definition_hash`. Only checked when `DEFINITION_HASH` is set.
+    const TYPE_HASHES: &'static [(&'static str, &'static str)] = &[];
+
+    fn procedure(name: &str) -> Option<&'static ProcedureInfo> {
+        Self::PROCEDURES
+            .iter()
+            .find(|procedure| procedure.name == name)
+    }
+
+    fn procedure_by_path(path: &str) -> Option<&'static ProcedureInfo> {
+        Self::PROCEDURES
+            .iter()
+            .find(|procedure| procedure.path == path)
+    }
+}
+
+#[cfg(test)]
+mod procedure_tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+        time::Duration,
+    };
+
+    use super::*;
+    use crate::{
+        deprecation::DeprecationSource,
+        loopback::ArriLoopback,
+        sse::{
+            testing::{SseScript, SseTestServer},
+            FixedDelay, ReconnectionPolicy,
+        },
+        ArriClientConfig, ArriErrorKind,
+    };
+
+    fn error(code: u16, message: &str) -> ArriError {
+        ArriError {
+            code,
+            message: message.to_string(),
+            stack: None,
+            data: None,
+        }
+    }
+
+    struct EchoProcedure;
+
+    impl ArriProcedure for EchoProcedure {
+        type Params = ArriError;
+        type Response = ArriError;
+        const PATH: &'static str = "/echo";
+        const METHOD: reqwest::Method = reqwest::Method::POST;
+        const IS_EVENT_STREAM: bool = false;
+        const IS_DEPRECATED: bool = false;
+        const CLIENT_VERSION: &'static str = "1";
+
+        fn parse_response(body: String) -> Self::Response {
+            ArriError::from_json_string(body)
+        }
+    }
+
+    struct FlakyProcedure;
+
+    impl ArriProcedure for FlakyProcedure {
+        type Params = EmptyArriModel;
+        type Response = ();
+        const PATH: &'static str = "/flaky";
+        const METHOD: reqwest::Method = reqwest::Method::GET;
+        const IS_EVENT_STREAM: bool = false;
+        const IS_DEPRECATED: bool = false;
+        const CLIENT_VERSION: &'static str = "1";
+
+        fn parse_response(_: String) -> Self::Response {}
+    }
+
+    struct WatchProcedure;
+
+    impl ArriProcedure for WatchProcedure {
+        type Params = EmptyArriModel;
+        type Response = EmptyArriModel;
+        const PATH: &'static str = "/watch";
+        const METHOD: reqwest::Method = reqwest::Method::GET;
+        const IS_EVENT_STREAM: bool = true;
+        const IS_DEPRECATED: bool = false;
+        const CLIENT_VERSION: &'static str = "1";
+
+        fn parse_response(body: String) -> Self::Response {
+            EmptyArriModel::from_json_string(body)
+        }
+    }
+
+    struct DeprecatedProcedure;
+
+    impl ArriProcedure for DeprecatedProcedure {
+        type Params = ArriError;
+        type Response = ArriError;
+        const PATH: &'static str = "/echo";
+        const METHOD: reqwest::Method = reqwest::Method::POST;
+        const IS_EVENT_STREAM: bool = false;
+        const IS_DEPRECATED: bool = true;
+        const CLIENT_VERSION: &'static str = "1";
+
+        fn parse_response(body: String) -> Self::Response {
+            ArriError::from_json_string(body)
+        }
+    }
+
+    struct TestClient {
+        _config: InternalArriClientConfig,
+    }
+
+    impl ArriProcedureClient for TestClient {
+        fn procedure_config(&self) -> &InternalArriClientConfig {
+            &self._config
+        }
+    }
+
+    fn test_client(attempts: Arc<AtomicUsize>) -> (ArriLoopback, TestClient) {
+        let loopback = ArriLoopback::new()
+            .handle("/echo", |params: ArriError| async move {
+                Ok(error(params.code, &format!("echo {}", params.message)))
+            })
+            .handle("/flaky", move |_: EmptyArriModel| {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    match attempt {
+                        0 => Err(error(503, "Service Unavailable")),
+                        1 => Err(error(500, "Internal Server Error")),
+                        _ => Ok(EmptyArriModel {}),
+                    }
+                }
+            });
+        let client = TestClient {
+            _config: InternalArriClientConfig::from(ArriClientConfig::loopback(&loopback)),
+        };
+        (loopback, client)
+    }
+
+    #[tokio::test]
+    async fn call_test() {
+        let (_loopback, client) = test_client(Arc::new(AtomicUsize::new(0)));
+        let result = client.call::<EchoProcedure>(error(1, "hi")).await.unwrap();
+        assert_eq!(result.code, 1);
+        assert_eq!(result.message, "echo hi");
+        let result = client.call::<WatchProcedure>(EmptyArriModel {}).await;
+        assert_eq!(result.unwrap_err().code, 0);
+    }
+
+    #[tokio::test]
+    async fn middleware_test() {
+        let (_loopback, client) = test_client(Arc::new(AtomicUsize::new(0)));
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let outer_order = order.clone();
+        let inner_order = order.clone();
+        client.configure_procedure(
+            ProcedureOptions::<EchoProcedure>::new()
+                .middleware(move |mut params, next| {
+                    outer_order.lock().unwrap().push("outer");
+                    params.message.push_str(" outer");
+                    next.run(params)
+                })
+                .middleware(move |mut params, next| {
+                    inner_order.lock().unwrap().push("inner");
+                    params.message.push_str(" inner");
+                    async move {
+                        let mut result = next.run(params).await?;
+                        result.code += 1;
+                        Ok(result)
+                    }
+                }),
+        );
+        let result = client.call::<EchoProcedure>(error(1, "hi")).await.unwrap();
+        assert_eq!(result.code, 2);
+        assert_eq!(result.message, "echo hi outer inner");
+        assert_eq!(*order.lock().unwrap(), vec!["outer", "inner"]);
+
+        client.reset_procedure::<EchoProcedure>();
+        let result = client.call::<EchoProcedure>(error(1, "hi")).await.unwrap();
+        assert_eq!(result.message, "echo hi");
+    }
+
+    #[tokio::test]
+    async fn retry_test() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let (_loopback, client) = test_client(attempts.clone());
+        assert_eq!(
+            client
+                .call::<FlakyProcedure>(EmptyArriModel {})
+                .await
+                .unwrap_err()
+                .code,
+            503
+        );
+        client.configure_procedure::<FlakyProcedure>(
+            ProcedureOptions::new().retry(FixedDelay::new(Duration::ZERO)),
+        );
+        client
+            .call::<FlakyProcedure>(EmptyArriModel {})
+            .await
+            .unwrap();
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        attempts.store(0, Ordering::SeqCst);
+        client.configure_procedure::<FlakyProcedure>(ProcedureOptions::new().retry(
+            FixedDelay::new(Duration::ZERO).retry_if(|failure| failure.status() == Some(503)),
+        ));
+        let result = client.call::<FlakyProcedure>(EmptyArriModel {}).await;
+        assert_eq!(result.unwrap_err().code, 500);
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn mock_test() {
+        let (_loopback, client) = test_client(Arc::new(AtomicUsize::new(0)));
+        client.configure_procedure(
+            ProcedureOptions::<EchoProcedure>::new()
+                .mock(|params| Ok(error(params.code, "mocked")))
+                .middleware(|params, next| async move {
+                    let mut result = next.run(params).await?;
+                    result.code *= 10;
+                    Ok(result)
+                }),
+        );
+        let result = client.call::<EchoProcedure>(error(4, "hi")).await.unwrap();
+        assert_eq!(result.code, 40);
+        assert_eq!(result.message, "mocked");
+    }
+
+    #[tokio::test]
+    async fn version_mismatch_test() {
+        let server = SseTestServer::start([
+            SseScript::error(426, r#"{"code":426,"message":"Please update the app"}"#)
+                .header("min-client-version", "2")
+                .header("recommended-client-version", "3"),
+            SseScript::error(500, r#"{"code":500,"message":"Internal Server Error"}"#),
+        ])
+        .await;
+        let client = TestClient {
+            _config: InternalArriClientConfig::from(ArriClientConfig {
+                http_client: reqwest::Client::new(),
+                base_url: server.url().to_string(),
+                headers: HashMap::new(),
+            }),
+        };
+        let mismatches = Arc::new(Mutex::new(Vec::new()));
+        let received = mismatches.clone();
+        client.on_version_mismatch(move |mismatch| {
+            received.lock().unwrap().push(mismatch.clone());
+        });
+        let expected = VersionMismatch {
+            client_version: Some("1".to_string()),
+            min_version: Some("2".to_string()),
+            recommended_version: Some("3".to_string()),
+        };
+        let err = client
+            .call::<EchoProcedure>(error(1, "hi"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, 426);
+        assert_eq!(err.message, "Please update the app");
+        assert_eq!(err.kind(), ArriErrorKind::VersionMismatch(expected.clone()));
+        assert_eq!(*mismatches.lock().unwrap(), vec![expected]);
+
+        let err = client
+            .call::<EchoProcedure>(error(1, "hi"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ArriErrorKind::Server);
+        assert_eq!(mismatches.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn deprecation_test() {
+        let (_loopback, client) = test_client(Arc::new(AtomicUsize::new(0)));
+        let notices = Arc::new(Mutex::new(Vec::new()));
+        let received = notices.clone();
+        client.on_deprecation(move |notice| {
+            received.lock().unwrap().push(notice.clone());
+        });
+        client.call::<EchoProcedure>(error(1, "hi")).await.unwrap();
+        client
+            .call::<DeprecatedProcedure>(error(1, "hi"))
+            .await
+            .unwrap();
+        assert_eq!(
+            *notices.lock().unwrap(),
+            vec![DeprecationNotice::from_definition("/echo")]
+        );
+
+        let server =
+            SseTestServer::start([SseScript::response(200, r#"{"code":1,"message":"hi"}"#)
+                .header("deprecation", "@1735689600")
+                .header("sunset", "Thu, 01 Jan 2026 00:00:00 GMT")])
+            .await;
+        let client = TestClient {
+            _config: InternalArriClientConfig::from(ArriClientConfig {
+                http_client: reqwest::Client::new(),
+                base_url: server.url().to_string(),
+                headers: HashMap::new(),
+            }),
+        };
+        let received = notices.clone();
+        client.on_deprecation(move |notice| {
+            received.lock().unwrap().push(notice.clone());
+        });
+        client.call::<EchoProcedure>(error(1, "hi")).await.unwrap();
+        assert_eq!(
+            notices.lock().unwrap().last(),
+            Some(&DeprecationNotice {
+                path: "/echo".to_string(),
+                source: DeprecationSource::Server,
+                deprecation: Some("@1735689600".to_string()),
+                sunset: Some("Thu, 01 Jan 2026 00:00:00 GMT".to_string()),
+            })
+        );
+    }
+
+    struct TestRegistry;
+
+    impl ArriProcedureRegistry for TestRegistry {
+        const PROCEDURES: &'static [ProcedureInfo] = &[
+            ProcedureInfo {
+                name: "echo",
+                path: "/echo",
+                method: Some(reqwest::Method::POST),
+                transport: ProcedureTransport::Http,
+                is_event_stream: false,
+                is_deprecated: false,
+                description: None,
+                params: Some("ArriError"),
+                response: Some("ArriError"),
+            },
+            ProcedureInfo {
+                name: "chat.connect",
+                path: "/chat/connect",
+                method: None,
+                transport: ProcedureTransport::Ws,
+                is_event_stream: false,
+                is_deprecated: true,
+                description: Some("Join the chat"),
+                params: None,
+                response: None,
+            },
+        ];
+    }
+
+    #[test]
+    fn registry_test() {
+        assert_eq!(TestRegistry::procedure("echo").unwrap().path, "/echo");
+        assert_eq!(
+            TestRegistry::procedure_by_path("/chat/connect")
+                .unwrap()
+                .transport,
+            ProcedureTransport::Ws
+        );
+        assert!(TestRegistry::procedure("chat").is_none());
+    }
+}
@@ -1,145 +1,470 @@
 #![allow(dead_code)]
 use std::{
     collections::HashMap,
-    sync::{Arc, RwLock},
-    time::{Duration, Instant},
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
 };
 
 use serde_json::from_str;
 
-use crate::{ArriError, ArriModel, ArriRequestErrorMethods};
+use crate::{
+    deprecation::DeprecationReporter,
+    loopback,
+    version::{self, VersionMismatchListener},
+    ArriError, ArriErrorKind, ArriModel,
+};
+
+mod buffer;
+mod decoder;
+mod long_poll;
+mod multiplex;
+mod reconnect;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+use buffer::{EventBuffer, PushResult};
+pub use buffer::{SseBufferOptions, SseOverflowPolicy};
+pub use decoder::SseDecoder;
+pub use long_poll::SseTransport;
+pub use multiplex::{SseMultiplexer, SseSubscription};
+pub use reconnect::{
+    DefaultReconnectionPolicy, ExponentialBackoff, FixedDelay, NoRetry, ReconnectionPolicy,
+    RetryIf, SseFailure, SseFailureKind,
+};
 
-pub struct ArriParsedSseRequestOptions<'a> {
+pub struct ArriParsedSseRequestOptions<'a, P, T> {
     pub client: &'a reqwest::Client,
     pub client_version: String,
     pub url: String,
     pub method: reqwest::Method,
     pub headers: Arc<RwLock<HashMap<&'static str, String>>>,
+    /// Reports `deprecation` and `sunset` headers whenever the stream is opened
+    pub deprecation: Option<DeprecationReporter>,
+    /// Called when the server rejects the client version, which also closes the stream
+    pub version_mismatch: Option<VersionMismatchListener>,
+    pub options: SseOptions<P, T>,
+}
+
+/// Builds the params for the next reconnection attempt from the previous params
+/// and the last message that was received (if any)
+pub type ReconnectParamsHook<P, T> = Arc<dyn Fn(&P, Option<&T>) -> P + Send + Sync>;
+
+/// Options that can be passed to event stream procedures.
+/// `P` is the params type of the procedure and `T` is the message type.
+pub struct SseOptions<P, T> {
     // Defaults to None
     pub max_retry_count: Option<u64>,
     // Max delay time in ms. defaults to Some(30000).
     pub max_retry_interval: Option<u64>,
+    /// Decides if and when to reconnect after a failure.
+    /// When set `max_retry_count` and `max_retry_interval` are ignored.
+    pub reconnection_policy: Option<Arc<dyn ReconnectionPolicy>>,
+    /// Buffer messages so that the stream keeps reading while the handler is busy.
+    /// Defaults to None, meaning the stream is only read while the handler is idle.
+    pub buffer: Option<SseBufferOptions>,
+    /// Share a single connection with every other subscriber of the same procedure and params.
+    /// Defaults to None, meaning every call opens its own connection.
+    pub multiplexer: Option<SseMultiplexer>,
+    /// Called before every reconnection attempt to replace the params sent with the request.
+    /// Use this to resume cursor based streams from the last message that was received.
+    /// Defaults to None, meaning the original params are sent every time.
+    pub reconnect_params: Option<ReconnectParamsHook<P, T>>,
+    /// Defaults to `SseTransport::EventStream`
+    pub transport: SseTransport,
+    /// End the connection attempt with a `Timeout` failure when no data (messages or heartbeats)
+    /// arrives for this long. When the server sends a `heartbeat-interval` header, twice that
+    /// interval is used instead. Defaults to None, meaning no limit unless the server sends the
+    /// header, or 30 seconds with `SseTransport::Fallback` so that proxies which buffer the
+    /// response count as failed attempts.
+    pub heartbeat_timeout: Option<Duration>,
+}
+
+impl<P, T> Default for SseOptions<P, T> {
+    fn default() -> Self {
+        Self {
+            max_retry_count: None,
+            max_retry_interval: None,
+            reconnection_policy: None,
+            buffer: None,
+            multiplexer: None,
+            reconnect_params: None,
+            transport: SseTransport::EventStream,
+            heartbeat_timeout: None,
+        }
+    }
+}
+
+impl<P, T> Clone for SseOptions<P, T> {
+    fn clone(&self) -> Self {
+        Self {
+            max_retry_count: self.max_retry_count,
+            max_retry_interval: self.max_retry_interval,
+            reconnection_policy: self.reconnection_policy.clone(),
+            buffer: self.buffer,
+            multiplexer: self.multiplexer.clone(),
+            reconnect_params: self.reconnect_params.clone(),
+            transport: self.transport,
+            heartbeat_timeout: self.heartbeat_timeout,
+        }
+    }
 }
 
+#[derive(Clone)]
 pub enum SseEvent<T> {
     Message(T),
+    /// Any event with an `event` name other than `message`, `error` or `done`
+    Custom(SseMessage),
+    /// Connection errors, error responses and `error` events sent by the server
     Error(ArriError),
     Open,
-    Close,
+    /// Emitted before waiting to reconnect after a failure
+    Reconnecting {
+        /// Number of consecutive failed attempts so far
+        attempt: u64,
+        /// How long until the next connection attempt
+        delay: Duration,
+    },
+    /// Emitted once when the event stream has stopped for good
+    Close(SseCloseReason),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SseCloseReason {
+    /// The server sent a `done` event
+    Done,
+    /// `SseController::abort()` was called
+    Aborted,
+    /// The reconnection policy decided not to reconnect after this failure
+    /// (for example because `max_retry_count` was reached)
+    GaveUp(SseFailure),
+    /// The event buffer was full while using `SseOverflowPolicy::Error`
+    BufferOverflow,
+    /// The server sent an `error` event and asked the client not to reconnect
+    ServerError,
+    /// The shared connection of an `SseMultiplexer` closed before the subscriber received
+    /// its close event, e.g. when joining a connection that was just shutting down
+    Ended,
+}
+
+/// Return type of event stream handlers.
+///
+/// Synchronous handlers return `()`. Asynchronous handlers return a pinned future which is awaited
+/// before the next event is delivered. Clone the controller to use it inside of the future.
+///
+/// ```ignore
+/// &mut |event, controller| {
+///     let controller = controller.clone();
+///     Box::pin(async move {
+///         save_to_db(event).await;
+///         controller.abort();
+///     })
+/// }
+/// ```
+pub trait SseHandlerOutput {
+    type Future: Future<Output = ()> + Send;
+    fn into_future(self) -> Self::Future;
+}
+
+impl SseHandlerOutput for () {
+    type Future = std::future::Ready<()>;
+    fn into_future(self) -> Self::Future {
+        std::future::ready(())
+    }
 }
 
+impl<F> SseHandlerOutput for Pin<Box<F>>
+where
+    F: Future<Output = ()> + Send + ?Sized,
+{
+    type Future = Self;
+    fn into_future(self) -> Self::Future {
+        self
+    }
+}
+
+/// Handle passed to event stream handlers. Clones share the same abort state.
 #[derive(Clone)]
 pub struct SseController {
-    is_aborted: bool,
+    is_aborted: Arc<AtomicBool>,
+    last_event_id: Option<String>,
+}
+
+impl Default for SseController {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SseController {
     pub fn new() -> Self {
-        Self { is_aborted: false }
+        Self {
+            is_aborted: Arc::new(AtomicBool::new(false)),
+            last_event_id: None,
+        }
+    }
+    /// Close the event stream. Can be called from any clone of the controller.
+    pub fn abort(&self) {
+        self.is_aborted.store(true, Ordering::SeqCst);
+    }
+    pub fn is_aborted(&self) -> bool {
+        self.is_aborted.load(Ordering::SeqCst)
     }
-    pub fn abort(&mut self) {
-        self.is_aborted = true;
+    /// The SSE id of the event currently being handled, or of the most recent event that had one.
+    /// Store this value to resume a stream later by sending it in the `Last-Event-ID` header.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref()
     }
 }
 
-pub async fn parsed_arri_sse_request<'a, T: ArriModel, OnEvent>(
-    options: ArriParsedSseRequestOptions<'a>,
-    params: Option<impl ArriModel + Clone + std::marker::Send>,
+pub async fn parsed_arri_sse_request<'a, P, T, OnEvent, OnEventOutput>(
+    options: ArriParsedSseRequestOptions<'a, P, T>,
+    params: Option<P>,
     on_event: &mut OnEvent,
 ) where
-    T: ArriModel + std::marker::Send + std::marker::Sync,
-    OnEvent: FnMut(SseEvent<T>, &mut SseController) + std::marker::Send + std::marker::Sync,
+    P: ArriModel + Clone + std::marker::Send + 'static,
+    T: ArriModel + Clone + std::marker::Send + std::marker::Sync + 'static,
+    OnEvent: FnMut(SseEvent<T>, &mut SseController) -> OnEventOutput
+        + std::marker::Send
+        + std::marker::Sync,
+    OnEventOutput: SseHandlerOutput,
 {
+    if let Some(multiplexer) = options.options.multiplexer.clone() {
+        let mut subscription = multiplexer.subscribe(options, params);
+        let mut controller = SseController::new();
+        while let Some(event) = subscription.recv().await {
+            let is_close = matches!(event, SseEvent::Close(_));
+            on_event(event, &mut controller).into_future().await;
+            if is_close {
+                return;
+            }
+            if controller.is_aborted() {
+                drop(subscription);
+                on_event(SseEvent::Close(SseCloseReason::Aborted), &mut controller)
+                    .into_future()
+                    .await;
+                return;
+            }
+        }
+        on_event(SseEvent::Close(SseCloseReason::Ended), &mut controller)
+            .into_future()
+            .await;
+        return;
+    }
+    if loopback::is_loopback_url(&options.url) {
+        loopback::loopback_sse_request(&options.url, params, on_event).await;
+        return;
+    }
+    let reconnection_policy = match options.options.reconnection_policy {
+        Some(policy) => policy,
+        None => Arc::new(DefaultReconnectionPolicy {
+            max_retry_count: options.options.max_retry_count,
+            max_retry_interval: options.options.max_retry_interval.unwrap_or(30000),
+        }),
+    };
     let mut es = EventSource {
-        http_client: &options.client,
+        http_client: options.client,
         url: options.url,
         method: options.method,
         client_version: options.client_version,
         headers: options.headers,
         retry_count: 0,
-        retry_interval: 0,
-        max_retry_interval: options.max_retry_interval.unwrap_or(30000),
-        max_retry_count: options.max_retry_count,
+        reconnection_policy,
+        buffer: options.options.buffer,
+        transport: options.options.transport,
+        heartbeat_timeout: match (options.options.heartbeat_timeout, options.options.transport) {
+            (Some(timeout), _) => Some(timeout),
+            (None, SseTransport::Fallback { .. }) => Some(FALLBACK_HEARTBEAT_TIMEOUT),
+            (None, _) => None,
+        },
+        message_count: 0,
+        server_retry: None,
+        last_event_id: None,
+        deprecation: options.deprecation,
+        version_mismatch: options.version_mismatch,
     };
-    es.listen(params, on_event).await
-}
-
-fn wait(duration: Duration) {
-    let start = Instant::now();
-    while start.elapsed().as_millis() < duration.as_millis() {
-        // keep waiting
-    }
+    es.listen(params, options.options.reconnect_params, on_event)
+        .await
 }
 
-#[derive(Debug)]
 pub struct EventSource<'a> {
     pub http_client: &'a reqwest::Client,
     pub url: String,
     pub method: reqwest::Method,
     pub client_version: String,
     pub headers: Arc<RwLock<HashMap<&'static str, String>>>,
+    /// Number of consecutive failed connection attempts
     pub retry_count: u64,
-    pub retry_interval: u64,
-    pub max_retry_interval: u64,
-    pub max_retry_count: Option<u64>,
+    pub reconnection_policy: Arc<dyn ReconnectionPolicy>,
+    pub buffer: Option<SseBufferOptions>,
+    pub transport: SseTransport,
+    /// Used when the server doesn't send a `heartbeat-interval` header
+    pub heartbeat_timeout: Option<Duration>,
+    /// Number of messages received across all connections
+    pub message_count: u64,
+    /// The most recent `retry` value sent by the server
+    pub server_retry: Option<u64>,
+    /// Sent as the `Last-Event-ID` header when reconnecting
+    pub last_event_id: Option<String>,
+    pub deprecation: Option<DeprecationReporter>,
+    pub version_mismatch: Option<VersionMismatchListener>,
+}
+
+impl std::fmt::Debug for EventSource<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventSource")
+            .field("url", &self.url)
+            .field("method", &self.method)
+            .field("client_version", &self.client_version)
+            .field("retry_count", &self.retry_count)
+            .field("buffer", &self.buffer)
+            .field("transport", &self.transport)
+            .field("heartbeat_timeout", &self.heartbeat_timeout)
+            .field("message_count", &self.message_count)
+            .field("server_retry", &self.server_retry)
+            .field("last_event_id", &self.last_event_id)
+            .finish_non_exhaustive()
+    }
 }
 
 enum SseAction {
-    Retry,
-    Abort,
+    Retry(SseFailureKind),
+    /// Close with `SseCloseReason::GaveUp` without asking the reconnection policy
+    GiveUp(SseFailureKind),
+    Close(SseCloseReason),
 }
 
 impl<'a> EventSource<'a> {
-    async fn listen<T: ArriModel, OnEvent>(
+    async fn listen<P, T, OnEvent, OnEventOutput>(
         &mut self,
-        params: Option<impl ArriModel + Clone>,
+        mut params: Option<P>,
+        reconnect_params: Option<ReconnectParamsHook<P, T>>,
         on_event: &mut OnEvent,
     ) where
-        OnEvent: FnMut(SseEvent<T>, &mut SseController),
+        P: ArriModel + Clone,
+        T: ArriModel + Clone,
+        OnEvent: FnMut(SseEvent<T>, &mut SseController) -> OnEventOutput,
+        OnEventOutput: SseHandlerOutput,
     {
-        loop {
-            match &self.max_retry_count {
-                Some(max_retry_count) => {
-                    if &self.retry_count > max_retry_count {
-                        return;
-                    }
-                }
-                None => {}
-            }
-            if self.retry_count > 5 {
-                if self.retry_interval == 0 {
-                    self.retry_interval = 2;
-                } else {
-                    self.retry_interval = if self.retry_interval * 2 > self.max_retry_interval {
-                        self.max_retry_interval
+        let mut controller = SseController::new();
+        // only kept when it is needed to build the params for the next attempt
+        let mut last_message: Option<T> = None;
+        let mut long_polling = self.transport == SseTransport::LongPolling;
+        // consecutive event stream attempts that failed without receiving anything
+        let mut silent_failures = 0;
+        let reason = loop {
+            let message_count = self.message_count;
+            let message_slot = reconnect_params.as_ref().map(|_| &mut last_message);
+            let result = if long_polling {
+                self.send_long_poll(params.clone(), on_event, &mut controller, message_slot)
+                    .await
+            } else {
+                self.send_request(params.clone(), on_event, &mut controller, message_slot)
+                    .await
+            };
+            match result {
+                SseAction::Retry(kind) => {
+                    if self.message_count == message_count {
+                        silent_failures += 1;
                     } else {
-                        self.retry_interval * 2
+                        silent_failures = 0;
+                    }
+                    if let SseTransport::Fallback { after_failures } = self.transport {
+                        if silent_failures >= after_failures {
+                            long_polling = true;
+                        }
+                    }
+                    self.retry_count += 1;
+                    let failure = SseFailure {
+                        kind,
+                        attempt: self.retry_count,
+                        server_retry: self.server_retry,
+                    };
+                    let delay = match self.reconnection_policy.next_delay(&failure) {
+                        Some(delay) => delay,
+                        None => break SseCloseReason::GaveUp(failure),
                     };
+                    on_event(
+                        SseEvent::Reconnecting {
+                            attempt: self.retry_count,
+                            delay,
+                        },
+                        &mut controller,
+                    )
+                    .into_future()
+                    .await;
+                    if controller.is_aborted() {
+                        break SseCloseReason::Aborted;
+                    }
+                    if !delay.is_zero() {
+                        tokio::time::sleep(delay).await;
+                    }
+                    if controller.is_aborted() {
+                        break SseCloseReason::Aborted;
+                    }
+                    if let (Some(hook), Some(previous)) = (&reconnect_params, &params) {
+                        params = Some(hook(previous, last_message.as_ref()));
+                    }
                 }
-            }
-            if self.retry_interval > 0 {
-                wait(Duration::from_millis(self.retry_interval.clone()));
-            }
-            let result = self.send_request(params.clone(), on_event).await;
-            match result {
-                SseAction::Retry => {
+                SseAction::GiveUp(kind) => {
                     self.retry_count += 1;
+                    break SseCloseReason::GaveUp(SseFailure {
+                        kind,
+                        attempt: self.retry_count,
+                        server_retry: self.server_retry,
+                    });
                 }
-                SseAction::Abort => {
-                    return;
-                }
+                SseAction::Close(reason) => break reason,
             }
-        }
+        };
+        on_event(SseEvent::Close(reason), &mut controller)
+            .into_future()
+            .await;
     }
-    async fn send_request<T: ArriModel, OnEvent>(
-        &mut self,
-        params: Option<impl ArriModel + Clone>,
+
+    /// Pass the error of a response outside of the 2xx range to the handler.
+    /// Version mismatches are reported to the listener and end the stream, since
+    /// reconnecting with the same client version won't succeed.
+    async fn response_failed<T, OnEvent, OnEventOutput>(
+        &self,
+        response: reqwest::Response,
         on_event: &mut OnEvent,
+        controller: &mut SseController,
     ) -> SseAction
     where
-        OnEvent: FnMut(SseEvent<T>, &mut SseController),
+        OnEvent: FnMut(SseEvent<T>, &mut SseController) -> OnEventOutput,
+        OnEventOutput: SseHandlerOutput,
     {
-        let mut controller = SseController::new();
+        let status = response.status().as_u16();
+        let headers = response.headers().clone();
+        let body = response.text().await.unwrap_or_default();
+        let error = version::error_from_response(status, body, &self.client_version, |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        });
+        let is_mismatch = matches!(error.kind(), ArriErrorKind::VersionMismatch(_));
+        if let Some(listener) = &self.version_mismatch {
+            listener.notify_error(&error);
+        }
+        on_event(SseEvent::Error(error), controller)
+            .into_future()
+            .await;
+        if controller.is_aborted() {
+            return SseAction::Close(SseCloseReason::Aborted);
+        }
+        if is_mismatch {
+            return SseAction::GiveUp(SseFailureKind::Status(status));
+        }
+        SseAction::Retry(SseFailureKind::Status(status))
+    }
+
+    /// Build the request for the next connection attempt
+    fn build_request(&self, params: Option<impl ArriModel>) -> reqwest::RequestBuilder {
         let query_string: Option<String>;
         let json_body: Option<String>;
         let mut headers = reqwest::header::HeaderMap::new();
@@ -162,7 +487,17 @@ impl<'a> EventSource<'a> {
                 reqwest::header::HeaderValue::from_str(&self.client_version).unwrap(),
             );
         }
-        match params.clone() {
+        if let Some(last_event_id) = &self.last_event_id {
+            match reqwest::header::HeaderValue::from_str(last_event_id) {
+                Ok(header_val) => {
+                    headers.insert("Last-Event-ID", header_val);
+                }
+                Err(error) => {
+                    println!("Invalid header value: {:?}", error);
+                }
+            }
+        }
+        match params {
             Some(val) => match self.method {
                 reqwest::Method::GET => {
                     query_string = Some(val.to_query_params_string());
@@ -184,261 +519,356 @@ impl<'a> EventSource<'a> {
             None => self.url.clone(),
         };
 
-        let response = match json_body {
-            Some(body) => {
-                self.http_client
-                    .request(self.method.clone(), url.clone())
-                    .headers(headers)
-                    .body(body)
-                    .send()
-                    .await
-            }
-            None => {
-                self.http_client
-                    .request(self.method.clone(), url.clone())
-                    .headers(headers)
-                    .send()
-                    .await
-            }
-        };
-        if controller.is_aborted {
-            return SseAction::Abort;
+        let request = self
+            .http_client
+            .request(self.method.clone(), url)
+            .headers(headers);
+        match json_body {
+            Some(body) => request.body(body),
+            None => request,
+        }
+    }
+
+    async fn send_request<T, OnEvent, OnEventOutput>(
+        &mut self,
+        params: Option<impl ArriModel + Clone>,
+        on_event: &mut OnEvent,
+        controller: &mut SseController,
+        mut last_message: Option<&mut Option<T>>,
+    ) -> SseAction
+    where
+        T: ArriModel + Clone,
+        OnEvent: FnMut(SseEvent<T>, &mut SseController) -> OnEventOutput,
+        OnEventOutput: SseHandlerOutput,
+    {
+        controller.last_event_id = self.last_event_id.clone();
+        let response = self.build_request(params).send().await;
+        if controller.is_aborted() {
+            return SseAction::Close(SseCloseReason::Aborted);
         }
 
-        if !response.is_ok() {
-            on_event(SseEvent::Error(ArriError::new()), &mut controller);
-            if controller.is_aborted {
-                return SseAction::Abort;
+        let mut ok_response = match response {
+            Ok(response) => response,
+            Err(err) => {
+                on_event(SseEvent::Error(connection_error(&err)), controller)
+                    .into_future()
+                    .await;
+                if controller.is_aborted() {
+                    return SseAction::Close(SseCloseReason::Aborted);
+                }
+                if err.is_timeout() {
+                    return SseAction::Retry(SseFailureKind::Timeout);
+                }
+                return SseAction::Retry(SseFailureKind::Connection);
             }
-            return SseAction::Retry;
+        };
+        if let Some(deprecation) = &self.deprecation {
+            deprecation.report_headers(ok_response.headers());
         }
-        let mut ok_response = response.unwrap();
 
-        // TODO: use this header to setup a heartbeat watcher
-        // that will reset whenever a message is received
-        let _heartbeat_ms = match ok_response.headers().get("heartbeat-interval") {
-            Some(val) => from_str::<u64>(val.to_str().unwrap_or("0")).unwrap_or(0),
-            None => 0,
+        let heartbeat_timeout = match ok_response.headers().get("heartbeat-interval") {
+            Some(val) => match from_str::<u64>(val.to_str().unwrap_or("0")).unwrap_or(0) {
+                0 => self.heartbeat_timeout,
+                heartbeat_ms => Some(Duration::from_millis(heartbeat_ms * 2)),
+            },
+            None => self.heartbeat_timeout,
         };
 
-        on_event(SseEvent::Open, &mut controller);
-        if controller.is_aborted {
-            return SseAction::Abort;
+        on_event(SseEvent::Open, controller).into_future().await;
+        if controller.is_aborted() {
+            return SseAction::Close(SseCloseReason::Aborted);
         }
         let status = ok_response.status().as_u16();
-        if status < 200 || status >= 300 {
-            let body = ok_response.text().await.unwrap_or_default();
-            on_event(
-                SseEvent::Error(ArriError::from_response_data(status, body)),
-                &mut controller,
-            );
-            if controller.is_aborted {
-                return SseAction::Abort;
-            }
-            return SseAction::Retry;
+        if !(200..300).contains(&status) {
+            return self
+                .response_failed(ok_response, on_event, controller)
+                .await;
         }
         self.retry_count = 0;
-        let mut pending_data: String = "".to_string();
-        while let Some(chunk) = ok_response.chunk().await.unwrap_or_default() {
-            if controller.is_aborted {
-                return SseAction::Abort;
-            }
-            let chunk_vec = chunk.to_vec();
-            let data = std::str::from_utf8(chunk_vec.as_slice());
-            match data {
-                Ok(text) => {
-                    if !text.ends_with("\n\n") {
-                        pending_data.push_str(text);
-                        continue;
-                    }
-                    let msg_text = format!("{}{}", pending_data, text);
-                    let (messages, left_over) = sse_message_list_from_string(msg_text, false);
-                    pending_data = left_over;
+        let mut decoder = SseDecoder::with_last_event_id(self.last_event_id.clone());
+        if let Some(buffer_options) = self.buffer {
+            let buffer = EventBuffer::new(buffer_options);
+            let mut overflowed = false;
+            let mut timed_out = false;
+            let mut read_error = None;
+            let mut decoded = 0;
+            let reader = async {
+                loop {
+                    let chunk = tokio::select! {
+                        biased;
+                        _ = buffer.closed() => break,
+                        chunk = next_chunk(&mut ok_response, heartbeat_timeout) => chunk,
+                    };
+                    let chunk = match chunk {
+                        Some(Ok(Some(chunk))) => chunk,
+                        Some(Ok(None)) => break,
+                        Some(Err(err)) => {
+                            read_error = Some(err);
+                            break;
+                        }
+                        None => {
+                            timed_out = true;
+                            break;
+                        }
+                    };
+                    let messages = decoder.decode(chunk.as_ref());
+                    decoded += messages.len() as u64;
                     for message in messages {
-                        let event = message.event.unwrap_or("".to_string());
-                        match event.as_str() {
-                            "done" => {
-                                on_event(SseEvent::Close, &mut controller);
-                                return SseAction::Abort;
+                        match buffer.push(message).await {
+                            PushResult::Pushed => {}
+                            PushResult::Closed => break,
+                            PushResult::Overflowed => {
+                                overflowed = true;
+                                buffer.close();
+                                break;
                             }
-                            "message" => {
-                                on_event(
-                                    SseEvent::Message(T::from_json_string(message.data)),
-                                    &mut controller,
-                                );
-                                if controller.is_aborted {
-                                    return SseAction::Abort;
-                                }
-                            }
-                            "" => on_event(
-                                SseEvent::Message(T::from_json_string(message.data)),
-                                &mut controller,
-                            ),
-                            _ => {}
                         }
                     }
                 }
-                _ => {}
-            }
-        }
-        if controller.is_aborted {
-            return SseAction::Abort;
-        }
-        return SseAction::Retry;
-    }
-}
-
-fn sse_message_list_from_string(input: String, debug: bool) -> (Vec<SseMessage>, String) {
-    let mut messages: Vec<SseMessage> = Vec::new();
-    let mut id: Option<String> = None;
-    let mut event: Option<String> = None;
-    let mut data: Option<String> = None;
-    let mut retry: Option<i32> = None;
-    let mut line = "".to_string();
-    let mut pending_index = 0;
-    let mut previous_char: Option<char> = None;
-    let mut ignore_next_newline = false;
-    let chars = input.chars();
-    let mut peekable = chars.peekable().enumerate();
-    while let Some((index, char)) = peekable.next() {
-        match char {
-            '\r' => {
-                let is_message_end = previous_char == Some('\n') || previous_char == Some('\r');
-                ignore_next_newline = true;
-                let parsed_result = parse_sse_line(line.as_str(), debug.clone());
-                match parsed_result {
-                    ParseSseLineResult::Id(id_val) => {
-                        id = Some(id_val);
-                    }
-                    ParseSseLineResult::Event(event_val) => {
-                        event = Some(event_val);
+                buffer.finish();
+            };
+            let handler = async {
+                while let Some(message) = buffer.pop().await {
+                    if let Some(action) =
+                        handle_message(message, on_event, controller, last_message.as_deref_mut())
+                            .await
+                    {
+                        buffer.close();
+                        return Some(action);
                     }
-                    ParseSseLineResult::Data(data_val) => {
-                        data = Some(data_val);
-                    }
-                    ParseSseLineResult::Retry(retry_val) => {
-                        retry = Some(retry_val);
-                    }
-                    ParseSseLineResult::Nothing => {}
-                }
-                line = "".to_string();
-                if is_message_end {
-                    if data.is_some() {
-                        messages.push(SseMessage {
-                            id: id.clone(),
-                            data: data.unwrap().clone(),
-                            event: event.clone(),
-                            retry: retry.clone(),
-                        });
-                    };
-                    id = None;
-                    data = None;
-                    event = None;
-                    retry = None;
-                    let next_char = peekable.next();
-                    pending_index = match next_char {
-                        Some((next_char_index, next_char_value)) => match next_char_value {
-                            '\n' => next_char_index + 1,
-                            '\r' => next_char_index + 1,
-                            _ => {
-                                line.push(next_char_value);
-                                next_char_index
-                            }
-                        },
-                        _ => index,
-                    };
                 }
+                None
+            };
+            let (_, action) = tokio::join!(reader, handler);
+            self.message_count += decoded;
+            self.last_event_id = decoder.last_event_id().map(|id| id.to_string());
+            if decoder.retry().is_some() {
+                self.server_retry = decoder.retry();
             }
-            '\n' => 'newline: {
-                if ignore_next_newline {
-                    ignore_next_newline = false;
-                    break 'newline;
-                }
-                let is_end = previous_char == Some('\n');
-                let parsed_result = parse_sse_line(line.as_str(), debug.clone());
-                match parsed_result {
-                    ParseSseLineResult::Id(id_val) => {
-                        id = Some(id_val);
-                    }
-                    ParseSseLineResult::Event(event_val) => {
-                        event = Some(event_val);
-                    }
-                    ParseSseLineResult::Data(data_val) => {
-                        data = Some(data_val);
-                    }
-                    ParseSseLineResult::Retry(retry_val) => {
-                        retry = Some(retry_val);
+            if overflowed {
+                on_event(
+                    SseEvent::Error(ArriError {
+                        code: 0,
+                        message: format!(
+                            "Event buffer overflowed. More than {} messages were waiting to be handled.",
+                            buffer_options.capacity
+                        ),
+                        stack: None,
+                        data: None,
+                    }),
+                    controller,
+                )
+                .into_future()
+                .await;
+                return SseAction::Close(SseCloseReason::BufferOverflow);
+            }
+            if let Some(action) = action {
+                return action;
+            }
+            if timed_out {
+                return self
+                    .heartbeat_timed_out(heartbeat_timeout, on_event, controller)
+                    .await;
+            }
+            if let Some(err) = read_error {
+                return body_failed(&err, on_event, controller).await;
+            }
+        } else {
+            loop {
+                let chunk = match next_chunk(&mut ok_response, heartbeat_timeout).await {
+                    Some(Ok(Some(chunk))) => chunk,
+                    Some(Ok(None)) => break,
+                    Some(Err(err)) => return body_failed(&err, on_event, controller).await,
+                    None => {
+                        return self
+                            .heartbeat_timed_out(heartbeat_timeout, on_event, controller)
+                            .await;
                     }
-                    ParseSseLineResult::Nothing => {}
                 };
-                line = "".to_string();
-                if is_end {
-                    if data.is_some() {
-                        messages.push(SseMessage {
-                            id: id.clone(),
-                            data: data.unwrap().clone(),
-                            event: event.clone(),
-                            retry: retry.clone(),
-                        });
-                    };
-                    id = None;
-                    data = None;
-                    event = None;
-                    retry = None;
-                    pending_index = index + 1;
+                if controller.is_aborted() {
+                    return SseAction::Close(SseCloseReason::Aborted);
+                }
+                let messages = decoder.decode(chunk.as_ref());
+                self.message_count += messages.len() as u64;
+                self.last_event_id = decoder.last_event_id().map(|id| id.to_string());
+                if decoder.retry().is_some() {
+                    self.server_retry = decoder.retry();
+                }
+                for message in messages {
+                    if let Some(action) =
+                        handle_message(message, on_event, controller, last_message.as_deref_mut())
+                            .await
+                    {
+                        return action;
+                    }
                 }
             }
-            _ => {
-                ignore_next_newline = false;
-                line.push(char);
-            }
         }
-        previous_char = Some(char);
+        if controller.is_aborted() {
+            return SseAction::Close(SseCloseReason::Aborted);
+        }
+        SseAction::Retry(SseFailureKind::Disconnected)
     }
 
-    return (messages, input[pending_index..].to_string());
+    /// Report that nothing was received within the heartbeat timeout
+    async fn heartbeat_timed_out<T, OnEvent, OnEventOutput>(
+        &self,
+        timeout: Option<Duration>,
+        on_event: &mut OnEvent,
+        controller: &mut SseController,
+    ) -> SseAction
+    where
+        OnEvent: FnMut(SseEvent<T>, &mut SseController) -> OnEventOutput,
+        OnEventOutput: SseHandlerOutput,
+    {
+        let error = ArriError {
+            code: 0,
+            message: format!(
+                "No data received for {}ms",
+                timeout.unwrap_or_default().as_millis()
+            ),
+            stack: None,
+            data: Some(serde_json::json!({ "kind": "timeout" })),
+        };
+        on_event(SseEvent::Error(error), controller)
+            .into_future()
+            .await;
+        if controller.is_aborted() {
+            return SseAction::Close(SseCloseReason::Aborted);
+        }
+        SseAction::Retry(SseFailureKind::Timeout)
+    }
 }
 
-fn parse_sse_line(input: &str, debug: bool) -> ParseSseLineResult {
-    if debug {
-        println!("PARSING_LINE: {:?}", input);
+/// Report an error that interrupted the response body, such as a reset connection
+async fn body_failed<T, OnEvent, OnEventOutput>(
+    err: &reqwest::Error,
+    on_event: &mut OnEvent,
+    controller: &mut SseController,
+) -> SseAction
+where
+    OnEvent: FnMut(SseEvent<T>, &mut SseController) -> OnEventOutput,
+    OnEventOutput: SseHandlerOutput,
+{
+    on_event(SseEvent::Error(connection_error(err)), controller)
+        .into_future()
+        .await;
+    if controller.is_aborted() {
+        return SseAction::Close(SseCloseReason::Aborted);
     }
-    if input.starts_with("data:") {
-        return ParseSseLineResult::Data(input[5..].trim().to_string());
-    };
-    if input.starts_with("id:") {
-        return ParseSseLineResult::Id(input[3..].trim().to_string());
-    };
-    if input.starts_with("event:") {
-        return ParseSseLineResult::Event(input[6..].trim().to_string());
+    if err.is_timeout() {
+        return SseAction::Retry(SseFailureKind::Timeout);
+    }
+    SseAction::Retry(SseFailureKind::Disconnected)
+}
+
+/// Used as `SseOptions::heartbeat_timeout` with `SseTransport::Fallback` when none is set
+const FALLBACK_HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Wait for the next chunk of the response body. Returns `None` when `timeout` passes first.
+async fn next_chunk(
+    response: &mut reqwest::Response,
+    timeout: Option<Duration>,
+) -> Option<reqwest::Result<Option<impl AsRef<[u8]>>>> {
+    match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, response.chunk()).await.ok(),
+        None => Some(response.chunk().await),
+    }
+}
+
+/// Error passed to the handler when the request could not be sent or no response was received
+fn connection_error(err: &reqwest::Error) -> ArriError {
+    let mut message = err.to_string();
+    let mut source = std::error::Error::source(err);
+    while let Some(cause) = source {
+        message.push_str(format!(": {}", cause).as_str());
+        source = cause.source();
+    }
+    let kind = if err.is_timeout() {
+        "timeout"
+    } else if err.is_connect() {
+        "connect"
+    } else if err.is_body() || err.is_decode() {
+        "body"
+    } else {
+        "request"
     };
-    if input.starts_with("retry:") {
-        let val = input[6..].trim().parse::<i32>();
-        match val {
-            Ok(val) => {
-                return ParseSseLineResult::Retry(val);
+    ArriError {
+        code: err.status().map(|status| status.as_u16()).unwrap_or(0),
+        message,
+        stack: None,
+        data: Some(serde_json::json!({ "kind": kind })),
+    }
+}
+
+/// Pass a decoded message to the handler. Returns an action if the stream should stop.
+/// Parsed messages are also stored in `last_message` when it is provided.
+async fn handle_message<T, OnEvent, OnEventOutput>(
+    message: SseMessage,
+    on_event: &mut OnEvent,
+    controller: &mut SseController,
+    last_message: Option<&mut Option<T>>,
+) -> Option<SseAction>
+where
+    T: ArriModel + Clone,
+    OnEvent: FnMut(SseEvent<T>, &mut SseController) -> OnEventOutput,
+    OnEventOutput: SseHandlerOutput,
+{
+    controller.last_event_id = message.id.clone();
+    let event = match message.event.as_deref().unwrap_or("message") {
+        "done" => {
+            return Some(SseAction::Close(SseCloseReason::Done));
+        }
+        "error" => {
+            let (error, reconnect) = parse_error_event(&message.data);
+            on_event(SseEvent::Error(error), controller)
+                .into_future()
+                .await;
+            if controller.is_aborted() {
+                return Some(SseAction::Close(SseCloseReason::Aborted));
             }
-            _ => {}
-        };
+            if !reconnect {
+                return Some(SseAction::Close(SseCloseReason::ServerError));
+            }
+            return None;
+        }
+        "message" => {
+            let parsed = T::from_json_string(message.data);
+            if let Some(last_message) = last_message {
+                *last_message = Some(parsed.clone());
+            }
+            SseEvent::Message(parsed)
+        }
+        _ => SseEvent::Custom(message),
     };
-    ParseSseLineResult::Nothing
+    on_event(event, controller).into_future().await;
+    if controller.is_aborted() {
+        return Some(SseAction::Close(SseCloseReason::Aborted));
+    }
+    None
 }
 
-#[derive(Debug, Clone, PartialEq)]
-enum ParseSseLineResult {
-    Id(String),
-    Event(String),
-    Data(String),
-    Retry(i32),
-    Nothing,
+/// The data of an `error` event is an `ArriError` with an optional `reconnect` field.
+/// Clients reconnect unless `reconnect` is `false`.
+fn parse_error_event(data: &str) -> (ArriError, bool) {
+    match from_str::<serde_json::Value>(data) {
+        Ok(value) => {
+            let reconnect = value
+                .get("reconnect")
+                .and_then(|reconnect| reconnect.as_bool())
+                .unwrap_or(true);
+            (ArriError::from_json(value), reconnect)
+        }
+        Err(_) => (ArriError::from_json_string(data.to_string()), true),
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct SseMessage {
-    id: Option<String>,
-    event: Option<String>,
-    data: String,
-    retry: Option<i32>,
+    pub id: Option<String>,
+    pub event: Option<String>,
+    pub data: String,
+    pub retry: Option<u64>,
 }
 
 pub trait SeeMessageMethods {
@@ -460,7 +890,7 @@ struct ParsedSseMessage<T: ArriModel> {
     pub id: Option<String>,
     pub event: Option<String>,
     pub data: T,
-    pub retry: Option<i32>,
+    pub retry: Option<u64>,
 }
 
 impl<T: ArriModel> SeeMessageMethods for ParsedSseMessage<T> {
@@ -486,107 +916,502 @@ impl<T: ArriModel> ParsedSseMessage<T> {
 }
 
 #[cfg(test)]
-mod parsing_and_serialization_tests {
-    use crate::sse::{sse_message_list_from_string, SseMessage};
-    fn get_test_data() -> (Vec<String>, Vec<SseMessage>, String) {
-        (
+mod event_source_tests {
+    use std::sync::Mutex;
+
+    use super::testing::{SseScript, SseTestServer};
+    use super::*;
+    use crate::{
+        deprecation::{DeprecationListener, DeprecationNotice, DeprecationSource},
+        EmptyArriModel,
+    };
+
+    /// Keeps the raw message data so that tests can check exactly what was received
+    #[derive(Debug, Clone, PartialEq)]
+    struct TestMessage(String);
+
+    impl ArriModel for TestMessage {
+        fn new() -> Self {
+            Self(String::new())
+        }
+        fn from_json(input: serde_json::Value) -> Self {
+            Self(input.to_string())
+        }
+        fn from_json_string(input: String) -> Self {
+            Self(input)
+        }
+        fn to_json_string(&self) -> String {
+            self.0.clone()
+        }
+        fn to_query_params_string(&self) -> String {
+            format!("cursor={}", self.0)
+        }
+    }
+
+    /// Listen to the test server and describe every event that was received.
+    /// The stream is aborted once `stop` returns true.
+    async fn listen<P>(
+        server: &SseTestServer,
+        params: Option<P>,
+        options: SseOptions<P, TestMessage>,
+        stop: impl Fn(&[String]) -> bool + Send + Sync,
+    ) -> Vec<String>
+    where
+        P: ArriModel + Clone + Send + 'static,
+    {
+        let client = reqwest::Client::new();
+        let mut headers = HashMap::new();
+        headers.insert("x-test", "true".to_string());
+        let mut log = Vec::new();
+        parsed_arri_sse_request(
+            ArriParsedSseRequestOptions {
+                client: &client,
+                client_version: "1".to_string(),
+                url: server.url().to_string(),
+                method: reqwest::Method::GET,
+                headers: Arc::new(RwLock::new(headers)),
+                deprecation: None,
+                version_mismatch: None,
+                options,
+            },
+            params,
+            &mut |event: SseEvent<TestMessage>, controller| {
+                log.push(match event {
+                    SseEvent::Message(message) => match controller.last_event_id() {
+                        Some(id) => format!("message {} #{}", message.0, id),
+                        None => format!("message {}", message.0),
+                    },
+                    SseEvent::Custom(message) => {
+                        format!("{} {}", message.event.unwrap_or_default(), message.data)
+                    }
+                    SseEvent::Error(err) => format!("error {} {}", err.code, err.message),
+                    SseEvent::Open => "open".to_string(),
+                    SseEvent::Reconnecting { attempt, .. } => format!("reconnecting {}", attempt),
+                    SseEvent::Close(reason) => format!("close {:?}", reason),
+                });
+                if stop(&log) {
+                    controller.abort();
+                }
+            },
+        )
+        .await;
+        log
+    }
+
+    fn retry_immediately() -> SseOptions<EmptyArriModel, TestMessage> {
+        SseOptions {
+            reconnection_policy: Some(Arc::new(FixedDelay::new(Duration::ZERO))),
+            ..Default::default()
+        }
+    }
+
+    fn never(_: &[String]) -> bool {
+        false
+    }
+
+    #[tokio::test]
+    async fn chunk_boundaries_test() {
+        let delay = Duration::from_millis(5);
+        let server = SseTestServer::start([SseScript::new()
+            .chunk("da")
+            .delay(delay)
+            .chunk("ta: hel")
+            .delay(delay)
+            .chunk("lo\r")
+            .delay(delay)
+            .chunk("\n\r\ndata: wor")
+            .delay(delay)
+            .chunk("ld\ndata: !\n")
+            .delay(delay)
+            .chunk("\n")
+            .done()])
+        .await;
+        let log = listen(&server, None::<EmptyArriModel>, Default::default(), never).await;
+        assert_eq!(
+            log,
+            vec!["open", "message hello", "message world\n!", "close Done"]
+        );
+    }
+
+    #[tokio::test]
+    async fn request_headers_and_last_event_id_test() {
+        let server = SseTestServer::start([
+            SseScript::new()
+                .id("1")
+                .message("a")
+                .event("ping", "b")
+                .disconnect(),
+            SseScript::new().id("2").message("c").done(),
+        ])
+        .await;
+        let log = listen(&server, None::<EmptyArriModel>, retry_immediately(), never).await;
+        assert_eq!(
+            log,
             vec![
-                "id: 1".to_string(),
-                "data: hello world".to_string(),
-                "".to_string(),
-                "data: hello world".to_string(),
-                "retry: 100".to_string(),
-                "".to_string(),
-                "id: 4".to_string(),
-            ],
+                "open",
+                "message a #1",
+                "ping b",
+                "reconnecting 1",
+                "open",
+                "message c #2",
+                "close Done"
+            ]
+        );
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].headers.get("last-event-id"), None);
+        assert_eq!(
+            requests[1].headers.get("last-event-id").map(String::as_str),
+            Some("1")
+        );
+        for request in requests {
+            assert_eq!(request.method, "GET");
+            assert_eq!(
+                request.headers.get("x-test").map(String::as_str),
+                Some("true")
+            );
+            assert_eq!(
+                request.headers.get("client-version").map(String::as_str),
+                Some("1")
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn server_retry_test() {
+        let server = SseTestServer::start([SseScript::new()
+            .retry(Duration::from_millis(1500))
+            .message("a")])
+        .await;
+        let failures = Arc::new(Mutex::new(Vec::new()));
+        let recorded = failures.clone();
+        let log = listen(
+            &server,
+            None::<EmptyArriModel>,
+            SseOptions {
+                reconnection_policy: Some(Arc::new(move |failure: &SseFailure| {
+                    recorded.lock().unwrap().push(failure.clone());
+                    None
+                })),
+                ..Default::default()
+            },
+            never,
+        )
+        .await;
+        let failure = SseFailure {
+            kind: SseFailureKind::Disconnected,
+            attempt: 1,
+            server_retry: Some(1500),
+        };
+        assert_eq!(*failures.lock().unwrap(), vec![failure.clone()]);
+        assert_eq!(
+            log,
             vec![
-                SseMessage {
-                    id: Some("1".to_string()),
-                    data: "hello world".to_string(),
-                    event: None,
-                    retry: None,
-                },
-                SseMessage {
-                    id: None,
-                    data: "hello world".to_string(),
-                    event: None,
-                    retry: Some(100),
+                "open".to_string(),
+                "message a".to_string(),
+                format!("close {:?}", SseCloseReason::GaveUp(failure))
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn error_status_test() {
+        let server = SseTestServer::start([
+            SseScript::error(503, r#"{"code":503,"message":"Try again later"}"#),
+            SseScript::error(500, "not json"),
+            SseScript::new().message("a").done(),
+        ])
+        .await;
+        let log = listen(&server, None::<EmptyArriModel>, retry_immediately(), never).await;
+        assert_eq!(
+            log,
+            vec![
+                "open",
+                "error 503 Try again later",
+                "reconnecting 1",
+                "open",
+                "error 500 Internal Server Error",
+                "reconnecting 2",
+                "open",
+                "message a",
+                "close Done"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn custom_events_and_heartbeats_test() {
+        let server = SseTestServer::start([SseScript::new()
+            .heartbeat_interval(Duration::from_millis(10))
+            .heartbeat()
+            .comment("keep alive")
+            .event("update", "x")
+            .heartbeat()
+            .done()])
+        .await;
+        let log = listen(&server, None::<EmptyArriModel>, Default::default(), never).await;
+        assert_eq!(
+            log,
+            vec!["open", "heartbeat ", "update x", "heartbeat ", "close Done"]
+        );
+    }
+
+    #[tokio::test]
+    async fn interrupted_body_test() {
+        for buffer in [
+            None,
+            Some(SseBufferOptions::new(8, SseOverflowPolicy::Error)),
+        ] {
+            // the connection closes before the announced content length has been sent
+            let server = SseTestServer::start([
+                SseScript::new()
+                    .header("content-length", "1000")
+                    .message("a")
+                    .disconnect(),
+                SseScript::new().message("b").done(),
+            ])
+            .await;
+            let log = listen(
+                &server,
+                None::<EmptyArriModel>,
+                SseOptions {
+                    buffer,
+                    ..retry_immediately()
                 },
-            ],
-            "id: 4".to_string(),
+                never,
+            )
+            .await;
+            assert_eq!(&log[..2], &["open", "message a"]);
+            assert!(log[2].starts_with("error 0 "), "{:?}", log);
+            assert_eq!(
+                &log[3..],
+                &["reconnecting 1", "open", "message b", "close Done"]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn abort_stalled_stream_test() {
+        let server =
+            SseTestServer::start([SseScript::new().message("a").message("b").stall()]).await;
+        let log = listen(
+            &server,
+            None::<EmptyArriModel>,
+            retry_immediately(),
+            |log| log.len() == 3,
         )
+        .await;
+        assert_eq!(log, vec!["open", "message a", "message b", "close Aborted"]);
+        assert_eq!(server.requests().len(), 1);
     }
 
-    #[test]
-    fn sse_message_list_from_string_lf_test() {
-        let (lines, expected_msgs, expected_leftover) = get_test_data();
-        let input = lines.join("\n");
-        let (messages, leftover) = sse_message_list_from_string(input, false);
-        assert_eq!(messages, expected_msgs);
-        assert_eq!(leftover, expected_leftover);
-    }
-    #[test]
-    fn sse_message_list_from_string_crlf_test() {
-        let (lines, expected_msgs, expected_leftover) = get_test_data();
-        let input = lines.join("\r\n");
-        let (messages, leftover) = sse_message_list_from_string(input, false);
-        assert_eq!(messages, expected_msgs);
-        assert_eq!(leftover, expected_leftover);
-    }
-    #[test]
-    fn sse_message_list_from_string_cr_test() {
-        let (lines, expected_msgs, expected_leftover) = get_test_data();
-        let input = lines.join("\r");
-        let (messages, leftover) = sse_message_list_from_string(input, false);
-        assert_eq!(messages, expected_msgs);
-        assert_eq!(leftover, expected_leftover);
-    }
-
-    fn get_invalid_msg_data() -> (Vec<String>, Vec<SseMessage>, String) {
-        (
+    #[tokio::test]
+    async fn partial_event_test() {
+        let server = SseTestServer::start([
+            SseScript::new()
+                .message("a")
+                .chunk("data: partial")
+                .disconnect(),
+            SseScript::new().message("b").done(),
+        ])
+        .await;
+        let log = listen(&server, None::<EmptyArriModel>, retry_immediately(), never).await;
+        assert_eq!(
+            log,
             vec![
-                "".to_string(),
-                ":".to_string(),
-                "hello world".to_string(),
-                "hi".to_string(),
-                "hi".to_string(),
-                "".to_string(),
-                "data: hello world".to_string(),
-                "".to_string(),
-                ":".to_string(),
-                ":".to_string(),
-                "".to_string(),
-                "data: hello world".to_string(),
-                "".to_string(),
-                "".to_string(),
-                "event: data".to_string(),
-            ],
-            vec![
-                SseMessage {
-                    id: None,
-                    event: None,
-                    data: "hello world".to_string(),
-                    retry: None,
+                "open",
+                "message a",
+                "reconnecting 1",
+                "open",
+                "message b",
+                "close Done"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn deprecation_headers_test() {
+        for transport in [SseTransport::EventStream, SseTransport::LongPolling] {
+            let server = SseTestServer::start([SseScript::new()
+                .header("deprecation", "@1735689600")
+                .message("a")
+                .done()])
+            .await;
+            let client = reqwest::Client::new();
+            let listener = DeprecationListener::default();
+            let notices = Arc::new(Mutex::new(Vec::new()));
+            let received = notices.clone();
+            listener.set(move |notice| received.lock().unwrap().push(notice.clone()));
+            parsed_arri_sse_request(
+                ArriParsedSseRequestOptions {
+                    client: &client,
+                    client_version: String::new(),
+                    url: server.url().to_string(),
+                    method: reqwest::Method::GET,
+                    headers: Arc::new(RwLock::new(HashMap::new())),
+                    deprecation: Some(listener.reporter("/books/watch-book")),
+                    version_mismatch: None,
+                    options: SseOptions {
+                        transport,
+                        ..Default::default()
+                    },
                 },
-                SseMessage {
-                    id: None,
-                    event: None,
-                    data: "hello world".to_string(),
-                    retry: None,
+                None::<EmptyArriModel>,
+                &mut |_: SseEvent<TestMessage>, _| {},
+            )
+            .await;
+            assert_eq!(
+                *notices.lock().unwrap(),
+                vec![DeprecationNotice {
+                    path: "/books/watch-book".to_string(),
+                    source: DeprecationSource::Server,
+                    deprecation: Some("@1735689600".to_string()),
+                    sunset: None,
+                }],
+                "{:?}",
+                transport
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn buffer_overflow_test() {
+        let mut script = SseScript::new();
+        for i in 0..20 {
+            script = script.message(&i.to_string());
+        }
+        let server = SseTestServer::start([script.stall()]).await;
+        let client = reqwest::Client::new();
+        let close_reasons = Arc::new(Mutex::new(Vec::new()));
+        let mut errors = 0;
+        parsed_arri_sse_request(
+            ArriParsedSseRequestOptions {
+                client: &client,
+                client_version: String::new(),
+                url: server.url().to_string(),
+                method: reqwest::Method::GET,
+                headers: Arc::new(RwLock::new(HashMap::new())),
+                deprecation: None,
+                version_mismatch: None,
+                options: SseOptions {
+                    buffer: Some(SseBufferOptions::new(2, SseOverflowPolicy::Error)),
+                    ..Default::default()
                 },
-            ],
-            "event: data".to_string(),
+            },
+            None::<EmptyArriModel>,
+            &mut |event: SseEvent<TestMessage>, _| {
+                if let SseEvent::Error(_) = event {
+                    errors += 1;
+                }
+                let close_reasons = close_reasons.clone();
+                Box::pin(async move {
+                    if let SseEvent::Close(reason) = event {
+                        close_reasons.lock().unwrap().push(reason);
+                    }
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                })
+            },
         )
+        .await;
+        assert_eq!(errors, 1);
+        assert_eq!(
+            *close_reasons.lock().unwrap(),
+            vec![SseCloseReason::BufferOverflow]
+        );
     }
 
-    #[test]
-    fn skip_invalid_lines_test() {
-        let (lines, expected_messages, expected_leftover) = get_invalid_msg_data();
-        let delimiters = vec!["\n", "\r\n", "\r"];
-        for delimiter in delimiters {
-            let (messages, leftover) = sse_message_list_from_string(lines.join(delimiter), false);
-            assert_eq!(messages, expected_messages);
-            assert_eq!(leftover, expected_leftover);
-        }
+    #[tokio::test]
+    async fn error_event_test() {
+        let unauthorized = ArriError {
+            code: 401,
+            message: "Unauthorized".to_string(),
+            ..ArriError::new()
+        };
+        let server = SseTestServer::start([SseScript::new()
+            .message("a")
+            .error_event(&unauthorized, false)
+            .message("b")])
+        .await;
+        let log = listen(&server, None::<EmptyArriModel>, retry_immediately(), never).await;
+        assert_eq!(
+            log,
+            vec![
+                "open",
+                "message a",
+                "error 401 Unauthorized",
+                "close ServerError"
+            ]
+        );
+        assert_eq!(server.requests().len(), 1);
+
+        let server = SseTestServer::start([
+            SseScript::new()
+                .error_event(
+                    &ArriError {
+                        code: 500,
+                        message: "Oops".to_string(),
+                        ..ArriError::new()
+                    },
+                    true,
+                )
+                .message("b")
+                .event("error", r#"{"code":400,"message":"No reconnect field"}"#)
+                .disconnect(),
+            SseScript::new().done(),
+        ])
+        .await;
+        let log = listen(&server, None::<EmptyArriModel>, retry_immediately(), never).await;
+        assert_eq!(
+            log,
+            vec![
+                "open",
+                "error 500 Oops",
+                "message b",
+                "error 400 No reconnect field",
+                "reconnecting 1",
+                "open",
+                "close Done"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn reconnect_params_test() {
+        let server = SseTestServer::start([
+            SseScript::new().message("10").disconnect(),
+            SseScript::new().disconnect(),
+            SseScript::new().message("20").disconnect(),
+            SseScript::new().done(),
+        ])
+        .await;
+        listen(
+            &server,
+            Some(TestMessage("start".to_string())),
+            SseOptions {
+                reconnection_policy: Some(Arc::new(FixedDelay::new(Duration::ZERO))),
+                reconnect_params: Some(Arc::new(
+                    |previous: &TestMessage, last_message: Option<&TestMessage>| {
+                        last_message.unwrap_or(previous).clone()
+                    },
+                )),
+                ..Default::default()
+            },
+            never,
+        )
+        .await;
+        let paths: Vec<String> = server
+            .requests()
+            .into_iter()
+            .map(|request| request.path)
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/?cursor=start",
+                "/?cursor=10",
+                "/?cursor=10",
+                "/?cursor=20"
+            ]
+        );
     }
 }
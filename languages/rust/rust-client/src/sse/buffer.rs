@@ -0,0 +1,194 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use tokio::sync::Notify;
+
+/// Opt-in bounded buffer between the network reader and the event handler.
+/// When enabled the stream keeps reading while the handler is busy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SseBufferOptions {
+    /// Max number of messages waiting to be handled
+    pub capacity: usize,
+    pub overflow: SseOverflowPolicy,
+}
+
+impl SseBufferOptions {
+    pub fn new(capacity: usize, overflow: SseOverflowPolicy) -> Self {
+        Self { capacity, overflow }
+    }
+}
+
+/// What to do when a message arrives while the buffer is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SseOverflowPolicy {
+    /// Stop reading from the network until the handler catches up
+    Block,
+    /// Discard the oldest buffered message to make room for the new one
+    DropOldest,
+    /// Emit an error event and close the stream
+    Error,
+}
+
+pub(crate) enum PushResult {
+    Pushed,
+    Closed,
+    Overflowed,
+}
+
+/// Single producer, single consumer queue used by `EventSource`
+pub(crate) struct EventBuffer<T> {
+    queue: Mutex<VecDeque<T>>,
+    options: SseBufferOptions,
+    closed: AtomicBool,
+    finished: AtomicBool,
+    to_reader: Notify,
+    to_handler: Notify,
+}
+
+impl<T> EventBuffer<T> {
+    pub fn new(options: SseBufferOptions) -> Self {
+        Self {
+            queue: Mutex::new(VecDeque::with_capacity(options.capacity.max(1))),
+            options,
+            closed: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+            to_reader: Notify::new(),
+            to_handler: Notify::new(),
+        }
+    }
+
+    pub async fn push(&self, item: T) -> PushResult {
+        let capacity = self.options.capacity.max(1);
+        loop {
+            if self.is_closed() {
+                return PushResult::Closed;
+            }
+            {
+                let mut queue = self.queue.lock().unwrap();
+                if queue.len() < capacity {
+                    queue.push_back(item);
+                    self.to_handler.notify_one();
+                    return PushResult::Pushed;
+                }
+                match self.options.overflow {
+                    SseOverflowPolicy::Block => {}
+                    SseOverflowPolicy::DropOldest => {
+                        queue.pop_front();
+                        queue.push_back(item);
+                        self.to_handler.notify_one();
+                        return PushResult::Pushed;
+                    }
+                    SseOverflowPolicy::Error => return PushResult::Overflowed,
+                }
+            }
+            self.to_reader.notified().await;
+        }
+    }
+
+    /// Returns `None` once the reader has finished and every buffered item has been handled
+    pub async fn pop(&self) -> Option<T> {
+        loop {
+            {
+                let mut queue = self.queue.lock().unwrap();
+                if let Some(item) = queue.pop_front() {
+                    self.to_reader.notify_one();
+                    return Some(item);
+                }
+                if self.finished.load(Ordering::SeqCst) || self.is_closed() {
+                    return None;
+                }
+            }
+            self.to_handler.notified().await;
+        }
+    }
+
+    /// Called by the reader when no more items will be pushed
+    pub fn finish(&self) {
+        self.finished.store(true, Ordering::SeqCst);
+        self.to_handler.notify_one();
+    }
+
+    /// Called by the handler to stop the reader
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.to_reader.notify_one();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once `close()` has been called
+    pub async fn closed(&self) {
+        while !self.is_closed() {
+            self.to_reader.notified().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod event_buffer_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn drop_oldest_test() {
+        let buffer = EventBuffer::new(SseBufferOptions::new(2, SseOverflowPolicy::DropOldest));
+        for i in 0..5 {
+            assert!(matches!(buffer.push(i).await, PushResult::Pushed));
+        }
+        buffer.finish();
+        assert_eq!(buffer.pop().await, Some(3));
+        assert_eq!(buffer.pop().await, Some(4));
+        assert_eq!(buffer.pop().await, None);
+    }
+
+    #[tokio::test]
+    async fn error_test() {
+        let buffer = EventBuffer::new(SseBufferOptions::new(1, SseOverflowPolicy::Error));
+        assert!(matches!(buffer.push(1).await, PushResult::Pushed));
+        assert!(matches!(buffer.push(2).await, PushResult::Overflowed));
+        assert_eq!(buffer.pop().await, Some(1));
+    }
+
+    #[tokio::test]
+    async fn block_test() {
+        let buffer = EventBuffer::new(SseBufferOptions::new(1, SseOverflowPolicy::Block));
+        let reader = async {
+            for i in 0..10 {
+                assert!(matches!(buffer.push(i).await, PushResult::Pushed));
+            }
+            buffer.finish();
+        };
+        let handler = async {
+            let mut items = Vec::new();
+            while let Some(item) = buffer.pop().await {
+                tokio::task::yield_now().await;
+                items.push(item);
+            }
+            items
+        };
+        let (_, items) = tokio::join!(reader, handler);
+        assert_eq!(items, (0..10).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn close_test() {
+        let buffer = EventBuffer::new(SseBufferOptions::new(1, SseOverflowPolicy::Block));
+        assert!(matches!(buffer.push(1).await, PushResult::Pushed));
+        let reader = buffer.push(2);
+        let handler = async {
+            tokio::task::yield_now().await;
+            buffer.close();
+        };
+        let (result, _) = tokio::join!(reader, handler);
+        assert!(matches!(result, PushResult::Closed));
+        buffer.closed().await;
+        assert_eq!(buffer.pop().await, Some(1));
+        assert_eq!(buffer.pop().await, None);
+    }
+}
@@ -0,0 +1,493 @@
+use super::SseMessage;
+
+const BOM: [u8; 3] = [0xEF, 0xBB, 0xBF];
+
+/// Incremental decoder for `text/event-stream` bodies.
+///
+/// Bytes can be pushed in chunks of any size. Lines are only decoded once they are complete
+/// so multi-byte UTF-8 characters that are split across chunks are preserved. Parsing follows
+/// the WHATWG EventSource rules:
+/// <https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation>
+#[derive(Debug, Clone)]
+pub struct SseDecoder {
+    line: Vec<u8>,
+    skip_next_lf: bool,
+    checked_bom: bool,
+    data: String,
+    has_data: bool,
+    event: String,
+    last_event_id: String,
+    retry: Option<u64>,
+    pending_retry: Option<u64>,
+}
+
+impl Default for SseDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SseDecoder {
+    pub fn new() -> Self {
+        Self {
+            line: Vec::new(),
+            skip_next_lf: false,
+            checked_bom: false,
+            data: String::new(),
+            has_data: false,
+            event: String::new(),
+            last_event_id: String::new(),
+            retry: None,
+            pending_retry: None,
+        }
+    }
+
+    /// Create a decoder that continues from a previously received event id, e.g. when reconnecting
+    pub fn with_last_event_id(last_event_id: Option<String>) -> Self {
+        Self {
+            last_event_id: last_event_id.unwrap_or_default(),
+            ..Self::new()
+        }
+    }
+
+    /// Feed the next chunk of the response body and return every message it completed
+    pub fn decode(&mut self, chunk: &[u8]) -> Vec<SseMessage> {
+        let mut messages = Vec::new();
+        let mut input = chunk;
+        if !self.checked_bom {
+            let pending = self.line.len();
+            let needed = (BOM.len() - pending).min(input.len());
+            self.line.extend_from_slice(&input[..needed]);
+            input = &input[needed..];
+            if !BOM.starts_with(&self.line) {
+                self.checked_bom = true;
+                let buffered = std::mem::take(&mut self.line);
+                self.decode_bytes(&buffered, &mut messages);
+            } else if self.line.len() == BOM.len() {
+                self.checked_bom = true;
+                self.line.clear();
+            } else {
+                return messages;
+            }
+        }
+        self.decode_bytes(input, &mut messages);
+        messages
+    }
+
+    /// Discard any partially received line or event.
+    ///
+    /// Should be called when the underlying connection ends. The last event id and retry
+    /// interval are kept so that they can be used when reconnecting.
+    pub fn reset(&mut self) {
+        self.line.clear();
+        self.skip_next_lf = false;
+        self.checked_bom = false;
+        self.data.clear();
+        self.has_data = false;
+        self.event.clear();
+        self.pending_retry = None;
+    }
+
+    /// The most recent id sent by the server. `None` if no id was sent or if it was cleared.
+    pub fn last_event_id(&self) -> Option<&str> {
+        if self.last_event_id.is_empty() {
+            return None;
+        }
+        Some(self.last_event_id.as_str())
+    }
+
+    /// The most recent reconnection time (in milliseconds) sent by the server
+    pub fn retry(&self) -> Option<u64> {
+        self.retry
+    }
+
+    fn decode_bytes(&mut self, input: &[u8], messages: &mut Vec<SseMessage>) {
+        let mut start = 0;
+        for (index, byte) in input.iter().enumerate() {
+            match byte {
+                b'\n' if self.skip_next_lf => {
+                    self.skip_next_lf = false;
+                    start = index + 1;
+                }
+                b'\r' | b'\n' => {
+                    self.skip_next_lf = *byte == b'\r';
+                    self.line.extend_from_slice(&input[start..index]);
+                    start = index + 1;
+                    let line = std::mem::take(&mut self.line);
+                    if let Some(message) = self.process_line(&line) {
+                        messages.push(message);
+                    }
+                }
+                _ => {
+                    self.skip_next_lf = false;
+                }
+            }
+        }
+        self.line.extend_from_slice(&input[start..]);
+    }
+
+    fn process_line(&mut self, line: &[u8]) -> Option<SseMessage> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line[0] == b':' {
+            return None;
+        }
+        let (field, value) = match line.iter().position(|byte| *byte == b':') {
+            Some(index) => {
+                let value = &line[index + 1..];
+                (&line[..index], value.strip_prefix(b" ").unwrap_or(value))
+            }
+            None => (line, &line[line.len()..]),
+        };
+        match field {
+            b"event" => {
+                self.event = String::from_utf8_lossy(value).into_owned();
+            }
+            b"data" => {
+                if self.has_data {
+                    self.data.push('\n');
+                }
+                self.data.push_str(&String::from_utf8_lossy(value));
+                self.has_data = true;
+            }
+            b"id" if !value.contains(&0) => {
+                self.last_event_id = String::from_utf8_lossy(value).into_owned();
+            }
+            b"retry" if !value.is_empty() && value.iter().all(u8::is_ascii_digit) => {
+                let retry = std::str::from_utf8(value)
+                    .ok()
+                    .and_then(|val| val.parse::<u64>().ok())
+                    .unwrap_or(u64::MAX);
+                self.retry = Some(retry);
+                self.pending_retry = Some(retry);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseMessage> {
+        let retry = self.pending_retry.take();
+        let event = std::mem::take(&mut self.event);
+        if !self.has_data {
+            return None;
+        }
+        self.has_data = false;
+        Some(SseMessage {
+            id: self.last_event_id().map(|id| id.to_string()),
+            event: if event.is_empty() { None } else { Some(event) },
+            data: std::mem::take(&mut self.data),
+            retry,
+        })
+    }
+}
+
+#[cfg(test)]
+mod sse_decoder_tests {
+    use super::SseDecoder;
+    use crate::sse::SseMessage;
+    use proptest::prelude::*;
+
+    fn message(
+        id: Option<&str>,
+        event: Option<&str>,
+        data: &str,
+        retry: Option<u64>,
+    ) -> SseMessage {
+        SseMessage {
+            id: id.map(|val| val.to_string()),
+            event: event.map(|val| val.to_string()),
+            data: data.to_string(),
+            retry,
+        }
+    }
+
+    fn decode_all(input: &[u8]) -> Vec<SseMessage> {
+        SseDecoder::new().decode(input)
+    }
+
+    fn get_test_data() -> (Vec<String>, Vec<SseMessage>) {
+        (
+            vec![
+                "id: 1".to_string(),
+                "data: hello world".to_string(),
+                "".to_string(),
+                "data: hello world".to_string(),
+                "retry: 100".to_string(),
+                "".to_string(),
+                "id: 4".to_string(),
+            ],
+            vec![
+                message(Some("1"), None, "hello world", None),
+                message(Some("1"), None, "hello world", Some(100)),
+            ],
+        )
+    }
+
+    #[test]
+    fn decode_lf_test() {
+        let (lines, expected_msgs) = get_test_data();
+        let mut decoder = SseDecoder::new();
+        assert_eq!(decoder.decode(lines.join("\n").as_bytes()), expected_msgs);
+        assert_eq!(decoder.decode(b"\n"), vec![]);
+        assert_eq!(decoder.last_event_id(), Some("4"));
+        assert_eq!(decoder.retry(), Some(100));
+    }
+
+    #[test]
+    fn decode_crlf_test() {
+        let (lines, expected_msgs) = get_test_data();
+        assert_eq!(decode_all(lines.join("\r\n").as_bytes()), expected_msgs);
+    }
+
+    #[test]
+    fn decode_cr_test() {
+        let (lines, expected_msgs) = get_test_data();
+        assert_eq!(decode_all(lines.join("\r").as_bytes()), expected_msgs);
+    }
+
+    #[test]
+    fn skip_invalid_lines_test() {
+        let lines = [
+            "",
+            ":",
+            "hello world",
+            "hi",
+            "hi",
+            "",
+            "data: hello world",
+            "",
+            ":",
+            ":",
+            "",
+            "data: hello world",
+            "",
+            "",
+            "event: data",
+        ];
+        let expected = vec![
+            message(None, None, "hello world", None),
+            message(None, None, "hello world", None),
+        ];
+        for delimiter in ["\n", "\r\n", "\r"] {
+            assert_eq!(decode_all(lines.join(delimiter).as_bytes()), expected);
+        }
+    }
+
+    #[test]
+    fn multiline_data_test() {
+        let input = b"data: hello\ndata:  world\ndata\ndata:\n\n";
+        assert_eq!(
+            decode_all(input),
+            vec![message(None, None, "hello\n world\n\n", None)]
+        );
+    }
+
+    #[test]
+    fn field_parsing_test() {
+        let input = b": this is a comment\nevent:custom\nid\ndata:no-space\nfoo: bar\n\n";
+        assert_eq!(
+            decode_all(input),
+            vec![message(None, Some("custom"), "no-space", None)]
+        );
+        let input = b"id: 1\x002\nretry: 10a\nretry\ndata\n\n";
+        let mut decoder = SseDecoder::new();
+        assert_eq!(decoder.decode(input), vec![message(None, None, "", None)]);
+        assert_eq!(decoder.retry(), None);
+    }
+
+    #[test]
+    fn event_without_data_is_not_dispatched_test() {
+        let mut decoder = SseDecoder::new();
+        let messages = decoder.decode(b"event: done\nid: 5\nretry: 20\n\ndata: a\n\n");
+        assert_eq!(messages, vec![message(Some("5"), None, "a", None)]);
+        assert_eq!(decoder.retry(), Some(20));
+    }
+
+    #[test]
+    fn bom_test() {
+        let mut input = vec![0xEF, 0xBB, 0xBF];
+        input.extend_from_slice(b"data: a\n\n");
+        assert_eq!(decode_all(&input), vec![message(None, None, "a", None)]);
+
+        let mut decoder = SseDecoder::new();
+        assert_eq!(decoder.decode(&[0xEF]), vec![]);
+        assert_eq!(decoder.decode(&[0xBB]), vec![]);
+        assert_eq!(
+            decoder.decode(&[0xBF, b'd', b'a', b't', b'a', b'\n', b'\n']),
+            vec![message(None, None, "", None)]
+        );
+
+        // only the first BOM is stripped
+        input.splice(9..9, [0xEF, 0xBB, 0xBF]);
+        assert_eq!(
+            decode_all(&input),
+            vec![message(None, None, "\u{feff}a", None)]
+        );
+        assert_eq!(decode_all(b"\xEF\xBB"), vec![]);
+    }
+
+    #[test]
+    fn split_multibyte_char_test() {
+        let input = "data: héllo 🌍\n\n".as_bytes();
+        let mut decoder = SseDecoder::new();
+        let mut messages = Vec::new();
+        for byte in input {
+            messages.append(&mut decoder.decode(&[*byte]));
+        }
+        assert_eq!(messages, vec![message(None, None, "héllo 🌍", None)]);
+    }
+
+    #[test]
+    fn crlf_split_across_chunks_test() {
+        let mut decoder = SseDecoder::new();
+        assert_eq!(decoder.decode(b"data: a\r"), vec![]);
+        assert_eq!(
+            decoder.decode(b"\n\r\ndata: b\r\n"),
+            vec![message(None, None, "a", None)]
+        );
+        assert_eq!(decoder.decode(b"\r"), vec![message(None, None, "b", None)]);
+    }
+
+    #[test]
+    fn with_last_event_id_test() {
+        let mut decoder = SseDecoder::with_last_event_id(Some("10".to_string()));
+        assert_eq!(
+            decoder.decode(b"data: a\n\nid\ndata: b\n\n"),
+            vec![
+                message(Some("10"), None, "a", None),
+                message(None, None, "b", None)
+            ]
+        );
+    }
+
+    #[test]
+    fn reset_test() {
+        let mut decoder = SseDecoder::new();
+        assert_eq!(
+            decoder.decode(b"id: 1\ndata: a\n\ndata: b\n"),
+            vec![message(Some("1"), None, "a", None)]
+        );
+        decoder.reset();
+        assert_eq!(decoder.decode(b"\n"), vec![]);
+        assert_eq!(decoder.last_event_id(), Some("1"));
+    }
+
+    /// Straightforward reference implementation of the spec that works on the complete input
+    fn reference_decode(input: &[u8]) -> Vec<SseMessage> {
+        let input = input.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(input);
+        let text = String::from_utf8_lossy(input)
+            .replace("\r\n", "\n")
+            .replace('\r', "\n");
+        let mut lines: Vec<&str> = text.split('\n').collect();
+        // the final line is incomplete
+        lines.pop();
+        let mut messages = Vec::new();
+        let mut data: Option<String> = None;
+        let mut event = String::new();
+        let mut id = String::new();
+        let mut retry: Option<u64> = None;
+        for line in lines {
+            if line.is_empty() {
+                if let Some(data) = data.take() {
+                    messages.push(SseMessage {
+                        id: if id.is_empty() {
+                            None
+                        } else {
+                            Some(id.clone())
+                        },
+                        event: if event.is_empty() {
+                            None
+                        } else {
+                            Some(event.clone())
+                        },
+                        data,
+                        retry,
+                    });
+                }
+                event.clear();
+                retry = None;
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line, ""),
+            };
+            match field {
+                "event" => event = value.to_string(),
+                "data" => match &mut data {
+                    Some(data) => {
+                        data.push('\n');
+                        data.push_str(value);
+                    }
+                    None => data = Some(value.to_string()),
+                },
+                "id" if !value.contains('\0') => id = value.to_string(),
+                "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                    retry = Some(value.parse().unwrap_or(u64::MAX))
+                }
+                _ => {}
+            }
+        }
+        messages
+    }
+
+    fn sse_like_bytes() -> impl Strategy<Value = Vec<u8>> {
+        let token = prop_oneof![
+            Just(b"data".to_vec()),
+            Just(b"event".to_vec()),
+            Just(b"id".to_vec()),
+            Just(b"retry".to_vec()),
+            Just(b":".to_vec()),
+            Just(b": ".to_vec()),
+            Just(b"\n".to_vec()),
+            Just(b"\r".to_vec()),
+            Just(b"\r\n".to_vec()),
+            Just(b"\n\n".to_vec()),
+            Just(vec![0xEF, 0xBB, 0xBF]),
+            Just("🌍é".as_bytes().to_vec()),
+            Just(b"\0".to_vec()),
+            "[0-9]{1,3}".prop_map(|val| val.into_bytes()),
+            "[a-z ]{0,6}".prop_map(|val| val.into_bytes()),
+            prop::collection::vec(any::<u8>(), 0..4),
+        ];
+        prop::collection::vec(token, 0..40).prop_map(|tokens| tokens.concat())
+    }
+
+    proptest! {
+        #[test]
+        fn matches_reference_implementation(input in sse_like_bytes()) {
+            prop_assert_eq!(decode_all(&input), reference_decode(&input));
+        }
+
+        #[test]
+        fn chunk_boundaries_do_not_change_output(
+            input in sse_like_bytes(),
+            splits in prop::collection::vec(any::<prop::sample::Index>(), 0..8),
+        ) {
+            let mut split_points: Vec<usize> = splits.iter().map(|idx| idx.index(input.len() + 1)).collect();
+            split_points.sort();
+            let mut decoder = SseDecoder::new();
+            let mut messages = Vec::new();
+            let mut start = 0;
+            for point in split_points {
+                messages.append(&mut decoder.decode(&input[start..point]));
+                start = point;
+            }
+            messages.append(&mut decoder.decode(&input[start..]));
+            prop_assert_eq!(messages, decode_all(&input));
+        }
+
+        #[test]
+        fn arbitrary_bytes_do_not_panic(input in prop::collection::vec(any::<u8>(), 0..512)) {
+            let mut decoder = SseDecoder::new();
+            for chunk in input.chunks(7) {
+                decoder.decode(chunk);
+            }
+        }
+    }
+}
@@ -0,0 +1,406 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::ArriModel;
+
+use super::{
+    body_failed, connection_error, handle_message, EventSource, SseAction, SseCloseReason,
+    SseController, SseDecoder, SseEvent, SseFailureKind, SseHandlerOutput,
+};
+
+/// Header sent with every long polling request
+pub(crate) const TRANSPORT_HEADER: &str = "arri-transport";
+pub(crate) const LONG_POLL_TRANSPORT: &str = "long-poll";
+/// Used after an empty poll when the server hasn't sent a `retry` field
+const MIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How event stream messages are delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SseTransport {
+    /// A single long lived `text/event-stream` response
+    #[default]
+    EventStream,
+    /// Repeated requests that each return a batch of messages. Use this when a proxy
+    /// buffers or cuts `text/event-stream` responses.
+    ///
+    /// Requests are the same as event stream requests with the `arri-transport: long-poll`
+    /// header added. The server holds each request open until it has at least one message
+    /// (or until its own poll timeout) and then responds with the messages encoded the same
+    /// way as server sent events. Messages after the `Last-Event-ID` header should be sent.
+    /// An empty response means that there are no new messages. The next poll is sent once the
+    /// `retry` interval sent by the server (or one second) has passed since the empty poll started,
+    /// so servers that answer straight away aren't polled in a busy loop.
+    ///
+    /// `SseOptions::buffer` is ignored since every batch is handled once its poll completes.
+    LongPolling,
+    /// Start with `EventStream` and switch to `LongPolling` once this many consecutive
+    /// connection attempts have failed without receiving a single message. Attempts that
+    /// receive no data within `SseOptions::heartbeat_timeout` count as failures.
+    Fallback { after_failures: u64 },
+}
+
+impl EventSource<'_> {
+    /// Poll until the stream ends or a request fails. `Open` is emitted after the first
+    /// successful poll.
+    pub(super) async fn send_long_poll<T, OnEvent, OnEventOutput>(
+        &mut self,
+        params: Option<impl ArriModel + Clone>,
+        on_event: &mut OnEvent,
+        controller: &mut SseController,
+        mut last_message: Option<&mut Option<T>>,
+    ) -> SseAction
+    where
+        T: ArriModel + Clone,
+        OnEvent: FnMut(SseEvent<T>, &mut SseController) -> OnEventOutput,
+        OnEventOutput: SseHandlerOutput,
+    {
+        let mut is_open = false;
+        loop {
+            controller.last_event_id = self.last_event_id.clone();
+            let poll_started = Instant::now();
+            let response = self
+                .build_request(params.clone())
+                .header(TRANSPORT_HEADER, LONG_POLL_TRANSPORT)
+                .send()
+                .await;
+            if controller.is_aborted() {
+                return SseAction::Close(SseCloseReason::Aborted);
+            }
+            let response = match response {
+                Ok(response) => response,
+                Err(err) => {
+                    on_event(SseEvent::Error(connection_error(&err)), controller)
+                        .into_future()
+                        .await;
+                    if controller.is_aborted() {
+                        return SseAction::Close(SseCloseReason::Aborted);
+                    }
+                    if err.is_timeout() {
+                        return SseAction::Retry(SseFailureKind::Timeout);
+                    }
+                    return SseAction::Retry(SseFailureKind::Connection);
+                }
+            };
+            if !is_open {
+                is_open = true;
+                if let Some(deprecation) = &self.deprecation {
+                    deprecation.report_headers(response.headers());
+                }
+                on_event(SseEvent::Open, controller).into_future().await;
+                if controller.is_aborted() {
+                    return SseAction::Close(SseCloseReason::Aborted);
+                }
+            }
+            let status = response.status().as_u16();
+            if !(200..300).contains(&status) {
+                return self.response_failed(response, on_event, controller).await;
+            }
+            self.retry_count = 0;
+            let body = match response.bytes().await {
+                Ok(body) => body,
+                Err(err) => return body_failed(&err, on_event, controller).await,
+            };
+            let mut decoder = SseDecoder::with_last_event_id(self.last_event_id.clone());
+            let messages = decoder.decode(&body);
+            let is_empty = messages.is_empty();
+            self.message_count += messages.len() as u64;
+            self.last_event_id = decoder.last_event_id().map(|id| id.to_string());
+            if decoder.retry().is_some() {
+                self.server_retry = decoder.retry();
+            }
+            for message in messages {
+                if let Some(action) =
+                    handle_message(message, on_event, controller, last_message.as_deref_mut()).await
+                {
+                    return action;
+                }
+            }
+            if controller.is_aborted() {
+                return SseAction::Close(SseCloseReason::Aborted);
+            }
+            if is_empty {
+                let interval = self
+                    .server_retry
+                    .map_or(MIN_POLL_INTERVAL, Duration::from_millis);
+                tokio::time::sleep_until(poll_started + interval).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod long_poll_tests {
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex, RwLock,
+        },
+    };
+
+    use super::*;
+    use crate::{
+        sse::{
+            parsed_arri_sse_request,
+            testing::{LongPollTestServer, SseScript, SseTestRequest, SseTestServer},
+            ArriParsedSseRequestOptions, FixedDelay, SseFailure, SseOptions,
+        },
+        version::{VersionMismatch, VersionMismatchListener},
+        ArriError, ArriErrorKind,
+    };
+
+    /// `(transport, Last-Event-ID)` of a request
+    fn transport_and_id(request: SseTestRequest) -> (String, Option<String>) {
+        (
+            request
+                .headers
+                .get(TRANSPORT_HEADER)
+                .cloned()
+                .unwrap_or_default(),
+            request.headers.get("last-event-id").cloned(),
+        )
+    }
+
+    async fn collect(
+        url: String,
+        options: SseOptions<ArriError, ArriError>,
+    ) -> (Vec<u16>, Vec<SseCloseReason>, usize) {
+        let client = reqwest::Client::new();
+        let mut codes = Vec::new();
+        let mut close_reasons = Vec::new();
+        let mut open_count = 0;
+        parsed_arri_sse_request(
+            ArriParsedSseRequestOptions {
+                client: &client,
+                client_version: String::new(),
+                url,
+                method: reqwest::Method::GET,
+                headers: Arc::new(RwLock::new(HashMap::new())),
+                deprecation: None,
+                version_mismatch: None,
+                options,
+            },
+            None::<ArriError>,
+            &mut |event: SseEvent<ArriError>, _| match event {
+                SseEvent::Message(message) => codes.push(message.code),
+                SseEvent::Open => open_count += 1,
+                SseEvent::Close(reason) => close_reasons.push(reason),
+                _ => {}
+            },
+        )
+        .await;
+        (codes, close_reasons, open_count)
+    }
+
+    #[tokio::test]
+    async fn long_polling_test() {
+        let server =
+            LongPollTestServer::start(5, Duration::from_millis(30), Duration::from_millis(10))
+                .await;
+        let (codes, close_reasons, open_count) = collect(
+            server.url().to_string(),
+            SseOptions {
+                transport: SseTransport::LongPolling,
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(codes, vec![0, 1, 2, 3, 4]);
+        assert_eq!(close_reasons, vec![SseCloseReason::Done]);
+        assert_eq!(open_count, 1);
+        let requests: Vec<_> = server
+            .requests()
+            .into_iter()
+            .map(transport_and_id)
+            .collect();
+        assert!(requests
+            .iter()
+            .all(|(transport, _)| transport == LONG_POLL_TRANSPORT));
+        assert_eq!(requests[0].1, None);
+        // every poll resumes after the last message that was received
+        let resumed_from: Vec<u16> = requests[1..]
+            .iter()
+            .map(|(_, id)| id.as_ref().unwrap().parse().unwrap())
+            .collect();
+        assert!(resumed_from.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(resumed_from.iter().all(|id| *id < 5));
+    }
+
+    #[tokio::test]
+    async fn empty_poll_interval_test() {
+        let server = SseTestServer::start([
+            SseScript::new(),
+            SseScript::new().retry(Duration::from_millis(300)),
+            SseScript::new().done(),
+        ])
+        .await;
+        let started = Instant::now();
+        let (_, close_reasons, _) = collect(
+            server.url().to_string(),
+            SseOptions {
+                transport: SseTransport::LongPolling,
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(close_reasons, vec![SseCloseReason::Done]);
+        assert_eq!(server.requests().len(), 3);
+        // one second after the first empty poll, then the interval sent by the server
+        assert!(started.elapsed() >= MIN_POLL_INTERVAL + Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn fallback_test() {
+        let server =
+            LongPollTestServer::start(3, Duration::from_millis(10), Duration::from_millis(50))
+                .await;
+        let (codes, close_reasons, _) = collect(
+            server.url().to_string(),
+            SseOptions {
+                transport: SseTransport::Fallback { after_failures: 2 },
+                reconnection_policy: Some(Arc::new(FixedDelay::new(Duration::ZERO))),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(codes, vec![0, 1, 2]);
+        assert_eq!(close_reasons, vec![SseCloseReason::Done]);
+        let transports: Vec<String> = server
+            .requests()
+            .into_iter()
+            .map(|request| transport_and_id(request).0)
+            .collect();
+        assert_eq!(&transports[..2], &["", ""]);
+        assert!(transports[2..]
+            .iter()
+            .all(|transport| transport == LONG_POLL_TRANSPORT));
+    }
+
+    #[tokio::test]
+    async fn buffering_proxy_fallback_test() {
+        // the proxy sends the response headers but holds back the body
+        let server = SseTestServer::start([
+            SseScript::new()
+                .heartbeat_interval(Duration::from_millis(20))
+                .stall(),
+            SseScript::new().stall(),
+            SseScript::new()
+                .id("0")
+                .message(r#"{"code":0,"message":""}"#)
+                .done(),
+        ])
+        .await;
+        let started = Instant::now();
+        let (codes, close_reasons, _) = collect(
+            server.url().to_string(),
+            SseOptions {
+                transport: SseTransport::Fallback { after_failures: 2 },
+                heartbeat_timeout: Some(Duration::from_millis(100)),
+                reconnection_policy: Some(Arc::new(FixedDelay::new(Duration::ZERO))),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(codes, vec![0]);
+        assert_eq!(close_reasons, vec![SseCloseReason::Done]);
+        let transports: Vec<String> = server
+            .requests()
+            .into_iter()
+            .map(|request| transport_and_id(request).0)
+            .collect();
+        assert_eq!(transports, vec!["", "", LONG_POLL_TRANSPORT]);
+        // the heartbeat-interval header takes precedence over the configured timeout
+        assert!(started.elapsed() >= Duration::from_millis(140));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn event_stream_never_falls_back_test() {
+        let failures = AtomicUsize::new(0);
+        let server =
+            LongPollTestServer::start(3, Duration::from_millis(10), Duration::from_millis(50))
+                .await;
+        let (codes, close_reasons, _) = collect(
+            server.url().to_string(),
+            SseOptions {
+                // the connection opens successfully every time so the attempt count keeps resetting
+                reconnection_policy: Some(Arc::new(move |_: &SseFailure| {
+                    match failures.fetch_add(1, Ordering::SeqCst) {
+                        0..=2 => Some(Duration::ZERO),
+                        _ => None,
+                    }
+                })),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert!(codes.is_empty());
+        assert!(matches!(close_reasons[..], [SseCloseReason::GaveUp(_)]));
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn version_mismatch_test() {
+        for transport in [SseTransport::EventStream, SseTransport::LongPolling] {
+            let server = SseTestServer::start([SseScript::error(
+                426,
+                r#"{"code":426,"message":"Please update"}"#,
+            )
+            .header("min-client-version", "2")])
+            .await;
+            let listener = VersionMismatchListener::default();
+            let mismatches = Arc::new(Mutex::new(Vec::new()));
+            let received = mismatches.clone();
+            listener.set(move |mismatch| received.lock().unwrap().push(mismatch.clone()));
+            let client = reqwest::Client::new();
+            let mut errors = Vec::new();
+            let mut close_reasons = Vec::new();
+            parsed_arri_sse_request(
+                ArriParsedSseRequestOptions {
+                    client: &client,
+                    client_version: "1".to_string(),
+                    url: server.url().to_string(),
+                    method: reqwest::Method::GET,
+                    headers: Arc::new(RwLock::new(HashMap::new())),
+                    deprecation: None,
+                    version_mismatch: Some(listener),
+                    // the default policy would keep reconnecting
+                    options: SseOptions {
+                        transport,
+                        reconnection_policy: Some(Arc::new(FixedDelay::new(Duration::ZERO))),
+                        ..Default::default()
+                    },
+                },
+                None::<ArriError>,
+                &mut |event: SseEvent<ArriError>, _| match event {
+                    SseEvent::Error(err) => errors.push(err),
+                    SseEvent::Close(reason) => close_reasons.push(reason),
+                    _ => {}
+                },
+            )
+            .await;
+            let expected = VersionMismatch {
+                client_version: Some("1".to_string()),
+                min_version: Some("2".to_string()),
+                recommended_version: None,
+            };
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].message, "Please update");
+            assert_eq!(
+                errors[0].kind(),
+                ArriErrorKind::VersionMismatch(expected.clone())
+            );
+            assert_eq!(*mismatches.lock().unwrap(), vec![expected]);
+            assert!(matches!(
+                close_reasons[..],
+                [SseCloseReason::GaveUp(SseFailure {
+                    kind: SseFailureKind::Status(426),
+                    ..
+                })]
+            ));
+            // an outdated client doesn't reconnect
+            assert_eq!(server.requests().len(), 1);
+        }
+    }
+}
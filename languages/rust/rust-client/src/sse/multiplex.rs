@@ -0,0 +1,491 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, Weak,
+    },
+};
+
+use tokio::{sync::broadcast, task::AbortHandle};
+
+use super::{parsed_arri_sse_request, ArriParsedSseRequestOptions, SseEvent};
+use crate::ArriModel;
+
+static NEXT_UPSTREAM_ID: AtomicU64 = AtomicU64::new(0);
+
+type Upstreams = Mutex<HashMap<String, Upstream>>;
+
+struct Upstream {
+    id: u64,
+    /// `broadcast::Sender<SseEvent<T>>`
+    sender: Box<dyn Any + Send>,
+    subscribers: usize,
+    /// Subscribers that join an open connection receive `SseEvent::Open` straight away
+    is_open: bool,
+    task: AbortHandle,
+}
+
+/// Shares a single upstream connection between every subscriber of the same event stream.
+///
+/// Streams are identified by their HTTP method, url, params, headers, client version and
+/// `SseOptions`, so subscribers only share a connection when they would have opened the same
+/// one on their own. The connection is opened by
+/// the first subscriber and closed once the last subscriber has been dropped. Clones of the
+/// multiplexer share the same connections.
+///
+/// Subscribers only receive events sent after they subscribed.
+#[derive(Clone)]
+pub struct SseMultiplexer {
+    upstreams: Arc<Upstreams>,
+    capacity: usize,
+}
+
+impl Default for SseMultiplexer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SseMultiplexer {
+    pub fn new() -> Self {
+        Self::with_capacity(256)
+    }
+
+    /// `capacity` is the number of events kept for subscribers that fall behind.
+    /// Slow subscribers skip the oldest events once it is exceeded.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            upstreams: Arc::new(Mutex::new(HashMap::new())),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Number of upstream connections that are currently open
+    pub fn connection_count(&self) -> usize {
+        self.upstreams.lock().unwrap().len()
+    }
+
+    /// Subscribe to an event stream. Opens a new upstream connection if there isn't one already.
+    ///
+    /// Must be called from within a tokio runtime.
+    pub fn subscribe<T, P>(
+        &self,
+        options: ArriParsedSseRequestOptions<'_, P, T>,
+        params: Option<P>,
+    ) -> SseSubscription<T>
+    where
+        T: ArriModel + Clone + Send + Sync + 'static,
+        P: ArriModel + Clone + Send + 'static,
+    {
+        let key = upstream_key(&options, params.as_ref());
+        let mut upstreams = self.upstreams.lock().unwrap();
+        if let Some(upstream) = upstreams.get_mut(&key) {
+            if let Some(sender) = upstream
+                .sender
+                .downcast_ref::<broadcast::Sender<SseEvent<T>>>()
+            {
+                upstream.subscribers += 1;
+                return SseSubscription {
+                    receiver: sender.subscribe(),
+                    replay_open: upstream.is_open,
+                    key,
+                    upstream_id: upstream.id,
+                    upstreams: Arc::downgrade(&self.upstreams),
+                };
+            }
+        }
+
+        let id = NEXT_UPSTREAM_ID.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = broadcast::channel::<SseEvent<T>>(self.capacity);
+        let task_sender = sender.clone();
+        let task_upstreams = Arc::downgrade(&self.upstreams);
+        let task_key = key.clone();
+        let http_client = options.client.clone();
+        let url = options.url;
+        let method = options.method;
+        let headers = options.headers;
+        let client_version = options.client_version;
+        let deprecation = options.deprecation;
+        let version_mismatch = options.version_mismatch;
+        let mut sse_options = options.options;
+        sse_options.multiplexer = None;
+        let task = tokio::spawn(async move {
+            parsed_arri_sse_request(
+                ArriParsedSseRequestOptions {
+                    client: &http_client,
+                    client_version,
+                    url,
+                    method,
+                    headers,
+                    deprecation,
+                    version_mismatch,
+                    options: sse_options,
+                },
+                params,
+                &mut |event: SseEvent<T>, _| {
+                    let is_open = match &event {
+                        SseEvent::Message(_) | SseEvent::Custom(_) => None,
+                        SseEvent::Open => Some(true),
+                        _ => Some(false),
+                    };
+                    // hold the lock while sending so that new subscribers see a consistent open state
+                    let upstreams = task_upstreams.upgrade();
+                    let mut upstreams = upstreams.as_ref().map(|val| val.lock().unwrap());
+                    if let (Some(is_open), Some(upstreams)) = (is_open, upstreams.as_mut()) {
+                        if let Some(upstream) = upstreams.get_mut(&task_key) {
+                            if upstream.id == id {
+                                upstream.is_open = is_open;
+                            }
+                        }
+                    }
+                    // sending only fails when there are no subscribers left
+                    let _ = task_sender.send(event);
+                },
+            )
+            .await;
+            if let Some(upstreams) = task_upstreams.upgrade() {
+                remove_upstream(&upstreams, &task_key, id);
+            }
+        });
+        upstreams.insert(
+            key.clone(),
+            Upstream {
+                id,
+                sender: Box::new(sender),
+                subscribers: 1,
+                is_open: false,
+                task: task.abort_handle(),
+            },
+        );
+        SseSubscription {
+            receiver,
+            replay_open: false,
+            key,
+            upstream_id: id,
+            upstreams: Arc::downgrade(&self.upstreams),
+        }
+    }
+}
+
+/// Everything that affects the upstream connection, so that subscribers with different
+/// credentials or options never share a connection
+fn upstream_key<P: ArriModel, T>(
+    options: &ArriParsedSseRequestOptions<'_, P, T>,
+    params: Option<&P>,
+) -> String {
+    let mut headers = options
+        .headers
+        .read()
+        .unwrap()
+        .iter()
+        .map(|(key, value)| format!("{}: {}", key.to_lowercase(), value))
+        .collect::<Vec<_>>();
+    headers.sort();
+    let sse_options = &options.options;
+    let reconnection_policy = sse_options
+        .reconnection_policy
+        .as_ref()
+        .map(|val| Arc::as_ptr(val) as *const () as usize);
+    let reconnect_params = sse_options
+        .reconnect_params
+        .as_ref()
+        .map(|val| Arc::as_ptr(val) as *const () as usize);
+    format!(
+        "{} {} {} {} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {}",
+        std::any::type_name::<T>(),
+        options.method,
+        options.url,
+        options.client_version,
+        headers,
+        sse_options.max_retry_count,
+        sse_options.max_retry_interval,
+        sse_options.buffer,
+        sse_options.transport,
+        sse_options.heartbeat_timeout,
+        reconnection_policy,
+        reconnect_params,
+        params.map(|val| val.to_json_string()).unwrap_or_default()
+    )
+}
+
+fn remove_upstream(upstreams: &Upstreams, key: &str, id: u64) -> Option<Upstream> {
+    let mut upstreams = upstreams.lock().unwrap();
+    match upstreams.get(key) {
+        Some(upstream) if upstream.id == id => upstreams.remove(key),
+        _ => None,
+    }
+}
+
+/// A subscriber of a shared event stream. Dropping it unsubscribes.
+pub struct SseSubscription<T> {
+    receiver: broadcast::Receiver<SseEvent<T>>,
+    replay_open: bool,
+    key: String,
+    upstream_id: u64,
+    upstreams: Weak<Upstreams>,
+}
+
+impl<T: Clone> SseSubscription<T> {
+    /// Wait for the next event. Returns `None` after the upstream connection has closed.
+    pub async fn recv(&mut self) -> Option<SseEvent<T>> {
+        if self.replay_open {
+            self.replay_open = false;
+            return Some(SseEvent::Open);
+        }
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) => return Some(event),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+impl<T> Drop for SseSubscription<T> {
+    fn drop(&mut self) {
+        let upstreams = match self.upstreams.upgrade() {
+            Some(upstreams) => upstreams,
+            None => return,
+        };
+        let removed = {
+            let mut upstreams = upstreams.lock().unwrap();
+            match upstreams.get_mut(&self.key) {
+                Some(upstream) if upstream.id == self.upstream_id => {
+                    upstream.subscribers -= 1;
+                    if upstream.subscribers == 0 {
+                        upstreams.remove(&self.key)
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            }
+        };
+        if let Some(upstream) = removed {
+            upstream.task.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod sse_multiplexer_tests {
+    use std::{sync::RwLock, time::Duration};
+
+    use super::*;
+    use crate::{
+        sse::{
+            testing::{SseScript, SseTestServer},
+            SseCloseReason, SseOptions,
+        },
+        ArriError, EmptyArriModel,
+    };
+
+    /// Sends a `tick` event every 10ms
+    async fn tick_server() -> SseTestServer {
+        let mut script = SseScript::new();
+        for i in 0..1000 {
+            script = script
+                .event("tick", &i.to_string())
+                .delay(Duration::from_millis(10));
+        }
+        SseTestServer::start([script]).await
+    }
+
+    fn options<'a, P>(
+        client: &'a reqwest::Client,
+        url: &str,
+    ) -> ArriParsedSseRequestOptions<'a, P, EmptyArriModel> {
+        ArriParsedSseRequestOptions {
+            client,
+            client_version: String::new(),
+            url: url.to_string(),
+            method: reqwest::Method::GET,
+            headers: Arc::new(RwLock::new(HashMap::new())),
+            deprecation: None,
+            version_mismatch: None,
+            options: SseOptions::default(),
+        }
+    }
+
+    fn options_with_headers<'a, P>(
+        client: &'a reqwest::Client,
+        url: &str,
+        headers: &[(&'static str, &str)],
+    ) -> ArriParsedSseRequestOptions<'a, P, EmptyArriModel> {
+        let options = options(client, url);
+        for (key, value) in headers {
+            options
+                .headers
+                .write()
+                .unwrap()
+                .insert(*key, value.to_string());
+        }
+        options
+    }
+
+    async fn next_tick(subscription: &mut SseSubscription<EmptyArriModel>) -> String {
+        loop {
+            match subscription.recv().await {
+                Some(SseEvent::Custom(message)) => return message.data,
+                Some(_) => continue,
+                None => panic!("subscription closed"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn shares_connection_test() {
+        let server = tick_server().await;
+        let url = server.url();
+        let client = reqwest::Client::new();
+        let multiplexer = SseMultiplexer::new();
+        let mut first = multiplexer
+            .subscribe::<EmptyArriModel, _>(options(&client, url), None::<EmptyArriModel>);
+        next_tick(&mut first).await;
+        let mut second = multiplexer
+            .subscribe::<EmptyArriModel, _>(options(&client, url), None::<EmptyArriModel>);
+        assert!(matches!(second.recv().await, Some(SseEvent::Open)));
+        assert_eq!(next_tick(&mut first).await, next_tick(&mut second).await);
+        assert_eq!(multiplexer.connection_count(), 1);
+        assert_eq!(server.requests().len(), 1);
+
+        drop(first);
+        next_tick(&mut second).await;
+        assert_eq!(multiplexer.connection_count(), 1);
+        drop(second);
+        assert_eq!(multiplexer.connection_count(), 0);
+
+        let mut third = multiplexer
+            .subscribe::<EmptyArriModel, _>(options(&client, url), None::<EmptyArriModel>);
+        next_tick(&mut third).await;
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn separate_params_test() {
+        let server = tick_server().await;
+        let url = server.url();
+        let client = reqwest::Client::new();
+        let multiplexer = SseMultiplexer::new();
+        let mut first = multiplexer.subscribe(
+            options(&client, url),
+            Some(ArriError {
+                code: 1,
+                ..ArriError::new()
+            }),
+        );
+        let mut second = multiplexer.subscribe(
+            options(&client, url),
+            Some(ArriError {
+                code: 2,
+                ..ArriError::new()
+            }),
+        );
+        next_tick(&mut first).await;
+        next_tick(&mut second).await;
+        assert_eq!(multiplexer.connection_count(), 2);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn separate_headers_test() {
+        let server = tick_server().await;
+        let url = server.url();
+        let client = reqwest::Client::new();
+        let multiplexer = SseMultiplexer::new();
+        let mut first = multiplexer.subscribe::<EmptyArriModel, _>(
+            options_with_headers(&client, url, &[("Authorization", "Bearer a")]),
+            None::<EmptyArriModel>,
+        );
+        let mut second = multiplexer.subscribe::<EmptyArriModel, _>(
+            options_with_headers(&client, url, &[("Authorization", "Bearer b")]),
+            None::<EmptyArriModel>,
+        );
+        let mut third = multiplexer.subscribe::<EmptyArriModel, _>(
+            options_with_headers(&client, url, &[("authorization", "Bearer a")]),
+            None::<EmptyArriModel>,
+        );
+        next_tick(&mut first).await;
+        next_tick(&mut second).await;
+        next_tick(&mut third).await;
+        assert_eq!(multiplexer.connection_count(), 2);
+        let mut authorization = server
+            .requests()
+            .iter()
+            .map(|request| request.headers.get("authorization").cloned())
+            .collect::<Vec<_>>();
+        authorization.sort();
+        assert_eq!(
+            authorization,
+            vec![Some("Bearer a".to_string()), Some("Bearer b".to_string())]
+        );
+
+        // subscribers with different options don't share a connection either
+        let mut fourth = multiplexer.subscribe::<EmptyArriModel, _>(
+            ArriParsedSseRequestOptions {
+                options: SseOptions {
+                    max_retry_count: Some(1),
+                    ..Default::default()
+                },
+                ..options_with_headers(&client, url, &[("Authorization", "Bearer a")])
+            },
+            None::<EmptyArriModel>,
+        );
+        next_tick(&mut fourth).await;
+        assert_eq!(multiplexer.connection_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn join_closing_connection_test() {
+        let client = reqwest::Client::new();
+        let multiplexer = SseMultiplexer::new();
+        // an upstream that has sent its close event but hasn't been removed yet
+        let (sender, _) = broadcast::channel::<SseEvent<EmptyArriModel>>(1);
+        let key = upstream_key(
+            &options::<EmptyArriModel>(&client, "http://localhost"),
+            None,
+        );
+        let id = NEXT_UPSTREAM_ID.fetch_add(1, Ordering::Relaxed);
+        multiplexer.upstreams.lock().unwrap().insert(
+            key.clone(),
+            Upstream {
+                id,
+                sender: Box::new(sender),
+                subscribers: 1,
+                is_open: false,
+                task: tokio::spawn(async {}).abort_handle(),
+            },
+        );
+        let sse_options = SseOptions {
+            multiplexer: Some(multiplexer.clone()),
+            ..Default::default()
+        };
+        let task = tokio::spawn(async move {
+            let mut events = Vec::new();
+            parsed_arri_sse_request(
+                ArriParsedSseRequestOptions {
+                    options: sse_options,
+                    ..options::<EmptyArriModel>(&client, "http://localhost")
+                },
+                None::<EmptyArriModel>,
+                &mut |event: SseEvent<EmptyArriModel>, _| events.push(event),
+            )
+            .await;
+            events
+        });
+        while multiplexer.upstreams.lock().unwrap()[&key].subscribers < 2 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        remove_upstream(&multiplexer.upstreams, &key, id);
+        let events = tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            events.as_slice(),
+            [SseEvent::Close(SseCloseReason::Ended)]
+        ));
+    }
+}
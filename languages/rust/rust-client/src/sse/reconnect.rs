@@ -0,0 +1,387 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::{Duration, SystemTime},
+};
+
+/// Why an event stream connection failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SseFailureKind {
+    /// The request could not be sent or no response was received
+    Connection,
+    /// The request timed out
+    Timeout,
+    /// The server responded with a non 2xx status code
+    Status(u16),
+    /// The connection was closed before the server sent a `done` event
+    Disconnected,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseFailure {
+    pub kind: SseFailureKind,
+    /// Number of consecutive failures including this one. Resets after a successful connection.
+    pub attempt: u64,
+    /// The most recent reconnection time (in milliseconds) sent by the server using the `retry` field
+    pub server_retry: Option<u64>,
+}
+
+impl SseFailure {
+    pub fn status(&self) -> Option<u16> {
+        match self.kind {
+            SseFailureKind::Status(status) => Some(status),
+            _ => None,
+        }
+    }
+}
+
+/// Decides if and when an event stream should reconnect after a failure
+pub trait ReconnectionPolicy: Send + Sync {
+    /// Return `None` to stop reconnecting
+    fn next_delay(&self, failure: &SseFailure) -> Option<Duration>;
+
+    /// Only reconnect when `predicate` returns true. Otherwise defer to this policy.
+    ///
+    /// ```
+    /// use arri_client::sse::{ExponentialBackoff, ReconnectionPolicy};
+    ///
+    /// let policy = ExponentialBackoff::default().retry_if(|failure| failure.status() != Some(401));
+    /// ```
+    fn retry_if<F>(self, predicate: F) -> RetryIf<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&SseFailure) -> bool + Send + Sync,
+    {
+        RetryIf {
+            policy: self,
+            predicate,
+        }
+    }
+}
+
+impl<F> ReconnectionPolicy for F
+where
+    F: Fn(&SseFailure) -> Option<Duration> + Send + Sync,
+{
+    fn next_delay(&self, failure: &SseFailure) -> Option<Duration> {
+        self(failure)
+    }
+}
+
+pub struct RetryIf<P, F> {
+    policy: P,
+    predicate: F,
+}
+
+impl<P, F> ReconnectionPolicy for RetryIf<P, F>
+where
+    P: ReconnectionPolicy,
+    F: Fn(&SseFailure) -> bool + Send + Sync,
+{
+    fn next_delay(&self, failure: &SseFailure) -> Option<Duration> {
+        if !(self.predicate)(failure) {
+            return None;
+        }
+        self.policy.next_delay(failure)
+    }
+}
+
+/// Exponentially increasing delays with random jitter so that many clients don't reconnect at the same time
+#[derive(Debug, Clone)]
+pub struct ExponentialBackoff {
+    pub initial_interval: Duration,
+    pub max_interval: Duration,
+    pub multiplier: f64,
+    /// Fraction of the delay (0.0 - 1.0) that is randomly subtracted from it
+    pub jitter: f64,
+    /// Defaults to None
+    pub max_retries: Option<u64>,
+}
+
+impl Default for ExponentialBackoff {
+    fn default() -> Self {
+        Self {
+            initial_interval: Duration::from_millis(250),
+            max_interval: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.5,
+            max_retries: None,
+        }
+    }
+}
+
+impl ReconnectionPolicy for ExponentialBackoff {
+    fn next_delay(&self, failure: &SseFailure) -> Option<Duration> {
+        if exceeds_max_retries(failure, self.max_retries) {
+            return None;
+        }
+        let exponent = i32::try_from(failure.attempt.saturating_sub(1)).unwrap_or(i32::MAX);
+        let max_interval = self.max_interval.as_secs_f64();
+        let delay = self.initial_interval.as_secs_f64() * self.multiplier.powi(exponent);
+        // `Duration::from_secs_f64` panics on negative and NaN values
+        let delay = if delay.is_finite() {
+            delay.clamp(0.0, max_interval)
+        } else {
+            max_interval
+        };
+        let jitter = if self.jitter.is_nan() {
+            0.0
+        } else {
+            self.jitter.clamp(0.0, 1.0) * random_fraction()
+        };
+        Some(Duration::from_secs_f64(delay * (1.0 - jitter)))
+    }
+}
+
+/// Wait the same amount of time before every reconnection attempt
+#[derive(Debug, Clone)]
+pub struct FixedDelay {
+    pub delay: Duration,
+    /// Defaults to None
+    pub max_retries: Option<u64>,
+}
+
+impl FixedDelay {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            max_retries: None,
+        }
+    }
+}
+
+impl ReconnectionPolicy for FixedDelay {
+    fn next_delay(&self, failure: &SseFailure) -> Option<Duration> {
+        if exceeds_max_retries(failure, self.max_retries) {
+            return None;
+        }
+        Some(self.delay)
+    }
+}
+
+/// Never reconnect
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoRetry;
+
+impl ReconnectionPolicy for NoRetry {
+    fn next_delay(&self, _: &SseFailure) -> Option<Duration> {
+        None
+    }
+}
+
+/// The policy used when no reconnection policy has been specified.
+/// Reconnects immediately for the first five attempts and then doubles the delay starting from 2ms.
+#[derive(Debug, Clone)]
+pub struct DefaultReconnectionPolicy {
+    /// Defaults to None
+    pub max_retry_count: Option<u64>,
+    /// Max delay time in ms. defaults to 30000.
+    pub max_retry_interval: u64,
+}
+
+impl Default for DefaultReconnectionPolicy {
+    fn default() -> Self {
+        Self {
+            max_retry_count: None,
+            max_retry_interval: 30000,
+        }
+    }
+}
+
+impl ReconnectionPolicy for DefaultReconnectionPolicy {
+    fn next_delay(&self, failure: &SseFailure) -> Option<Duration> {
+        if exceeds_max_retries(failure, self.max_retry_count) {
+            return None;
+        }
+        if failure.attempt <= 5 {
+            return Some(Duration::ZERO);
+        }
+        let exponent = u32::try_from(failure.attempt - 6).unwrap_or(u32::MAX);
+        let interval = 2u64
+            .saturating_mul(2u64.checked_pow(exponent).unwrap_or(u64::MAX))
+            .min(self.max_retry_interval);
+        Some(Duration::from_millis(interval))
+    }
+}
+
+fn exceeds_max_retries(failure: &SseFailure, max_retries: Option<u64>) -> bool {
+    match max_retries {
+        Some(max_retries) => failure.attempt > max_retries,
+        None => false,
+    }
+}
+
+/// Random number between 0.0 and 1.0. Good enough for jitter without pulling in an RNG crate.
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod reconnection_policy_tests {
+    use super::*;
+
+    fn failure(kind: SseFailureKind, attempt: u64) -> SseFailure {
+        SseFailure {
+            kind,
+            attempt,
+            server_retry: None,
+        }
+    }
+
+    #[test]
+    fn default_policy_test() {
+        let policy = DefaultReconnectionPolicy {
+            max_retry_count: Some(10),
+            max_retry_interval: 10,
+        };
+        let delays: Vec<Option<u64>> = (1..=11)
+            .map(|attempt| {
+                policy
+                    .next_delay(&failure(SseFailureKind::Disconnected, attempt))
+                    .map(|delay| delay.as_millis() as u64)
+            })
+            .collect();
+        assert_eq!(
+            delays,
+            vec![
+                Some(0),
+                Some(0),
+                Some(0),
+                Some(0),
+                Some(0),
+                Some(2),
+                Some(4),
+                Some(8),
+                Some(10),
+                Some(10),
+                None
+            ]
+        );
+        let policy = DefaultReconnectionPolicy::default();
+        assert_eq!(
+            policy.next_delay(&failure(SseFailureKind::Connection, 1000)),
+            Some(Duration::from_millis(30000))
+        );
+    }
+
+    #[test]
+    fn exponential_backoff_test() {
+        let policy = ExponentialBackoff {
+            initial_interval: Duration::from_millis(100),
+            max_interval: Duration::from_millis(1000),
+            multiplier: 2.0,
+            jitter: 0.5,
+            max_retries: Some(20),
+        };
+        for (attempt, max) in [
+            (1, 100),
+            (2, 200),
+            (3, 400),
+            (4, 800),
+            (5, 1000),
+            (20, 1000),
+        ] {
+            let delay = policy
+                .next_delay(&failure(SseFailureKind::Timeout, attempt))
+                .unwrap();
+            assert!(delay <= Duration::from_millis(max));
+            assert!(delay >= Duration::from_millis(max / 2));
+        }
+        assert_eq!(
+            policy.next_delay(&failure(SseFailureKind::Timeout, 21)),
+            None
+        );
+        let no_jitter = ExponentialBackoff {
+            jitter: 0.0,
+            ..policy
+        };
+        assert_eq!(
+            no_jitter.next_delay(&failure(SseFailureKind::Timeout, 3)),
+            Some(Duration::from_millis(400))
+        );
+    }
+
+    #[test]
+    fn invalid_exponential_backoff_test() {
+        let policy = ExponentialBackoff {
+            initial_interval: Duration::from_millis(100),
+            max_interval: Duration::from_millis(1000),
+            multiplier: -1.0,
+            jitter: 0.0,
+            max_retries: None,
+        };
+        let delays: Vec<Option<Duration>> = (1..=3)
+            .map(|attempt| policy.next_delay(&failure(SseFailureKind::Timeout, attempt)))
+            .collect();
+        assert_eq!(
+            delays,
+            vec![
+                Some(Duration::from_millis(100)),
+                Some(Duration::ZERO),
+                Some(Duration::from_millis(100))
+            ]
+        );
+        let not_a_number = ExponentialBackoff {
+            multiplier: f64::NAN,
+            jitter: f64::NAN,
+            ..policy
+        };
+        assert_eq!(
+            not_a_number.next_delay(&failure(SseFailureKind::Timeout, 2)),
+            Some(Duration::from_millis(1000))
+        );
+    }
+
+    #[test]
+    fn fixed_delay_and_no_retry_test() {
+        let policy = FixedDelay {
+            delay: Duration::from_millis(5),
+            max_retries: Some(2),
+        };
+        assert_eq!(
+            policy.next_delay(&failure(SseFailureKind::Connection, 2)),
+            Some(Duration::from_millis(5))
+        );
+        assert_eq!(
+            policy.next_delay(&failure(SseFailureKind::Connection, 3)),
+            None
+        );
+        assert_eq!(
+            NoRetry.next_delay(&failure(SseFailureKind::Connection, 1)),
+            None
+        );
+    }
+
+    #[test]
+    fn per_failure_decision_test() {
+        let policy = FixedDelay::new(Duration::from_millis(1))
+            .retry_if(|failure| !matches!(failure.status(), Some(401) | Some(403)));
+        assert_eq!(
+            policy.next_delay(&failure(SseFailureKind::Status(401), 1)),
+            None
+        );
+        assert_eq!(
+            policy.next_delay(&failure(SseFailureKind::Status(503), 1)),
+            Some(Duration::from_millis(1))
+        );
+        let closure = |failure: &SseFailure| match failure.kind {
+            SseFailureKind::Status(503) => Some(Duration::from_secs(failure.attempt)),
+            _ => None,
+        };
+        assert_eq!(
+            closure.next_delay(&failure(SseFailureKind::Status(503), 3)),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(
+            closure.next_delay(&failure(SseFailureKind::Disconnected, 1)),
+            None
+        );
+    }
+}
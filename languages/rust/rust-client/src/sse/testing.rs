@@ -0,0 +1,452 @@
+//! Local server for testing event stream clients.
+//!
+//! Every connection is answered by an `SseScript` which controls the exact bytes that are written,
+//! how long to wait between them, and how the connection ends.
+//!
+//! ```ignore
+//! let server = SseTestServer::start([
+//!     SseScript::new().id("1").message(r#"{"id":"1"}"#).disconnect(),
+//!     SseScript::error(503, "Service unavailable"),
+//!     SseScript::new().message(r#"{"id":"2"}"#).done(),
+//! ])
+//! .await;
+//! // point the client at server.url()
+//! assert_eq!(server.requests()[1].headers.get("last-event-id"), Some(&"1".to_string()));
+//! ```
+//!
+//! `LongPollTestServer` implements the server side of `SseTransport::LongPolling`.
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+#[cfg(unix)]
+use tokio::net::UnixListener;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpListener,
+    task::JoinHandle,
+    time::Instant,
+};
+
+use super::long_poll::{LONG_POLL_TRANSPORT, TRANSPORT_HEADER};
+use crate::{status_message_from_status_code, ArriError, ArriModel};
+
+#[derive(Debug, Clone)]
+enum SseStep {
+    Write(Vec<u8>),
+    Delay(Duration),
+    Disconnect,
+    Stall,
+}
+
+/// The response sent to a single connection
+#[derive(Debug, Clone)]
+pub struct SseScript {
+    status: u16,
+    headers: Vec<(String, String)>,
+    steps: Vec<SseStep>,
+}
+
+impl Default for SseScript {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SseScript {
+    /// A `200` event stream response. The connection is closed after the last step.
+    pub fn new() -> Self {
+        Self {
+            status: 200,
+            headers: vec![("content-type".to_string(), "text/event-stream".to_string())],
+            steps: Vec::new(),
+        }
+    }
+
+    /// A JSON response with the given status code and body, for mocking regular procedures
+    pub fn response(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: vec![("content-type".to_string(), "application/json".to_string())],
+            steps: vec![SseStep::Write(body.into().into_bytes())],
+        }
+    }
+
+    /// An error response with the given status code and body. Same as `response()`.
+    pub fn error(status: u16, body: impl Into<String>) -> Self {
+        Self::response(status, body)
+    }
+
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the `heartbeat-interval` header
+    pub fn heartbeat_interval(self, interval: Duration) -> Self {
+        self.header("heartbeat-interval", interval.as_millis().to_string())
+    }
+
+    /// Write raw bytes as a single chunk
+    pub fn chunk(mut self, bytes: impl Into<Vec<u8>>) -> Self {
+        self.steps.push(SseStep::Write(bytes.into()));
+        self
+    }
+
+    /// A `message` event. Multi-line data is written as multiple `data` lines.
+    pub fn message(self, data: &str) -> Self {
+        self.chunk(format!("{}\n", data_lines(data)))
+    }
+
+    /// An event with a custom event name
+    pub fn event(self, event: &str, data: &str) -> Self {
+        self.chunk(format!("event: {}\n{}\n", event, data_lines(data)))
+    }
+
+    /// An `id` line. Applies to the next event that is written.
+    pub fn id(self, id: &str) -> Self {
+        self.chunk(format!("id: {}\n", id))
+    }
+
+    /// A `retry` line telling the client how long to wait before reconnecting
+    pub fn retry(self, retry: Duration) -> Self {
+        self.chunk(format!("retry: {}\n", retry.as_millis()))
+    }
+
+    pub fn comment(self, comment: &str) -> Self {
+        self.chunk(format!(": {}\n", comment))
+    }
+
+    pub fn heartbeat(self) -> Self {
+        self.event("heartbeat", "")
+    }
+
+    /// An `error` event. The client closes the stream after it when `reconnect` is false.
+    pub fn error_event(self, error: &ArriError, reconnect: bool) -> Self {
+        let mut data = serde_json::from_str::<serde_json::Value>(&error.to_json_string())
+            .unwrap_or_else(|_| serde_json::json!({}));
+        data["reconnect"] = serde_json::Value::Bool(reconnect);
+        self.event("error", &data.to_string())
+    }
+
+    /// The `done` event which tells the client that the stream has ended
+    pub fn done(self) -> Self {
+        self.event("done", "")
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.steps.push(SseStep::Delay(delay));
+        self
+    }
+
+    /// Close the connection straight away, ignoring any remaining steps
+    pub fn disconnect(mut self) -> Self {
+        self.steps.push(SseStep::Disconnect);
+        self
+    }
+
+    /// Keep the connection open without sending anything else until the client disconnects.
+    /// Use this to simulate missing heartbeats.
+    pub fn stall(mut self) -> Self {
+        self.steps.push(SseStep::Stall);
+        self
+    }
+}
+
+fn data_lines(data: &str) -> String {
+    let mut output = String::new();
+    for line in data.split('\n') {
+        output.push_str("data: ");
+        output.push_str(line);
+        output.push('\n');
+    }
+    output
+}
+
+/// A request received by `SseTestServer`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseTestRequest {
+    pub method: String,
+    /// Includes the query string
+    pub path: String,
+    /// Header names are lowercase
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+/// Serves event streams on localhost. The server stops when it is dropped.
+pub struct SseTestServer {
+    url: String,
+    requests: Arc<Mutex<Vec<SseTestRequest>>>,
+    task: JoinHandle<()>,
+    /// Removed when the server is dropped
+    socket_path: Option<PathBuf>,
+}
+
+impl SseTestServer {
+    /// Connection `n` is answered with `scripts[n]`. Once the scripts run out
+    /// the last script is used for every following connection.
+    pub async fn start(scripts: impl IntoIterator<Item = SseScript>) -> Self {
+        let scripts: Vec<SseScript> = scripts.into_iter().collect();
+        assert!(!scripts.is_empty(), "at least one script is required");
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind test server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+        let task = tokio::spawn(async move {
+            let mut connection_count = 0;
+            while let Ok((socket, _)) = listener.accept().await {
+                let script = scripts[connection_count.min(scripts.len() - 1)].clone();
+                connection_count += 1;
+                let received = received.clone();
+                tokio::spawn(async move {
+                    if socket.set_nodelay(true).is_ok() {
+                        let _ = serve(socket, script, &received).await;
+                    }
+                });
+            }
+        });
+        Self {
+            url,
+            requests,
+            task,
+            socket_path: None,
+        }
+    }
+
+    /// Same as `start()` but listens on a Unix domain socket at `path`.
+    /// `url()` returns `http://localhost` for use with `ArriClientConfig::unix_socket()`.
+    #[cfg(unix)]
+    pub async fn start_unix(
+        path: impl Into<PathBuf>,
+        scripts: impl IntoIterator<Item = SseScript>,
+    ) -> Self {
+        let path = path.into();
+        let scripts: Vec<SseScript> = scripts.into_iter().collect();
+        assert!(!scripts.is_empty(), "at least one script is required");
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).expect("failed to bind test server");
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+        let task = tokio::spawn(async move {
+            let mut connection_count = 0;
+            while let Ok((socket, _)) = listener.accept().await {
+                let script = scripts[connection_count.min(scripts.len() - 1)].clone();
+                connection_count += 1;
+                let received = received.clone();
+                tokio::spawn(async move {
+                    let _ = serve(socket, script, &received).await;
+                });
+            }
+        });
+        Self {
+            url: "http://localhost".to_string(),
+            requests,
+            task,
+            socket_path: Some(path),
+        }
+    }
+
+    /// Base url of the server without a trailing slash
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Every request received so far, in the order that they were received
+    pub fn requests(&self) -> Vec<SseTestRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for SseTestServer {
+    fn drop(&mut self) {
+        self.task.abort();
+        if let Some(path) = &self.socket_path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Serves a stream of `message_count` messages with the long polling protocol on localhost.
+/// The server stops when it is dropped.
+///
+/// Message `i` is an `ArriError` with code `i` and id `i`. It becomes available `i * interval`
+/// after the first request and a `done` event follows the last message. Polls are held for up
+/// to `poll_timeout` and every response sets `retry` to `interval`, so clients wait that long
+/// after an empty poll. Event stream requests behave like a proxy that cuts the response before
+/// anything is sent, which makes clients using `SseTransport::Fallback` switch to long polling.
+pub struct LongPollTestServer {
+    url: String,
+    requests: Arc<Mutex<Vec<SseTestRequest>>>,
+    task: JoinHandle<()>,
+}
+
+impl LongPollTestServer {
+    pub async fn start(message_count: usize, interval: Duration, poll_timeout: Duration) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind test server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+        let started = Arc::new(tokio::sync::OnceCell::new());
+        let task = tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let received = received.clone();
+                let started = started.clone();
+                tokio::spawn(async move {
+                    let Some(request) = read_request(&mut socket).await else {
+                        return;
+                    };
+                    let started = *started.get_or_init(|| async { Instant::now() }).await;
+                    let transport = request.headers.get(TRANSPORT_HEADER).cloned();
+                    let last_event_id = request.headers.get("last-event-id").cloned();
+                    received.lock().unwrap().push(request);
+                    if transport.as_deref() != Some(LONG_POLL_TRANSPORT) {
+                        let _ = socket
+                            .write_all(
+                                b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\r\n",
+                            )
+                            .await;
+                        return;
+                    }
+                    let next = last_event_id
+                        .and_then(|id| id.parse::<usize>().ok())
+                        .map_or(0, |id| id + 1);
+                    let deadline = Instant::now() + poll_timeout;
+                    let available_at = started + interval * next as u32;
+                    tokio::time::sleep_until(available_at.min(deadline)).await;
+                    let available =
+                        (started.elapsed().as_millis() / interval.as_millis().max(1)) as usize + 1;
+                    let mut body = format!("retry: {}\n\n", interval.as_millis());
+                    for i in next..available.min(message_count) {
+                        body.push_str(&format!(
+                            "id: {i}\nevent: message\ndata: {{\"code\":{i},\"message\":\"\"}}\n\n"
+                        ));
+                    }
+                    if available >= message_count || next >= message_count {
+                        body.push_str("event: done\ndata: \n\n");
+                    }
+                    let _ = socket
+                        .write_all(
+                            format!(
+                                "HTTP/1.1 200 OK\r\ncontent-type: text/plain\r\nconnection: close\r\ncontent-length: {}\r\n\r\n{}",
+                                body.len(),
+                                body
+                            )
+                            .as_bytes(),
+                        )
+                        .await;
+                });
+            }
+        });
+        Self {
+            url,
+            requests,
+            task,
+        }
+    }
+
+    /// Base url of the server without a trailing slash
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Every request received so far, in the order that they were received
+    pub fn requests(&self) -> Vec<SseTestRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for LongPollTestServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn serve(
+    mut socket: impl AsyncRead + AsyncWrite + Unpin,
+    script: SseScript,
+    requests: &Mutex<Vec<SseTestRequest>>,
+) -> std::io::Result<()> {
+    match read_request(&mut socket).await {
+        Some(request) => requests.lock().unwrap().push(request),
+        None => return Ok(()),
+    }
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\n",
+        script.status,
+        status_message_from_status_code(script.status)
+    );
+    for (name, value) in &script.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("connection: close\r\n\r\n");
+    socket.write_all(head.as_bytes()).await?;
+    for step in script.steps {
+        match step {
+            SseStep::Write(bytes) => {
+                socket.write_all(&bytes).await?;
+                socket.flush().await?;
+            }
+            SseStep::Delay(delay) => tokio::time::sleep(delay).await,
+            SseStep::Disconnect => break,
+            SseStep::Stall => {
+                let mut buf = [0u8; 64];
+                while socket.read(&mut buf).await? > 0 {}
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn read_request(socket: &mut (impl AsyncRead + Unpin)) -> Option<SseTestRequest> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    let head_end = loop {
+        if let Some(index) = data.windows(4).position(|window| window == b"\r\n\r\n") {
+            break index;
+        }
+        match socket.read(&mut buf).await {
+            Ok(0) | Err(_) => return None,
+            Ok(len) => data.extend_from_slice(&buf[..len]),
+        }
+    };
+    let head = String::from_utf8_lossy(&data[..head_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split(' ');
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+    let content_length = headers
+        .get("content-length")
+        .and_then(|len| len.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = data[head_end + 4..].to_vec();
+    while body.len() < content_length {
+        match socket.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(len) => body.extend_from_slice(&buf[..len]),
+        }
+    }
+    Some(SseTestRequest {
+        method,
+        path,
+        headers,
+        body: String::from_utf8_lossy(&body).to_string(),
+    })
+}
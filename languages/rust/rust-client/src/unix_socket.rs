@@ -0,0 +1,148 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::{ArriClientConfig, ArriError};
+
+impl ArriClientConfig {
+    /// Config for a server that listens on a Unix domain socket instead of a TCP port.
+    ///
+    /// Every request (including event streams) is sent over the socket at `path`. The base url
+    /// is `http://localhost` so procedures are routed by their url path as usual and the host
+    /// name is never resolved. Websocket procedures are not supported over Unix sockets.
+    pub fn unix_socket(
+        path: impl AsRef<Path>,
+        headers: HashMap<&'static str, String>,
+    ) -> Result<Self, ArriError> {
+        let http_client = reqwest::Client::builder()
+            .unix_socket(path.as_ref())
+            .build()
+            .map_err(|err| ArriError {
+                code: 0,
+                message: format!("Error creating Unix socket client: {}", err),
+                stack: None,
+                data: None,
+            })?;
+        Ok(Self {
+            http_client,
+            base_url: "http://localhost".to_string(),
+            headers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod unix_socket_tests {
+    use std::{path::PathBuf, sync::Arc, time::Duration};
+
+    use super::*;
+    use crate::{
+        parsed_arri_request,
+        sse::{
+            parsed_arri_sse_request,
+            testing::{SseScript, SseTestServer},
+            ArriParsedSseRequestOptions, FixedDelay, SseEvent, SseOptions,
+        },
+        ArriModel, ArriParsedRequestOptions, InternalArriClientConfig,
+    };
+
+    fn socket_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("arri-{}-{}.sock", std::process::id(), name))
+    }
+
+    fn test_config(path: &Path) -> InternalArriClientConfig {
+        let mut headers = HashMap::new();
+        headers.insert("authorization", "Bearer 123".to_string());
+        InternalArriClientConfig::from(ArriClientConfig::unix_socket(path, headers).unwrap())
+    }
+
+    #[tokio::test]
+    async fn unary_request_test() {
+        let path = socket_path("unary");
+        let server = SseTestServer::start_unix(
+            &path,
+            [SseScript::response(200, r#"{"code":1,"message":"hello"}"#)],
+        )
+        .await;
+        let config = test_config(&path);
+        let result = parsed_arri_request(
+            ArriParsedRequestOptions {
+                http_client: &config.http_client,
+                url: format!("{}/messages/get-message", &config.base_url),
+                method: reqwest::Method::POST,
+                headers: config.headers.clone(),
+                client_version: "1".to_string(),
+            },
+            Some(ArriError {
+                code: 2,
+                message: "params".to_string(),
+                stack: None,
+                data: None,
+            }),
+            ArriError::from_json_string,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.code, 1);
+        assert_eq!(result.message, "hello");
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/messages/get-message");
+        assert_eq!(
+            requests[0].headers.get("authorization"),
+            Some(&"Bearer 123".to_string())
+        );
+        assert_eq!(requests[0].body, r#"{"code":2,"message":"params"}"#);
+    }
+
+    #[tokio::test]
+    async fn event_stream_test() {
+        let path = socket_path("event-stream");
+        let server = SseTestServer::start_unix(
+            &path,
+            [
+                SseScript::new()
+                    .id("1")
+                    .message(r#"{"code":1,"message":""}"#)
+                    .disconnect(),
+                SseScript::new()
+                    .message(r#"{"code":2,"message":""}"#)
+                    .done(),
+            ],
+        )
+        .await;
+        let config = test_config(&path);
+        let mut codes = Vec::new();
+        parsed_arri_sse_request(
+            ArriParsedSseRequestOptions {
+                client: &config.http_client,
+                url: format!("{}/messages/watch-messages", &config.base_url),
+                method: reqwest::Method::GET,
+                headers: config.headers.clone(),
+                deprecation: None,
+                version_mismatch: None,
+                client_version: "1".to_string(),
+                options: SseOptions {
+                    reconnection_policy: Some(Arc::new(FixedDelay::new(Duration::ZERO))),
+                    ..Default::default()
+                },
+            },
+            None::<ArriError>,
+            &mut |event: SseEvent<ArriError>, _| {
+                if let SseEvent::Message(message) = event {
+                    codes.push(message.code);
+                }
+            },
+        )
+        .await;
+        assert_eq!(codes, vec![1, 2]);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests
+            .iter()
+            .all(|request| request.path == "/messages/watch-messages"));
+        assert_eq!(
+            requests[1].headers.get("last-event-id"),
+            Some(&"1".to_string())
+        );
+    }
+}
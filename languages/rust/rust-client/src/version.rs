@@ -0,0 +1,185 @@
+//! Recognises servers rejecting the version sent in the `client-version` header.
+//!
+//! A server signals a version mismatch by responding with `426 Upgrade Required`,
+//! or with any error that has a `min-client-version` header or a `minClientVersion` in its data.
+//! `recommended-client-version` can be sent alongside to suggest a version to update to.
+use crate::{listener::Listener, ArriError, ArriRequestErrorMethods};
+
+pub const MIN_CLIENT_VERSION_HEADER: &str = "min-client-version";
+pub const RECOMMENDED_CLIENT_VERSION_HEADER: &str = "recommended-client-version";
+
+/// The status code used by servers to reject outdated clients
+pub const UPGRADE_REQUIRED: u16 = 426;
+
+/// The versions reported by a server that rejected the client
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VersionMismatch {
+    /// The version the client sent. None when the client doesn't send a version.
+    pub client_version: Option<String>,
+    /// The oldest client version the server accepts
+    pub min_version: Option<String>,
+    /// The version the server suggests updating to
+    pub recommended_version: Option<String>,
+}
+
+impl VersionMismatch {
+    /// Read the versions from an error. None if the error isn't a version mismatch.
+    pub fn from_error(err: &ArriError) -> Option<Self> {
+        let version = |key: &str| {
+            err.data
+                .as_ref()
+                .and_then(|data| data.get(key))
+                .and_then(|value| value.as_str())
+                .map(|value| value.to_string())
+        };
+        let min_version = version("minClientVersion");
+        if err.code != UPGRADE_REQUIRED && min_version.is_none() {
+            return None;
+        }
+        Some(Self {
+            client_version: version("clientVersion"),
+            min_version,
+            recommended_version: version("recommendedClientVersion"),
+        })
+    }
+}
+
+/// Error for a response outside of the 2xx range.
+/// Versions sent in headers are added to the error data so that `ArriError::kind()` can read them.
+pub(crate) fn error_from_response(
+    status: u16,
+    body: String,
+    client_version: &str,
+    header: impl Fn(&str) -> Option<String>,
+) -> ArriError {
+    let mut err = ArriError::from_response_data(status, body);
+    let min_version = header(MIN_CLIENT_VERSION_HEADER);
+    if status != UPGRADE_REQUIRED && min_version.is_none() {
+        return err;
+    }
+    let mut data = match err.data.take() {
+        Some(serde_json::Value::Object(data)) => data,
+        None => serde_json::Map::new(),
+        Some(data) => {
+            // keep data that we don't understand as is
+            err.data = Some(data);
+            return err;
+        }
+    };
+    let fields = [
+        ("clientVersion", Some(client_version.to_string())),
+        ("minClientVersion", min_version),
+        (
+            "recommendedClientVersion",
+            header(RECOMMENDED_CLIENT_VERSION_HEADER),
+        ),
+    ];
+    for (key, value) in fields {
+        match value {
+            Some(value) if !value.is_empty() && !data.contains_key(key) => {
+                data.insert(key.to_string(), serde_json::Value::String(value));
+            }
+            _ => {}
+        }
+    }
+    err.data = Some(serde_json::Value::Object(data));
+    err
+}
+
+/// The version mismatch handler of a client
+pub type VersionMismatchListener = Listener<VersionMismatch>;
+
+impl Listener<VersionMismatch> {
+    /// Call the handler if `err` is a version mismatch
+    pub fn notify_error(&self, err: &ArriError) {
+        if let Some(mismatch) = VersionMismatch::from_error(err) {
+            self.notify(&mismatch);
+        }
+    }
+}
+
+#[cfg(test)]
+mod version_tests {
+    use super::*;
+    use crate::ArriErrorKind;
+
+    #[test]
+    fn kind_test() {
+        let err = |code, data| ArriError {
+            code,
+            message: String::new(),
+            stack: None,
+            data,
+        };
+        assert_eq!(err(0, None).kind(), ArriErrorKind::Client);
+        assert_eq!(err(503, None).kind(), ArriErrorKind::Server);
+        assert_eq!(
+            err(426, None).kind(),
+            ArriErrorKind::VersionMismatch(VersionMismatch::default())
+        );
+        assert_eq!(
+            err(400, Some(serde_json::json!({ "minClientVersion": "2" }))).kind(),
+            ArriErrorKind::VersionMismatch(VersionMismatch {
+                min_version: Some("2".to_string()),
+                ..Default::default()
+            })
+        );
+    }
+
+    fn headers<'a>(headers: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            headers
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn error_from_response_test() {
+        let err = error_from_response(
+            426,
+            r#"{"code":426,"message":"Please update","data":{"minClientVersion":"3"}}"#.to_string(),
+            "1",
+            headers(&[
+                ("min-client-version", "2"),
+                ("recommended-client-version", "4"),
+            ]),
+        );
+        assert_eq!(err.message, "Please update");
+        assert_eq!(
+            VersionMismatch::from_error(&err),
+            Some(VersionMismatch {
+                client_version: Some("1".to_string()),
+                // the body takes precedence over the headers
+                min_version: Some("3".to_string()),
+                recommended_version: Some("4".to_string()),
+            })
+        );
+
+        let err = error_from_response(
+            400,
+            String::new(),
+            "",
+            headers(&[("min-client-version", "2")]),
+        );
+        assert_eq!(err.message, "Bad Request");
+        assert_eq!(
+            VersionMismatch::from_error(&err),
+            Some(VersionMismatch {
+                client_version: None,
+                min_version: Some("2".to_string()),
+                recommended_version: None,
+            })
+        );
+
+        let err = error_from_response(
+            400,
+            String::new(),
+            "1",
+            headers(&[("recommended-client-version", "4")]),
+        );
+        assert_eq!(err.data, None);
+        assert_eq!(VersionMismatch::from_error(&err), None);
+    }
+}
@@ -0,0 +1,781 @@
+use std::{
+    collections::HashMap,
+    marker::PhantomData,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use futures_util::{Sink, SinkExt, Stream, StreamExt};
+use tokio_tungstenite::tungstenite::{
+    self,
+    client::IntoClientRequest,
+    http::{HeaderName, HeaderValue},
+    protocol::frame::coding::CloseCode,
+    Message,
+};
+
+mod session;
+pub use session::{WsCloseReason, WsEvent, WsReceiver, WsSender, WsSession};
+
+use crate::{
+    deprecation::DeprecationReporter,
+    loopback,
+    sse::{DefaultReconnectionPolicy, ReconnectionPolicy, SseFailure, SseFailureKind},
+    version::{self, VersionMismatchListener},
+    ArriError, ArriErrorKind, ArriModel,
+};
+
+pub struct ArriParsedWsRequestOptions {
+    pub url: String,
+    pub headers: Arc<RwLock<HashMap<&'static str, String>>>,
+    pub client_version: String,
+    /// Reports `deprecation` and `sunset` headers of every handshake response
+    pub deprecation: Option<DeprecationReporter>,
+    /// Called when a handshake is rejected because of the client version
+    pub version_mismatch: Option<VersionMismatchListener>,
+    pub options: WsOptions,
+}
+
+/// Options that can be passed to websocket procedures
+#[derive(Clone, Default)]
+pub struct WsOptions {
+    // Defaults to None
+    pub max_retry_count: Option<u64>,
+    // Max delay time in ms. defaults to Some(30000).
+    pub max_retry_interval: Option<u64>,
+    /// Decides if and when to reconnect after the connection fails or drops.
+    /// When set `max_retry_count` and `max_retry_interval` are ignored.
+    pub reconnection_policy: Option<Arc<dyn ReconnectionPolicy>>,
+    /// Send a ping this often while a `WsSession` is running. The connection is treated as
+    /// dropped when nothing was received from the server between two pings.
+    /// Defaults to None, meaning no pings are sent.
+    pub ping_interval: Option<Duration>,
+}
+
+/// An open websocket, whatever it runs over. Mocks use sockets over in-memory streams.
+pub(crate) trait WsTransport:
+    Stream<Item = Result<Message, tungstenite::Error>>
+    + Sink<Message, Error = tungstenite::Error>
+    + Send
+    + Unpin
+{
+}
+
+impl<S> WsTransport for S where
+    S: Stream<Item = Result<Message, tungstenite::Error>>
+        + Sink<Message, Error = tungstenite::Error>
+        + Send
+        + Unpin
+{
+}
+
+type WsStream = Box<dyn WsTransport>;
+
+/// Open a websocket connection to a procedure.
+///
+/// The client headers and `client-version` are sent with the handshake request.
+/// `http` and `https` urls are converted to `ws` and `wss`. Failed connection attempts
+/// are retried according to the reconnection policy and the last error is returned
+/// once it gives up. Handshakes that the server rejects with a 4xx status fail straight away.
+pub async fn arri_ws_request<P, T>(
+    options: ArriParsedWsRequestOptions,
+) -> Result<WsConnection<P, T>, ArriError>
+where
+    P: ArriModel,
+    T: ArriModel,
+{
+    if loopback::is_loopback_url(&options.url) {
+        return Err(ArriError {
+            code: 0,
+            message: "Websocket procedures can't be called through a loopback".to_string(),
+            stack: None,
+            data: None,
+        });
+    }
+    let mut connection = WsConnection::new(options);
+    match open_socket(&connection.options).await {
+        Ok(socket) => connection.socket = Some(socket),
+        Err((kind, err)) => connection.recover(kind, err).await?,
+    }
+    Ok(connection)
+}
+
+/// An open websocket connection that sends `P` and receives `T`.
+///
+/// Dropped connections are reopened transparently the next time `send()` or `receive()` is
+/// called. Messages that were in flight when the connection dropped are lost.
+pub struct WsConnection<P, T> {
+    options: ArriParsedWsRequestOptions,
+    policy: Arc<dyn ReconnectionPolicy>,
+    /// `None` once the connection has been closed for good
+    socket: Option<WsStream>,
+    retry_count: u64,
+    _types: PhantomData<fn(P) -> T>,
+}
+
+impl<P, T> WsConnection<P, T>
+where
+    P: ArriModel,
+    T: ArriModel,
+{
+    fn new(options: ArriParsedWsRequestOptions) -> Self {
+        let policy = match options.options.reconnection_policy.clone() {
+            Some(policy) => policy,
+            None => Arc::new(DefaultReconnectionPolicy {
+                max_retry_count: options.options.max_retry_count,
+                max_retry_interval: options.options.max_retry_interval.unwrap_or(30000),
+            }),
+        };
+        Self {
+            options,
+            policy,
+            socket: None,
+            retry_count: 0,
+            _types: PhantomData,
+        }
+    }
+
+    /// A connection over a socket that has already been opened. Reconnects still go to `options.url`.
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn from_socket(
+        options: ArriParsedWsRequestOptions,
+        socket: impl WsTransport + 'static,
+    ) -> Self {
+        let mut connection = Self::new(options);
+        connection.socket = Some(Box::new(socket));
+        connection
+    }
+
+    /// Send a message to the server
+    pub async fn send(&mut self, message: &P) -> Result<(), ArriError> {
+        let text = message.to_json_string();
+        loop {
+            let socket = self.socket.as_mut().ok_or_else(closed_error)?;
+            match socket.send(Message::text(text.clone())).await {
+                Ok(_) => return Ok(()),
+                Err(err) => {
+                    self.socket = None;
+                    self.recover(SseFailureKind::Disconnected, ws_error(&err))
+                        .await?;
+                }
+            }
+        }
+    }
+
+    /// Wait for the next message from the server.
+    ///
+    /// `error` events sent by the server are returned as `Err`. Returns `None` once the
+    /// connection has been closed, either by `close()`, by the server sending a normal close
+    /// frame, or after the reconnection policy has given up (in which case the last error
+    /// is returned first).
+    pub async fn receive(&mut self) -> Option<Result<T, ArriError>> {
+        loop {
+            let socket = self.socket.as_mut()?;
+            let (kind, error) = match socket.next().await {
+                Some(Ok(Message::Text(text))) => {
+                    let message = WsMessage::parse(text.as_str());
+                    match message.event.as_str() {
+                        "message" => return Some(Ok(T::from_json_string(message.data))),
+                        "error" => return Some(Err(ArriError::from_json_string(message.data))),
+                        _ => continue,
+                    }
+                }
+                Some(Ok(Message::Close(frame))) => {
+                    let code = frame.as_ref().map_or(CloseCode::Status, |frame| frame.code);
+                    if matches!(code, CloseCode::Normal | CloseCode::Away) {
+                        self.socket = None;
+                        return None;
+                    }
+                    let reason = frame.map(|frame| frame.reason.to_string());
+                    (SseFailureKind::Disconnected, close_error(code, reason))
+                }
+                Some(Ok(_)) => continue,
+                Some(Err(err)) => (SseFailureKind::Disconnected, ws_error(&err)),
+                None => (
+                    SseFailureKind::Disconnected,
+                    close_error(CloseCode::Abnormal, None),
+                ),
+            };
+            self.socket = None;
+            if let Err(err) = self.recover(kind, error).await {
+                return Some(Err(err));
+            }
+        }
+    }
+
+    /// Close the connection. Subsequent calls to `send()` will fail and `receive()` will return `None`.
+    pub async fn close(&mut self) {
+        if let Some(mut socket) = self.socket.take() {
+            let _ = socket.send(Message::Close(None)).await;
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.socket.is_none()
+    }
+
+    /// Ask the reconnection policy what to do after a failure and keep reconnecting until
+    /// a connection is opened or the policy gives up
+    async fn recover(
+        &mut self,
+        mut kind: SseFailureKind,
+        mut error: ArriError,
+    ) -> Result<(), ArriError> {
+        loop {
+            if is_rejected(kind, &error) {
+                return Err(error);
+            }
+            self.retry_count += 1;
+            let failure = SseFailure {
+                kind,
+                attempt: self.retry_count,
+                server_retry: None,
+            };
+            let delay = match self.policy.next_delay(&failure) {
+                Some(delay) => delay,
+                None => return Err(error),
+            };
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+            match open_socket(&self.options).await {
+                Ok(socket) => {
+                    self.socket = Some(socket);
+                    self.retry_count = 0;
+                    return Ok(());
+                }
+                Err((next_kind, next_error)) => {
+                    kind = next_kind;
+                    error = next_error;
+                }
+            }
+        }
+    }
+}
+
+async fn open_socket(
+    options: &ArriParsedWsRequestOptions,
+) -> Result<WsStream, (SseFailureKind, ArriError)> {
+    let mut request = ws_url(&options.url)
+        .into_client_request()
+        .map_err(|err| (SseFailureKind::Connection, ws_error(&err)))?;
+    {
+        let headers = request.headers_mut();
+        for (key, value) in options.headers.read().unwrap().iter() {
+            if let (Ok(key), Ok(value)) = (
+                HeaderName::from_bytes(key.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.insert(key, value);
+            }
+        }
+        if !options.client_version.is_empty() {
+            if let Ok(value) = HeaderValue::from_str(&options.client_version) {
+                headers.insert("client-version", value);
+            }
+        }
+    }
+    let result = tokio_tungstenite::connect_async(request).await;
+    if let (Some(deprecation), Ok((_, response))) = (&options.deprecation, &result) {
+        deprecation.report_headers(response.headers());
+    }
+    match result {
+        Ok((socket, _)) => Ok(Box::new(socket)),
+        Err(tungstenite::Error::Http(response)) => {
+            if let Some(deprecation) = &options.deprecation {
+                deprecation.report_headers(response.headers());
+            }
+            let status = response.status().as_u16();
+            let body = response
+                .body()
+                .as_ref()
+                .map(|body| String::from_utf8_lossy(body).to_string())
+                .unwrap_or_default();
+            let error =
+                version::error_from_response(status, body, &options.client_version, |name| {
+                    response
+                        .headers()
+                        .get(name)
+                        .and_then(|value| value.to_str().ok())
+                        .map(|value| value.to_string())
+                });
+            if let Some(listener) = &options.version_mismatch {
+                listener.notify_error(&error);
+            }
+            Err((SseFailureKind::Status(status), error))
+        }
+        Err(err) => Err((SseFailureKind::Connection, ws_error(&err))),
+    }
+}
+
+/// Handshakes rejected by the server won't succeed when retried, apart from timeouts and rate limits.
+/// The same goes for version mismatches whatever their status.
+fn is_rejected(kind: SseFailureKind, error: &ArriError) -> bool {
+    if matches!(error.kind(), ArriErrorKind::VersionMismatch(_)) {
+        return true;
+    }
+    match kind {
+        SseFailureKind::Status(408 | 429) => false,
+        SseFailureKind::Status(status) => (400..500).contains(&status),
+        _ => false,
+    }
+}
+
+fn ws_url(url: &str) -> String {
+    if let Some(rest) = url.strip_prefix("http://") {
+        return format!("ws://{}", rest);
+    }
+    if let Some(rest) = url.strip_prefix("https://") {
+        return format!("wss://{}", rest);
+    }
+    url.to_string()
+}
+
+fn ws_error(err: &tungstenite::Error) -> ArriError {
+    ArriError {
+        code: 0,
+        message: err.to_string(),
+        stack: None,
+        data: Some(serde_json::json!({ "kind": "websocket" })),
+    }
+}
+
+fn close_error(code: CloseCode, reason: Option<String>) -> ArriError {
+    let mut message = format!("Connection closed with code {}", u16::from(code));
+    if let Some(reason) = reason.filter(|reason| !reason.is_empty()) {
+        message.push_str(format!(": {}", reason).as_str());
+    }
+    ArriError {
+        code: 0,
+        message,
+        stack: None,
+        data: Some(serde_json::json!({ "kind": "close", "closeCode": u16::from(code) })),
+    }
+}
+
+fn closed_error() -> ArriError {
+    ArriError {
+        code: 0,
+        message: "Connection is closed".to_string(),
+        stack: None,
+        data: Some(serde_json::json!({ "kind": "close" })),
+    }
+}
+
+/// A text frame sent by the server. Frames use the same `event:` and `data:` lines as
+/// server sent events.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WsMessage {
+    pub event: String,
+    pub data: String,
+}
+
+impl WsMessage {
+    fn parse(input: &str) -> Self {
+        let mut event = "message".to_string();
+        let mut data: Vec<&str> = Vec::new();
+        for line in input.lines() {
+            if let Some(value) = line.strip_prefix("event:") {
+                event = value.trim().to_string();
+            } else if let Some(value) = line.strip_prefix("data:") {
+                data.push(value.strip_prefix(' ').unwrap_or(value));
+            }
+        }
+        Self {
+            event,
+            data: data.join("\n"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod ws_tests {
+    use std::sync::Mutex;
+
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::{
+        handshake::server::{ErrorResponse, Request, Response},
+        http::StatusCode,
+        protocol::CloseFrame,
+    };
+
+    use super::*;
+    use crate::{
+        deprecation::{DeprecationListener, DeprecationNotice, DeprecationSource},
+        sse::{FixedDelay, NoRetry},
+    };
+
+    /// `(path, headers)` of every handshake
+    type HandshakeLog = Arc<Mutex<Vec<(String, HashMap<String, String>)>>>;
+
+    /// Echo server used by the tests.
+    ///
+    /// Every connection starts with a message whose code is the connection index. Client
+    /// messages are echoed back except for these codes:
+    /// - `1` drops the connection without a close frame
+    /// - `2` sends a normal close frame
+    /// - `3` is sent back as a `typing` event
+    /// - `4` stops reading from the connection without closing it
+    /// - `400..` are sent back as an `error` event
+    ///
+    /// Handshakes to `/unauthorized` are rejected with a 401 and handshakes to `/unavailable`
+    /// with a 503. Only the first handshake to `/reject-reconnect` is accepted, later ones are
+    /// rejected with a 401. Handshakes to `/deprecated` are answered with a `sunset` header.
+    pub(super) struct EchoServer {
+        pub(super) url: String,
+        handshakes: HandshakeLog,
+        close_codes: Arc<Mutex<Vec<u16>>>,
+    }
+
+    impl EchoServer {
+        pub(super) async fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let handshakes: HandshakeLog = Arc::new(Mutex::new(Vec::new()));
+            let received = handshakes.clone();
+            let close_codes = Arc::new(Mutex::new(Vec::new()));
+            let received_close_codes = close_codes.clone();
+            tokio::spawn(async move {
+                let mut connection_count = 0u16;
+                while let Ok((stream, _)) = listener.accept().await {
+                    let index = connection_count;
+                    connection_count += 1;
+                    let received = received.clone();
+                    let received_close_codes = received_close_codes.clone();
+                    tokio::spawn(async move {
+                        // the callback signature is defined by tungstenite
+                        #[allow(clippy::result_large_err)]
+                        let callback = |request: &Request, mut response: Response| {
+                            let headers = request
+                                .headers()
+                                .iter()
+                                .map(|(key, value)| {
+                                    (key.to_string(), value.to_str().unwrap().to_string())
+                                })
+                                .collect();
+                            let path = request.uri().path().to_string();
+                            received.lock().unwrap().push((path.clone(), headers));
+                            // only the first connection to this path is accepted
+                            let rejected = path == "/unauthorized"
+                                || (path == "/reject-reconnect" && index > 0);
+                            if rejected {
+                                let mut error = ErrorResponse::new(Some(
+                                    r#"{"code":401,"message":"Unauthorized"}"#.to_string(),
+                                ));
+                                *error.status_mut() = StatusCode::UNAUTHORIZED;
+                                return Err(error);
+                            }
+                            if path == "/deprecated" {
+                                response.headers_mut().insert(
+                                    "sunset",
+                                    "Thu, 01 Jan 2026 00:00:00 GMT".parse().unwrap(),
+                                );
+                            }
+                            if path == "/unavailable" {
+                                let mut error = ErrorResponse::new(None);
+                                *error.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+                                return Err(error);
+                            }
+                            Ok(response)
+                        };
+                        let Ok(mut socket) =
+                            tokio_tungstenite::accept_hdr_async(stream, callback).await
+                        else {
+                            return;
+                        };
+                        let welcome = ArriError {
+                            code: index,
+                            message: "welcome".to_string(),
+                            stack: None,
+                            data: None,
+                        };
+                        let _ = socket.send(message_frame("message", &welcome)).await;
+                        while let Some(Ok(frame)) = socket.next().await {
+                            let text = match frame {
+                                Message::Text(text) => text,
+                                Message::Close(frame) => {
+                                    received_close_codes
+                                        .lock()
+                                        .unwrap()
+                                        .push(frame.map_or(0, |frame| u16::from(frame.code)));
+                                    continue;
+                                }
+                                _ => continue,
+                            };
+                            let message = ArriError::from_json_string(text.to_string());
+                            match message.code {
+                                1 => return,
+                                2 => {
+                                    let _ = socket
+                                        .close(Some(CloseFrame {
+                                            code: CloseCode::Normal,
+                                            reason: "".into(),
+                                        }))
+                                        .await;
+                                    return;
+                                }
+                                3 => {
+                                    let _ = socket.send(message_frame("typing", &message)).await;
+                                }
+                                4 => {
+                                    // keep the connection open without answering pings
+                                    tokio::time::sleep(Duration::from_secs(60)).await;
+                                    return;
+                                }
+                                400.. => {
+                                    let _ = socket.send(message_frame("error", &message)).await;
+                                }
+                                _ => {
+                                    let _ = socket.send(message_frame("message", &message)).await;
+                                }
+                            }
+                        }
+                    });
+                }
+            });
+            Self {
+                url,
+                handshakes,
+                close_codes,
+            }
+        }
+
+        pub(super) fn handshakes(&self) -> Vec<(String, HashMap<String, String>)> {
+            self.handshakes.lock().unwrap().clone()
+        }
+
+        /// Codes of the close frames sent by clients
+        pub(super) fn close_codes(&self) -> Vec<u16> {
+            self.close_codes.lock().unwrap().clone()
+        }
+    }
+
+    fn message_frame(event: &str, data: &ArriError) -> Message {
+        Message::text(format!("event: {}\ndata: {}", event, data.to_json_string()))
+    }
+
+    pub(super) fn test_message(code: u16) -> ArriError {
+        ArriError {
+            code,
+            message: format!("message {}", code),
+            stack: None,
+            data: None,
+        }
+    }
+
+    pub(super) async fn connect(
+        url: String,
+        headers: HashMap<&'static str, String>,
+        options: WsOptions,
+    ) -> Result<WsConnection<ArriError, ArriError>, ArriError> {
+        arri_ws_request(ArriParsedWsRequestOptions {
+            url,
+            headers: Arc::new(RwLock::new(headers)),
+            deprecation: None,
+            version_mismatch: None,
+            client_version: "1.2.3".to_string(),
+            options,
+        })
+        .await
+    }
+
+    async fn receive(connection: &mut WsConnection<ArriError, ArriError>) -> Result<u16, u16> {
+        tokio::time::timeout(Duration::from_secs(5), connection.receive())
+            .await
+            .expect("timed out waiting for a message")
+            .expect("connection closed")
+            .map(|message| message.code)
+            .map_err(|err| err.code)
+    }
+
+    #[tokio::test]
+    async fn send_and_receive_test() {
+        let server = EchoServer::start().await;
+        let mut connection = connect(server.url.clone(), HashMap::new(), WsOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(receive(&mut connection).await, Ok(0));
+        for code in [10, 11, 12] {
+            connection.send(&test_message(code)).await.unwrap();
+        }
+        assert_eq!(receive(&mut connection).await, Ok(10));
+        assert_eq!(receive(&mut connection).await, Ok(11));
+        assert_eq!(receive(&mut connection).await, Ok(12));
+        connection.close().await;
+        assert!(connection.is_closed());
+        assert!(connection.receive().await.is_none());
+        assert!(connection.send(&test_message(13)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn handshake_headers_test() {
+        let server = EchoServer::start().await;
+        let mut headers = HashMap::new();
+        headers.insert("authorization", "Bearer 123".to_string());
+        let _connection = connect(
+            format!("{}/books/create-connection", server.url),
+            headers,
+            WsOptions::default(),
+        )
+        .await
+        .unwrap();
+        let handshakes = server.handshakes();
+        assert_eq!(handshakes.len(), 1);
+        let (path, headers) = &handshakes[0];
+        assert_eq!(path, "/books/create-connection");
+        assert_eq!(
+            headers.get("authorization"),
+            Some(&"Bearer 123".to_string())
+        );
+        assert_eq!(headers.get("client-version"), Some(&"1.2.3".to_string()));
+    }
+
+    #[tokio::test]
+    async fn deprecation_headers_test() {
+        let server = EchoServer::start().await;
+        let listener = DeprecationListener::default();
+        let notices = Arc::new(Mutex::new(Vec::new()));
+        let received = notices.clone();
+        listener.set(move |notice| received.lock().unwrap().push(notice.clone()));
+        let _connection = arri_ws_request::<ArriError, ArriError>(ArriParsedWsRequestOptions {
+            url: format!("{}/deprecated", server.url),
+            headers: Arc::new(RwLock::new(HashMap::new())),
+            client_version: String::new(),
+            deprecation: Some(listener.reporter("/deprecated")),
+            version_mismatch: None,
+            options: WsOptions::default(),
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            *notices.lock().unwrap(),
+            vec![DeprecationNotice {
+                path: "/deprecated".to_string(),
+                source: DeprecationSource::Server,
+                deprecation: None,
+                sunset: Some("Thu, 01 Jan 2026 00:00:00 GMT".to_string()),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn error_event_test() {
+        let server = EchoServer::start().await;
+        let mut connection = connect(server.url.clone(), HashMap::new(), WsOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(receive(&mut connection).await, Ok(0));
+        connection.send(&test_message(403)).await.unwrap();
+        connection.send(&test_message(20)).await.unwrap();
+        assert_eq!(receive(&mut connection).await, Err(403));
+        // error events don't close the connection
+        assert_eq!(receive(&mut connection).await, Ok(20));
+    }
+
+    #[tokio::test]
+    async fn reconnect_test() {
+        let server = EchoServer::start().await;
+        let mut connection = connect(
+            server.url.clone(),
+            HashMap::new(),
+            WsOptions {
+                reconnection_policy: Some(Arc::new(FixedDelay::new(Duration::ZERO))),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(receive(&mut connection).await, Ok(0));
+        connection.send(&test_message(1)).await.unwrap();
+        // the welcome message of the second connection
+        assert_eq!(receive(&mut connection).await, Ok(1));
+        connection.send(&test_message(30)).await.unwrap();
+        assert_eq!(receive(&mut connection).await, Ok(30));
+        assert_eq!(server.handshakes().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn server_close_test() {
+        let server = EchoServer::start().await;
+        let mut connection = connect(server.url.clone(), HashMap::new(), WsOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(receive(&mut connection).await, Ok(0));
+        connection.send(&test_message(2)).await.unwrap();
+        assert!(connection.receive().await.is_none());
+        assert!(connection.is_closed());
+        assert_eq!(server.handshakes().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn rejected_handshake_test() {
+        let server = EchoServer::start().await;
+        let result = connect(
+            format!("{}/unauthorized", server.url),
+            HashMap::new(),
+            WsOptions {
+                reconnection_policy: Some(Arc::new(NoRetry)),
+                ..Default::default()
+            },
+        )
+        .await;
+        let err = result.err().unwrap();
+        assert_eq!(err.code, 401);
+        assert_eq!(err.message, "Unauthorized");
+        assert_eq!(server.handshakes().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn rejected_handshake_fails_fast_test() {
+        let server = EchoServer::start().await;
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            connect(
+                format!("{}/unauthorized", server.url),
+                HashMap::new(),
+                WsOptions::default(),
+            ),
+        )
+        .await
+        .expect("rejected handshakes shouldn't be retried");
+        assert_eq!(result.err().unwrap().code, 401);
+        assert_eq!(server.handshakes().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_retries_test() {
+        let server = EchoServer::start().await;
+        let result = connect(
+            format!("{}/unavailable", server.url),
+            HashMap::new(),
+            WsOptions {
+                max_retry_count: Some(2),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(result.err().unwrap().code, 503);
+        assert_eq!(server.handshakes().len(), 3);
+    }
+
+    #[test]
+    fn parse_message_test() {
+        assert_eq!(
+            WsMessage::parse("event: message\ndata: {\"id\":1}"),
+            WsMessage {
+                event: "message".to_string(),
+                data: "{\"id\":1}".to_string()
+            }
+        );
+        assert_eq!(
+            WsMessage::parse("event: error\ndata:{\"code\":500}"),
+            WsMessage {
+                event: "error".to_string(),
+                data: "{\"code\":500}".to_string()
+            }
+        );
+        assert_eq!(ws_url("http://localhost:2020/a"), "ws://localhost:2020/a");
+        assert_eq!(ws_url("https://example.com"), "wss://example.com");
+        assert_eq!(ws_url("ws://example.com"), "ws://example.com");
+    }
+}
@@ -0,0 +1,609 @@
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures_util::{SinkExt, Stream, StreamExt};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::{Instant, Interval},
+};
+use tokio_tungstenite::tungstenite::{
+    protocol::{frame::coding::CloseCode, CloseFrame},
+    Message,
+};
+
+use super::{
+    close_error, closed_error, is_rejected, open_socket, ws_error, WsConnection, WsMessage,
+    WsStream,
+};
+use crate::{
+    sse::{SseFailure, SseFailureKind},
+    ArriError, ArriModel,
+};
+
+/// Number of events that can be queued before the session stops reading from the socket
+const EVENT_CAPACITY: usize = 64;
+/// How long to wait for the server to answer a close frame
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone)]
+pub enum WsEvent<T> {
+    Message(T),
+    /// Any event with an `event` name other than `message` or `error`
+    Custom(WsMessage),
+    /// Connection errors, missed pongs and `error` events sent by the server
+    Error(ArriError),
+    Open,
+    /// Emitted before waiting to reconnect after a failure
+    Reconnecting {
+        /// Number of consecutive failed attempts so far
+        attempt: u64,
+        /// How long until the next connection attempt
+        delay: Duration,
+    },
+    /// Emitted once when the session has stopped for good
+    Close(WsCloseReason),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WsCloseReason {
+    /// `WsSender::close()` was called
+    Closed,
+    /// The server sent a normal close frame
+    ServerClosed,
+    /// The reconnection policy decided not to reconnect after this failure
+    /// (for example because `max_retry_count` was reached)
+    GaveUp(SseFailure),
+}
+
+enum WsCommand {
+    Send(String, oneshot::Sender<()>),
+    Close,
+}
+
+/// A message waiting for the connection to be reopened
+type PendingMessage = (String, oneshot::Sender<()>);
+
+enum PumpResult {
+    /// `None` when the receiver was dropped
+    Closed(Option<WsCloseReason>),
+    Dropped(SseFailureKind, ArriError),
+}
+
+impl<P, T> WsConnection<P, T>
+where
+    P: ArriModel + Send + 'static,
+    T: ArriModel + Send + 'static,
+{
+    /// Run the connection in the background so that messages can be sent and received at the
+    /// same time. Dropped connections are reopened according to the reconnection policy and
+    /// messages sent while reconnecting are delivered once the connection is open again.
+    pub fn into_session(self) -> WsSession<P, T> {
+        let (command_sender, commands) = mpsc::unbounded_channel();
+        let (event_sender, events) = mpsc::channel(EVENT_CAPACITY);
+        tokio::spawn(self.run_session(commands, event_sender));
+        WsSession {
+            sender: WsSender {
+                commands: command_sender,
+                _types: PhantomData,
+            },
+            receiver: WsReceiver { events },
+        }
+    }
+
+    async fn run_session(
+        mut self,
+        mut commands: mpsc::UnboundedReceiver<WsCommand>,
+        events: mpsc::Sender<WsEvent<T>>,
+    ) {
+        if let Some(reason) = self.drive_session(&mut commands, &events).await {
+            let _ = events.send(WsEvent::Close(reason)).await;
+        }
+        self.socket = None;
+    }
+
+    /// Returns `None` when the receiver has been dropped
+    async fn drive_session(
+        &mut self,
+        commands: &mut mpsc::UnboundedReceiver<WsCommand>,
+        events: &mpsc::Sender<WsEvent<T>>,
+    ) -> Option<WsCloseReason> {
+        if self.socket.is_none() {
+            return Some(WsCloseReason::Closed);
+        }
+        let mut pending: VecDeque<PendingMessage> = VecDeque::new();
+        loop {
+            events.send(WsEvent::Open).await.ok()?;
+            let (mut kind, error) = match self.pump(commands, &mut pending, events).await {
+                PumpResult::Closed(reason) => return reason,
+                PumpResult::Dropped(kind, error) => (kind, error),
+            };
+            self.socket = None;
+            events.send(WsEvent::Error(error)).await.ok()?;
+            loop {
+                self.retry_count += 1;
+                let failure = SseFailure {
+                    kind,
+                    attempt: self.retry_count,
+                    server_retry: None,
+                };
+                let delay = match self.policy.next_delay(&failure) {
+                    Some(delay) => delay,
+                    None => return Some(WsCloseReason::GaveUp(failure)),
+                };
+                events
+                    .send(WsEvent::Reconnecting {
+                        attempt: self.retry_count,
+                        delay,
+                    })
+                    .await
+                    .ok()?;
+                let wake_at = Instant::now() + delay;
+                loop {
+                    tokio::select! {
+                        _ = tokio::time::sleep_until(wake_at) => break,
+                        command = commands.recv() => match command {
+                            Some(WsCommand::Send(text, sent)) => pending.push_back((text, sent)),
+                            Some(WsCommand::Close) => return Some(WsCloseReason::Closed),
+                            // there is nothing left to send but events are still delivered
+                            None => {
+                                tokio::time::sleep_until(wake_at).await;
+                                break;
+                            }
+                        },
+                        _ = events.closed() => return None,
+                    }
+                }
+                match open_socket(&self.options).await {
+                    Ok(socket) => {
+                        self.socket = Some(socket);
+                        self.retry_count = 0;
+                        break;
+                    }
+                    Err((next_kind, next_error)) => {
+                        kind = next_kind;
+                        let rejected = is_rejected(kind, &next_error);
+                        events.send(WsEvent::Error(next_error)).await.ok()?;
+                        // the server won't accept this client so the policy isn't asked
+                        if rejected {
+                            return Some(WsCloseReason::GaveUp(SseFailure {
+                                kind,
+                                attempt: self.retry_count,
+                                server_retry: None,
+                            }));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Move messages between the socket and the session until the connection drops or closes
+    async fn pump(
+        &mut self,
+        commands: &mut mpsc::UnboundedReceiver<WsCommand>,
+        pending: &mut VecDeque<PendingMessage>,
+        events: &mpsc::Sender<WsEvent<T>>,
+    ) -> PumpResult {
+        let ping_interval = self.options.options.ping_interval;
+        let Some(socket) = self.socket.as_mut() else {
+            return PumpResult::Closed(Some(WsCloseReason::Closed));
+        };
+        while let Some((text, sent)) = pending.pop_front() {
+            if let Err(err) = socket.send(Message::text(text.clone())).await {
+                pending.push_front((text, sent));
+                return PumpResult::Dropped(SseFailureKind::Disconnected, ws_error(&err));
+            }
+            let _ = sent.send(());
+        }
+        let mut keepalive = ping_interval
+            .map(|interval| tokio::time::interval_at(Instant::now() + interval, interval));
+        let mut received_since_ping = true;
+        let mut commands_open = true;
+        loop {
+            tokio::select! {
+                frame = socket.next() => {
+                    received_since_ping = true;
+                    let event = match frame {
+                        Some(Ok(Message::Text(text))) => {
+                            let message = WsMessage::parse(text.as_str());
+                            match message.event.as_str() {
+                                "message" => WsEvent::Message(T::from_json_string(message.data)),
+                                "error" => WsEvent::Error(ArriError::from_json_string(message.data)),
+                                _ => WsEvent::Custom(message),
+                            }
+                        }
+                        Some(Ok(Message::Close(frame))) => {
+                            let code = frame.as_ref().map_or(CloseCode::Status, |frame| frame.code);
+                            if matches!(code, CloseCode::Normal | CloseCode::Away) {
+                                return PumpResult::Closed(Some(WsCloseReason::ServerClosed));
+                            }
+                            let reason = frame.map(|frame| frame.reason.to_string());
+                            return PumpResult::Dropped(
+                                SseFailureKind::Disconnected,
+                                close_error(code, reason),
+                            );
+                        }
+                        Some(Ok(_)) => continue,
+                        Some(Err(err)) => {
+                            return PumpResult::Dropped(SseFailureKind::Disconnected, ws_error(&err))
+                        }
+                        None => {
+                            return PumpResult::Dropped(
+                                SseFailureKind::Disconnected,
+                                close_error(CloseCode::Abnormal, None),
+                            )
+                        }
+                    };
+                    if events.send(event).await.is_err() {
+                        close_gracefully(socket).await;
+                        return PumpResult::Closed(None);
+                    }
+                }
+                command = commands.recv(), if commands_open => match command {
+                    Some(WsCommand::Send(text, sent)) => {
+                        if let Err(err) = socket.send(Message::text(text.clone())).await {
+                            pending.push_back((text, sent));
+                            return PumpResult::Dropped(SseFailureKind::Disconnected, ws_error(&err));
+                        }
+                        let _ = sent.send(());
+                    }
+                    Some(WsCommand::Close) => {
+                        close_gracefully(socket).await;
+                        return PumpResult::Closed(Some(WsCloseReason::Closed));
+                    }
+                    None => commands_open = false,
+                },
+                _ = tick(&mut keepalive) => {
+                    if !received_since_ping {
+                        return PumpResult::Dropped(
+                            SseFailureKind::Timeout,
+                            keepalive_error(ping_interval.unwrap_or_default()),
+                        );
+                    }
+                    received_since_ping = false;
+                    if let Err(err) = socket.send(Message::Ping(Default::default())).await {
+                        return PumpResult::Dropped(SseFailureKind::Disconnected, ws_error(&err));
+                    }
+                }
+                _ = events.closed() => {
+                    close_gracefully(socket).await;
+                    return PumpResult::Closed(None);
+                }
+            }
+        }
+    }
+}
+
+async fn tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Send a close frame and wait for the server to answer it. Messages that arrive in the
+/// meantime are discarded.
+async fn close_gracefully(socket: &mut WsStream) {
+    let frame = CloseFrame {
+        code: CloseCode::Normal,
+        reason: "".into(),
+    };
+    if socket.send(Message::Close(Some(frame))).await.is_err() {
+        return;
+    }
+    let _ = tokio::time::timeout(CLOSE_TIMEOUT, async {
+        while let Some(Ok(_)) = socket.next().await {}
+    })
+    .await;
+}
+
+fn keepalive_error(ping_interval: Duration) -> ArriError {
+    ArriError {
+        code: 0,
+        message: format!(
+            "Nothing was received from the server within {}ms of sending a ping",
+            ping_interval.as_millis()
+        ),
+        stack: None,
+        data: Some(serde_json::json!({ "kind": "keepalive" })),
+    }
+}
+
+/// A websocket connection that sends `P` and receives `WsEvent<T>` at the same time.
+/// Use `split()` to move the sender and the receiver to different tasks.
+pub struct WsSession<P, T> {
+    sender: WsSender<P>,
+    receiver: WsReceiver<T>,
+}
+
+impl<P: ArriModel, T> WsSession<P, T> {
+    pub fn split(self) -> (WsSender<P>, WsReceiver<T>) {
+        (self.sender, self.receiver)
+    }
+
+    pub async fn send(&self, message: &P) -> Result<(), ArriError> {
+        self.sender.send(message).await
+    }
+
+    pub fn close(&self) {
+        self.sender.close()
+    }
+
+    pub async fn recv(&mut self) -> Option<WsEvent<T>> {
+        self.receiver.recv().await
+    }
+}
+
+impl<P, T> Stream for WsSession<P, T> {
+    type Item = WsEvent<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.get_mut().receiver).poll_next(cx)
+    }
+}
+
+/// Sends messages over a `WsSession`. Can be cloned to send from multiple tasks.
+pub struct WsSender<P> {
+    commands: mpsc::UnboundedSender<WsCommand>,
+    _types: PhantomData<fn(P)>,
+}
+
+impl<P> Clone for WsSender<P> {
+    fn clone(&self) -> Self {
+        Self {
+            commands: self.commands.clone(),
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<P: ArriModel> WsSender<P> {
+    /// Resolves once the message has been written to the socket. Messages sent while the
+    /// session is reconnecting are held until the connection has been reopened.
+    pub async fn send(&self, message: &P) -> Result<(), ArriError> {
+        let (sent, is_sent) = oneshot::channel();
+        self.commands
+            .send(WsCommand::Send(message.to_json_string(), sent))
+            .map_err(|_| closed_error())?;
+        is_sent.await.map_err(|_| closed_error())
+    }
+
+    /// Send a close frame and wait for the server to acknowledge it in the background.
+    /// The receiver gets `WsEvent::Close(WsCloseReason::Closed)` once the session has stopped.
+    pub fn close(&self) {
+        let _ = self.commands.send(WsCommand::Close);
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.commands.is_closed()
+    }
+}
+
+/// Receives the events of a `WsSession`. Dropping the receiver closes the session.
+pub struct WsReceiver<T> {
+    events: mpsc::Receiver<WsEvent<T>>,
+}
+
+impl<T> WsReceiver<T> {
+    /// Returns `None` after `WsEvent::Close` has been received
+    pub async fn recv(&mut self) -> Option<WsEvent<T>> {
+        self.events.recv().await
+    }
+}
+
+impl<T> Stream for WsReceiver<T> {
+    type Item = WsEvent<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod session_tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use super::*;
+    use crate::{
+        sse::{FixedDelay, NoRetry},
+        ws::{
+            ws_tests::{connect, test_message, EchoServer},
+            WsOptions,
+        },
+    };
+
+    async fn start_session(
+        server: &EchoServer,
+        options: WsOptions,
+    ) -> WsSession<ArriError, ArriError> {
+        connect(server.url.clone(), HashMap::new(), options)
+            .await
+            .unwrap()
+            .into_session()
+    }
+
+    fn describe(event: WsEvent<ArriError>) -> String {
+        match event {
+            WsEvent::Message(message) => format!("message {}", message.code),
+            WsEvent::Custom(message) => format!("{} {}", message.event, message.data),
+            WsEvent::Error(err) => format!("error {}", err.code),
+            WsEvent::Open => "open".to_string(),
+            WsEvent::Reconnecting { attempt, .. } => format!("reconnecting {}", attempt),
+            WsEvent::Close(reason) => format!("close {:?}", reason),
+        }
+    }
+
+    /// Wait for the next `count` events
+    async fn next_events(
+        events: &mut (impl Stream<Item = WsEvent<ArriError>> + Unpin),
+        count: usize,
+    ) -> Vec<String> {
+        let mut result = Vec::new();
+        while result.len() < count {
+            let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+                .await
+                .expect("timed out waiting for an event");
+            match event {
+                Some(event) => result.push(describe(event)),
+                None => break,
+            }
+        }
+        result
+    }
+
+    #[tokio::test]
+    async fn session_test() {
+        let server = EchoServer::start().await;
+        let (sender, mut receiver) = start_session(&server, WsOptions::default()).await.split();
+        let background_sender = sender.clone();
+        tokio::spawn(async move {
+            for code in [10, 3, 403] {
+                background_sender.send(&test_message(code)).await.unwrap();
+            }
+        });
+        assert_eq!(
+            next_events(&mut receiver, 5).await,
+            vec![
+                "open",
+                "message 0",
+                "message 10",
+                r#"typing {"code":3,"message":"message 3"}"#,
+                "error 403",
+            ]
+        );
+        sender.close();
+        assert_eq!(next_events(&mut receiver, 2).await, vec!["close Closed"]);
+        assert_eq!(server.close_codes(), vec![1000]);
+        assert!(sender.is_closed());
+        assert!(sender.send(&test_message(11)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn reconnect_test() {
+        let server = EchoServer::start().await;
+        let mut session = start_session(
+            &server,
+            WsOptions {
+                reconnection_policy: Some(Arc::new(FixedDelay::new(Duration::ZERO))),
+                ..Default::default()
+            },
+        )
+        .await;
+        session.send(&test_message(1)).await.unwrap();
+        assert_eq!(
+            next_events(&mut session, 5).await,
+            vec!["open", "message 0", "error 0", "reconnecting 1", "open"]
+        );
+        session.send(&test_message(20)).await.unwrap();
+        assert_eq!(
+            next_events(&mut session, 2).await,
+            vec!["message 1", "message 20"]
+        );
+        assert_eq!(server.handshakes().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn keepalive_test() {
+        let server = EchoServer::start().await;
+        let mut session = start_session(
+            &server,
+            WsOptions {
+                reconnection_policy: Some(Arc::new(FixedDelay::new(Duration::ZERO))),
+                ping_interval: Some(Duration::from_millis(20)),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(
+            next_events(&mut session, 2).await,
+            vec!["open", "message 0"]
+        );
+        // the server keeps answering pings until it stops reading
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(server.handshakes().len(), 1);
+        session.send(&test_message(4)).await.unwrap();
+        let events = tokio::time::timeout(Duration::from_secs(5), session.recv())
+            .await
+            .unwrap();
+        match events {
+            Some(WsEvent::Error(err)) => {
+                assert_eq!(err.data, Some(serde_json::json!({ "kind": "keepalive" })))
+            }
+            _ => panic!("expected a keepalive error"),
+        }
+        assert_eq!(
+            next_events(&mut session, 3).await,
+            vec!["reconnecting 1", "open", "message 1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn gave_up_test() {
+        let server = EchoServer::start().await;
+        let (sender, mut receiver) = start_session(
+            &server,
+            WsOptions {
+                reconnection_policy: Some(Arc::new(NoRetry)),
+                ..Default::default()
+            },
+        )
+        .await
+        .split();
+        sender.send(&test_message(1)).await.unwrap();
+        let events = next_events(&mut receiver, 5).await;
+        assert_eq!(&events[..3], &["open", "message 0", "error 0"]);
+        assert!(events[3].starts_with("close GaveUp"));
+        assert_eq!(events.len(), 4);
+        assert!(sender.send(&test_message(2)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejected_reconnect_test() {
+        let server = EchoServer::start().await;
+        let mut session = connect(
+            format!("{}/reject-reconnect", server.url),
+            HashMap::new(),
+            WsOptions {
+                reconnection_policy: Some(Arc::new(FixedDelay::new(Duration::ZERO))),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .into_session();
+        session.send(&test_message(1)).await.unwrap();
+        let events = next_events(&mut session, 6).await;
+        assert_eq!(
+            &events[..5],
+            &[
+                "open",
+                "message 0",
+                "error 0",
+                "reconnecting 1",
+                "error 401"
+            ]
+        );
+        assert!(events[5].starts_with("close GaveUp"));
+        assert_eq!(events.len(), 6);
+        assert_eq!(server.handshakes().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn server_close_test() {
+        let server = EchoServer::start().await;
+        let mut session = start_session(&server, WsOptions::default()).await;
+        session.send(&test_message(2)).await.unwrap();
+        assert_eq!(
+            next_events(&mut session, 4).await,
+            vec!["open", "message 0", "close ServerClosed"]
+        );
+        assert_eq!(server.handshakes().len(), 1);
+    }
+}
@@ -6,13 +6,23 @@
     deprecated,
     clippy::all
 )]
+#[cfg(feature = "testing")]
+use arri_client::mock::{MockEventStream, MockProcedure, MockWsProcedure};
 use arri_client::{
     chrono::{DateTime, FixedOffset},
     parsed_arri_request,
+    procedure::{
+        arri_procedure_request, ArriProcedure, ArriProcedureClient, ArriProcedureRegistry,
+        ArriWsProcedure, ProcedureInfo, ProcedureTransport,
+    },
     reqwest::{self, Request},
     serde_json::{self, Map},
-    sse::{parsed_arri_sse_request, ArriParsedSseRequestOptions, SseController, SseEvent},
+    sse::{
+        parsed_arri_sse_request, ArriParsedSseRequestOptions, SseController, SseEvent,
+        SseHandlerOutput, SseOptions,
+    },
     utils::{serialize_date_time, serialize_string},
+    ws::{arri_ws_request, ArriParsedWsRequestOptions, WsConnection, WsOptions},
     ArriClientConfig, ArriClientService, ArriEnum, ArriError, ArriModel, ArriParsedRequestOptions,
     EmptyArriModel, InternalArriClientConfig,
 };
@@ -26,10 +36,7 @@ pub struct ExampleClient {
 
 impl ArriClientService for ExampleClient {
     fn create(config: ArriClientConfig) -> Self {
-        Self {
-            _config: InternalArriClientConfig::from(config.clone()),
-            books: ExampleClientBooksService::create(config),
-        }
+        Self::_from_config(InternalArriClientConfig::from(config))
     }
 
     fn update_headers(&self, headers: HashMap<&'static str, String>) {
@@ -39,20 +46,164 @@ impl ArriClientService for ExampleClient {
     }
 }
 
+impl ArriProcedureClient for ExampleClient {
+    fn procedure_config(&self) -> &InternalArriClientConfig {
+        &self._config
+    }
+}
+
+impl ArriProcedureRegistry for ExampleClient {
+    const DEFINITION_HASH: &'static str = "6eb85c4403cb6d1a";
+    const PROCEDURES: &'static [ProcedureInfo] = &[
+        ProcedureInfo {
+            name: "sendObject",
+            path: "/send-object",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("NestedObject"),
+            response: Some("NestedObject"),
+        },
+        ProcedureInfo {
+            name: "books.getBook",
+            path: "/books/get-book",
+            method: Some(reqwest::Method::GET),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: Some("Get a book"),
+            params: Some("BookParams"),
+            response: Some("Book"),
+        },
+        ProcedureInfo {
+            name: "books.createBook",
+            path: "/books/create-book",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: true,
+            description: Some("Create a book"),
+            params: Some("Book"),
+            response: Some("Book"),
+        },
+        ProcedureInfo {
+            name: "books.watchBook",
+            path: "/books/watch-book",
+            method: Some(reqwest::Method::GET),
+            transport: ProcedureTransport::Http,
+            is_event_stream: true,
+            is_deprecated: true,
+            description: None,
+            params: Some("BookParams"),
+            response: Some("Book"),
+        },
+        ProcedureInfo {
+            name: "books.createConnection",
+            path: "/books/create-connection",
+            method: None,
+            transport: ProcedureTransport::Ws,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("BookParams"),
+            response: Some("Book"),
+        },
+    ];
+    const TYPE_HASHES: &'static [(&'static str, &'static str)] = &[
+        ("EmptyObject", "9922b3393c03b9e8"),
+        ("Book", "6fa33869aa3824cb"),
+        ("BookParams", "5561f69d0c26d5c3"),
+        ("NestedObject", "2354ca52029d1dc8"),
+        ("ObjectWithEveryType", "e3a8ccaa24b13ee1"),
+        ("ObjectWithOptionalFields", "7afde2bf1dcd9f18"),
+        ("ObjectWithNullableFields", "203cf3c05f9d7f5e"),
+        ("RecursiveObject", "b1f6e570845c4951"),
+    ];
+}
+
 impl ExampleClient {
+    fn _from_config(config: InternalArriClientConfig) -> Self {
+        Self {
+            books: ExampleClientBooksService::_from_config(config.child()),
+            _config: config,
+        }
+    }
     pub async fn send_object(&self, params: NestedObject) -> Result<NestedObject, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!("{}/send-object", &self._config.base_url),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "20".to_string(),
-            },
-            Some(params),
-            |body| return NestedObject::from_json_string(body),
-        )
-        .await
+        arri_procedure_request::<ExampleClientSendObjectProcedure>(&self._config, params).await
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ExampleClientSendObjectProcedure;
+
+impl ArriProcedure for ExampleClientSendObjectProcedure {
+    type Params = NestedObject;
+    type Response = NestedObject;
+    const PATH: &'static str = "/send-object";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "20";
+
+    fn parse_response(body: String) -> Self::Response {
+        NestedObject::from_json_string(body)
+    }
+}
+
+pub trait ExampleClientApi {
+    type Books: ExampleClientBooksServiceApi;
+    fn books(&self) -> &Self::Books;
+    fn send_object(
+        &self,
+        params: NestedObject,
+    ) -> impl std::future::Future<Output = Result<NestedObject, ArriError>> + std::marker::Send;
+}
+
+impl ExampleClientApi for ExampleClient {
+    type Books = ExampleClientBooksService;
+    fn books(&self) -> &Self::Books {
+        &self.books
+    }
+    fn send_object(
+        &self,
+        params: NestedObject,
+    ) -> impl std::future::Future<Output = Result<NestedObject, ArriError>> + std::marker::Send
+    {
+        ExampleClient::send_object(self, params)
+    }
+}
+
+#[cfg(feature = "testing")]
+#[derive(Clone)]
+pub struct ExampleClientMock {
+    pub send_object: MockProcedure<NestedObject, NestedObject>,
+    pub books: ExampleClientBooksServiceMock,
+}
+
+#[cfg(feature = "testing")]
+impl Default for ExampleClientMock {
+    fn default() -> Self {
+        Self {
+            send_object: MockProcedure::new("/send-object"),
+            books: ExampleClientBooksServiceMock::default(),
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+impl ExampleClientApi for ExampleClientMock {
+    type Books = ExampleClientBooksServiceMock;
+    fn books(&self) -> &Self::Books {
+        &self.books
+    }
+    fn send_object(
+        &self,
+        params: NestedObject,
+    ) -> impl std::future::Future<Output = Result<NestedObject, ArriError>> + std::marker::Send
+    {
+        self.send_object.call(params)
     }
 }
 
@@ -63,9 +214,7 @@ pub struct ExampleClientBooksService {
 
 impl ArriClientService for ExampleClientBooksService {
     fn create(config: ArriClientConfig) -> Self {
-        Self {
-            _config: InternalArriClientConfig::from(config),
-        }
+        Self::_from_config(InternalArriClientConfig::from(config))
     }
 
     fn update_headers(&self, headers: HashMap<&'static str, String>) {
@@ -74,64 +223,276 @@ impl ArriClientService for ExampleClientBooksService {
     }
 }
 
+impl ArriProcedureClient for ExampleClientBooksService {
+    fn procedure_config(&self) -> &InternalArriClientConfig {
+        &self._config
+    }
+}
+
 impl ExampleClientBooksService {
+    fn _from_config(config: InternalArriClientConfig) -> Self {
+        Self { _config: config }
+    }
     /// Get a book
     pub async fn get_book(&self, params: BookParams) -> Result<Book, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!("{}/books/get-book", &self._config.base_url),
-                method: reqwest::Method::GET,
-                headers: self._config.headers.clone(),
-                client_version: "20".to_string(),
-            },
-            Some(params),
-            |body| return Book::from_json_string(body),
-        )
-        .await
+        arri_procedure_request::<ExampleClientBooksGetBookProcedure>(&self._config, params).await
     }
 
     /// Create a book
     #[deprecated]
     pub async fn create_book(&self, params: Book) -> Result<Book, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!("{}/books/create-book", &self._config.base_url),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "20".to_string(),
-            },
-            Some(params),
-            |body| return Book::from_json_string(body),
-        )
-        .await
+        arri_procedure_request::<ExampleClientBooksCreateBookProcedure>(&self._config, params).await
     }
     #[deprecated]
-    pub async fn watch_book<OnEvent>(
+    pub async fn watch_book<OnEvent, OnEventOutput>(
         &self,
         params: BookParams,
         on_event: &mut OnEvent,
-        max_retry_count: Option<u64>,
-        max_retry_interval: Option<u64>,
+        options: SseOptions<BookParams, Book>,
     ) where
-        OnEvent: FnMut(SseEvent<Book>, &mut SseController) + std::marker::Send + std::marker::Sync,
+        OnEvent: FnMut(SseEvent<Book>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
     {
+        self._config
+            .deprecation
+            .procedure_called(ExampleClientBooksWatchBookProcedure::PATH);
         parsed_arri_sse_request(
             ArriParsedSseRequestOptions {
                 client: &self._config.http_client,
-                url: format!("{}/books/watch-book", &self._config.base_url),
-                method: reqwest::Method::GET,
+                url: format!(
+                    "{}{}",
+                    &self._config.base_url,
+                    ExampleClientBooksWatchBookProcedure::PATH
+                ),
+                method: ExampleClientBooksWatchBookProcedure::METHOD,
                 headers: self._config.headers.clone(),
-                client_version: "20".to_string(),
-                max_retry_count,
-                max_retry_interval,
+                client_version: ExampleClientBooksWatchBookProcedure::CLIENT_VERSION.to_string(),
+                deprecation: Some(
+                    self._config
+                        .deprecation
+                        .reporter(ExampleClientBooksWatchBookProcedure::PATH),
+                ),
+                version_mismatch: Some(self._config.version_mismatch.clone()),
+                options,
             },
             Some(params),
             on_event,
         )
         .await;
     }
+    pub async fn create_connection(
+        &self,
+        options: WsOptions,
+    ) -> Result<WsConnection<BookParams, Book>, ArriError> {
+        arri_ws_request(ArriParsedWsRequestOptions {
+            url: format!(
+                "{}{}",
+                &self._config.base_url,
+                ExampleClientBooksCreateConnectionProcedure::PATH
+            ),
+            headers: self._config.headers.clone(),
+            client_version: ExampleClientBooksCreateConnectionProcedure::CLIENT_VERSION.to_string(),
+            deprecation: Some(
+                self._config
+                    .deprecation
+                    .reporter(ExampleClientBooksCreateConnectionProcedure::PATH),
+            ),
+            version_mismatch: Some(self._config.version_mismatch.clone()),
+            options,
+        })
+        .await
+    }
+}
+
+/// Get a book
+#[derive(Clone, Copy, Debug)]
+pub struct ExampleClientBooksGetBookProcedure;
+
+impl ArriProcedure for ExampleClientBooksGetBookProcedure {
+    type Params = BookParams;
+    type Response = Book;
+    const PATH: &'static str = "/books/get-book";
+    const METHOD: reqwest::Method = reqwest::Method::GET;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "20";
+
+    fn parse_response(body: String) -> Self::Response {
+        Book::from_json_string(body)
+    }
+}
+
+/// Create a book
+#[derive(Clone, Copy, Debug)]
+pub struct ExampleClientBooksCreateBookProcedure;
+
+impl ArriProcedure for ExampleClientBooksCreateBookProcedure {
+    type Params = Book;
+    type Response = Book;
+    const PATH: &'static str = "/books/create-book";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = true;
+    const CLIENT_VERSION: &'static str = "20";
+
+    fn parse_response(body: String) -> Self::Response {
+        Book::from_json_string(body)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ExampleClientBooksWatchBookProcedure;
+
+impl ArriProcedure for ExampleClientBooksWatchBookProcedure {
+    type Params = BookParams;
+    type Response = Book;
+    const PATH: &'static str = "/books/watch-book";
+    const METHOD: reqwest::Method = reqwest::Method::GET;
+    const IS_EVENT_STREAM: bool = true;
+    const IS_DEPRECATED: bool = true;
+    const CLIENT_VERSION: &'static str = "20";
+
+    fn parse_response(body: String) -> Self::Response {
+        Book::from_json_string(body)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ExampleClientBooksCreateConnectionProcedure;
+
+impl ArriWsProcedure for ExampleClientBooksCreateConnectionProcedure {
+    type Params = BookParams;
+    type Response = Book;
+    const PATH: &'static str = "/books/create-connection";
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "20";
+}
+
+pub trait ExampleClientBooksServiceApi {
+    /// Get a book
+    fn get_book(
+        &self,
+        params: BookParams,
+    ) -> impl std::future::Future<Output = Result<Book, ArriError>> + std::marker::Send;
+    /// Create a book
+    #[deprecated]
+    fn create_book(
+        &self,
+        params: Book,
+    ) -> impl std::future::Future<Output = Result<Book, ArriError>> + std::marker::Send;
+    #[deprecated]
+    fn watch_book<OnEvent, OnEventOutput>(
+        &self,
+        params: BookParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<BookParams, Book>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<Book>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput;
+    fn create_connection(
+        &self,
+        options: WsOptions,
+    ) -> impl std::future::Future<Output = Result<WsConnection<BookParams, Book>, ArriError>>
+           + std::marker::Send;
+}
+
+impl ExampleClientBooksServiceApi for ExampleClientBooksService {
+    fn get_book(
+        &self,
+        params: BookParams,
+    ) -> impl std::future::Future<Output = Result<Book, ArriError>> + std::marker::Send {
+        ExampleClientBooksService::get_book(self, params)
+    }
+    fn create_book(
+        &self,
+        params: Book,
+    ) -> impl std::future::Future<Output = Result<Book, ArriError>> + std::marker::Send {
+        ExampleClientBooksService::create_book(self, params)
+    }
+    fn watch_book<OnEvent, OnEventOutput>(
+        &self,
+        params: BookParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<BookParams, Book>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<Book>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        ExampleClientBooksService::watch_book(self, params, on_event, options)
+    }
+    fn create_connection(
+        &self,
+        options: WsOptions,
+    ) -> impl std::future::Future<Output = Result<WsConnection<BookParams, Book>, ArriError>>
+           + std::marker::Send {
+        ExampleClientBooksService::create_connection(self, options)
+    }
+}
+
+#[cfg(feature = "testing")]
+#[derive(Clone)]
+pub struct ExampleClientBooksServiceMock {
+    pub get_book: MockProcedure<BookParams, Book>,
+    pub create_book: MockProcedure<Book, Book>,
+    pub watch_book: MockEventStream<BookParams, Book>,
+    pub create_connection: MockWsProcedure<BookParams, Book>,
+}
+
+#[cfg(feature = "testing")]
+impl Default for ExampleClientBooksServiceMock {
+    fn default() -> Self {
+        Self {
+            get_book: MockProcedure::new("/books/get-book"),
+            create_book: MockProcedure::new("/books/create-book"),
+            watch_book: MockEventStream::new("/books/watch-book"),
+            create_connection: MockWsProcedure::new("/books/create-connection"),
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+impl ExampleClientBooksServiceApi for ExampleClientBooksServiceMock {
+    fn get_book(
+        &self,
+        params: BookParams,
+    ) -> impl std::future::Future<Output = Result<Book, ArriError>> + std::marker::Send {
+        self.get_book.call(params)
+    }
+    fn create_book(
+        &self,
+        params: Book,
+    ) -> impl std::future::Future<Output = Result<Book, ArriError>> + std::marker::Send {
+        self.create_book.call(params)
+    }
+    fn watch_book<OnEvent, OnEventOutput>(
+        &self,
+        params: BookParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<BookParams, Book>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<Book>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        self.watch_book.call(params, on_event)
+    }
+    fn create_connection(
+        &self,
+        options: WsOptions,
+    ) -> impl std::future::Future<Output = Result<WsConnection<BookParams, Book>, ArriError>>
+           + std::marker::Send {
+        self.create_connection.call(options)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
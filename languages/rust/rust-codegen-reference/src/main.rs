@@ -11,6 +11,7 @@ fn get_headers() -> HashMap<&'static str, String> {
 }
 
 #[tokio::main]
+#[allow(deprecated)]
 async fn main() {
     let client = ExampleClient::create(ArriClientConfig {
         http_client: reqwest::Client::new(),
@@ -39,12 +40,13 @@ async fn main() {
                         controller.abort();
                         client.update_headers(HashMap::new());
                     }
+                    arri_client::sse::SseEvent::Custom(_) => {}
                     arri_client::sse::SseEvent::Error(_) => {}
                     arri_client::sse::SseEvent::Open => {}
-                    arri_client::sse::SseEvent::Close => {}
+                    arri_client::sse::SseEvent::Reconnecting { .. } => {}
+                    arri_client::sse::SseEvent::Close(_) => {}
                 },
-                None,
-                None,
+                arri_client::sse::SseOptions::default(),
             )
             .await;
     });
@@ -52,6 +54,7 @@ async fn main() {
 }
 
 #[cfg(test)]
+#[allow(deprecated)]
 mod parsing_and_serialization_tests {
     use crate::example_client::{
         Book, BookParams, Discriminator, Enumerator, NestedObject, ObjectWithEveryType,
@@ -65,7 +68,7 @@ mod parsing_and_serialization_tests {
     use std::{collections::BTreeMap, fs};
 
     fn get_test_date() -> DateTime<FixedOffset> {
-        DateTime::parse_from_rfc3339("2001-01-01T16:00:00.000Z").unwrap_or(DateTime::default())
+        DateTime::parse_from_rfc3339("2001-01-01T16:00:00.000Z").unwrap_or_default()
     }
 
     #[test]
@@ -340,3 +343,289 @@ mod parsing_and_serialization_tests {
         assert_eq!(reference.to_query_params_string(), "".to_string());
     }
 }
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod loopback_tests {
+    use crate::example_client::{Book, BookParams, ExampleClient};
+    use arri_client::{
+        chrono::DateTime,
+        loopback::{ArriLoopback, LoopbackStream},
+        sse::{SseEvent, SseOptions},
+        ArriClientConfig, ArriClientService, ArriModel,
+    };
+
+    fn book(id: &str) -> Book {
+        Book {
+            id: id.to_string(),
+            name: "Tom Sawyer".to_string(),
+            created_at: DateTime::default(),
+            updated_at: DateTime::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn generated_client_test() {
+        let loopback = ArriLoopback::new()
+            .handle("/books/get-book", |params: BookParams| async move {
+                Ok(book(&params.book_id))
+            })
+            .handle_stream(
+                "/books/watch-book",
+                |params: BookParams, stream: LoopbackStream<Book>| async move {
+                    for i in 0..2 {
+                        stream
+                            .send(book(&format!("{}-{}", params.book_id, i)))
+                            .await?;
+                    }
+                    Ok(())
+                },
+            );
+        let client = ExampleClient::create(ArriClientConfig::loopback(&loopback));
+        let result = client
+            .books
+            .get_book(BookParams {
+                book_id: "1".to_string(),
+            })
+            .await;
+        assert_eq!(result.unwrap(), book("1"));
+
+        let mut ids = Vec::new();
+        client
+            .books
+            .watch_book(
+                BookParams {
+                    book_id: "2".to_string(),
+                },
+                &mut |event, _| {
+                    if let SseEvent::Message(book) = event {
+                        ids.push(book.id);
+                    }
+                },
+                SseOptions::default(),
+            )
+            .await;
+        assert_eq!(ids, vec!["2-0", "2-1"]);
+
+        // unregistered procedures fail like a 404 from a server
+        let result = client.books.create_book(Book::new()).await;
+        assert_eq!(result.unwrap_err().code, 404);
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod procedure_tests {
+    use crate::example_client::{
+        Book, BookParams, ExampleClient, ExampleClientBooksCreateBookProcedure,
+        ExampleClientBooksGetBookProcedure,
+    };
+    use arri_client::{
+        chrono::DateTime,
+        drift::DefinitionDrift,
+        loopback::{ArriLoopback, LoopbackStream},
+        procedure::{
+            ArriProcedure, ArriProcedureClient, ArriProcedureRegistry, ProcedureOptions,
+            ProcedureTransport,
+        },
+        reqwest, serde_json,
+        sse::SseOptions,
+        ArriClientConfig, ArriClientService, ArriModel,
+    };
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn generated_registry_test() {
+        assert_eq!(ExampleClient::PROCEDURES.len(), 5);
+        let procedure = ExampleClient::procedure("books.watchBook").unwrap();
+        assert_eq!(procedure.path, "/books/watch-book");
+        assert_eq!(procedure.method, Some(reqwest::Method::GET));
+        assert!(procedure.is_event_stream && procedure.is_deprecated);
+        assert_eq!(procedure.params, Some("BookParams"));
+        let procedure = ExampleClient::procedure_by_path("/books/create-connection").unwrap();
+        assert_eq!(procedure.transport, ProcedureTransport::Ws);
+        assert_eq!(procedure.method, None);
+    }
+
+    #[test]
+    fn generated_definition_hash_test() {
+        let mut server: serde_json::Value = serde_json::from_str(include_str!(
+            "../../../../tests/test-files/AppDefinition.json"
+        ))
+        .unwrap();
+        let drift = DefinitionDrift::compare::<ExampleClient>(&server).unwrap();
+        assert!(drift.is_identical());
+        assert!(!drift.has_mismatches());
+
+        server["definitions"]["Book"]["properties"]["name"]["type"] = "int32".into();
+        let drift = DefinitionDrift::compare::<ExampleClient>(&server).unwrap();
+        assert!(!drift.is_identical());
+        assert_eq!(
+            drift.mismatches[0].to_string(),
+            "Book: Type doesn't match the server"
+        );
+    }
+
+    #[tokio::test]
+    async fn generated_procedure_test() {
+        assert_eq!(ExampleClientBooksGetBookProcedure::PATH, "/books/get-book");
+        assert_eq!(
+            ExampleClientBooksCreateBookProcedure::METHOD,
+            reqwest::Method::POST
+        );
+        let loopback =
+            ArriLoopback::new().handle("/books/get-book", |params: BookParams| async move {
+                Ok(Book {
+                    id: params.book_id,
+                    name: "Tom Sawyer".to_string(),
+                    created_at: DateTime::default(),
+                    updated_at: DateTime::default(),
+                })
+            });
+        let client = ExampleClient::create(ArriClientConfig::loopback(&loopback));
+        let result = client
+            .call::<ExampleClientBooksGetBookProcedure>(BookParams {
+                book_id: "1".to_string(),
+            })
+            .await;
+        assert_eq!(result.unwrap().id, "1");
+
+        // options configured on the client apply to the methods of its services
+        client.configure_procedure(
+            ProcedureOptions::<ExampleClientBooksCreateBookProcedure>::new().mock(Ok),
+        );
+        let mut book = Book::new();
+        book.id = "2".to_string();
+        let result = client.books.create_book(book).await;
+        assert_eq!(result.unwrap().id, "2");
+    }
+
+    #[tokio::test]
+    async fn generated_deprecation_test() {
+        let loopback = ArriLoopback::new().handle_stream(
+            "/books/watch-book",
+            |_: BookParams, _stream: LoopbackStream<Book>| async move { Ok(()) },
+        );
+        let client = ExampleClient::create(ArriClientConfig::loopback(&loopback));
+        let paths = Arc::new(Mutex::new(Vec::new()));
+        let received = paths.clone();
+        client.on_deprecation(move |notice| {
+            received.lock().unwrap().push(notice.path.clone());
+        });
+        client.configure_procedure(
+            ProcedureOptions::<ExampleClientBooksCreateBookProcedure>::new().mock(Ok),
+        );
+        client.books.create_book(Book::new()).await.unwrap();
+        client
+            .books
+            .watch_book(BookParams::new(), &mut |_, _| {}, SseOptions::default())
+            .await;
+        client.books.get_book(BookParams::new()).await.unwrap_err();
+        assert_eq!(
+            *paths.lock().unwrap(),
+            vec!["/books/create-book", "/books/watch-book"]
+        );
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+#[allow(deprecated)]
+mod mock_tests {
+    use crate::example_client::{
+        Book, BookParams, ExampleClientApi, ExampleClientBooksServiceApi, ExampleClientMock,
+    };
+    use arri_client::{
+        sse::{SseEvent, SseOptions},
+        ws::WsOptions,
+        ArriError, ArriModel,
+    };
+
+    /// Business logic that only depends on the generated trait
+    async fn book_names(client: &impl ExampleClientApi, ids: &[&str]) -> Vec<String> {
+        let mut names = Vec::new();
+        for id in ids {
+            let params = BookParams {
+                book_id: id.to_string(),
+            };
+            match client.books().get_book(params).await {
+                Ok(book) => names.push(book.name),
+                Err(err) => names.push(format!("error {}", err.code)),
+            }
+        }
+        names
+    }
+
+    #[tokio::test]
+    async fn generated_mock_test() {
+        let client = ExampleClientMock::default();
+        client
+            .books
+            .get_book
+            .returns_once(Err(ArriError {
+                code: 404,
+                message: "Not Found".to_string(),
+                stack: None,
+                data: None,
+            }))
+            .returns_with(|params| {
+                let mut book = Book::new();
+                book.name = format!("Book {}", params.book_id);
+                Ok(book)
+            });
+        let names = book_names(&client, &["1", "2"]).await;
+        assert_eq!(names, vec!["error 404", "Book 2"]);
+        let ids: Vec<String> = client
+            .books
+            .get_book
+            .calls()
+            .into_iter()
+            .map(|params| params.book_id)
+            .collect();
+        assert_eq!(ids, vec!["1", "2"]);
+
+        client
+            .books
+            .watch_book
+            .emits(vec![SseEvent::Open, SseEvent::Message(Book::new())]);
+        let mut message_count = 0;
+        client
+            .books()
+            .watch_book(
+                BookParams::new(),
+                &mut |event, _| {
+                    if let SseEvent::Message(_) = event {
+                        message_count += 1;
+                    }
+                },
+                SseOptions::default(),
+            )
+            .await;
+        assert_eq!(message_count, 1);
+        assert_eq!(client.books.create_book.call_count(), 0);
+
+        client
+            .books
+            .create_connection
+            .sends(vec![Ok(Book::new())])
+            .replies_with(|params| {
+                let mut book = Book::new();
+                book.id = params.book_id.clone();
+                vec![Ok(book)]
+            });
+        let mut connection = client
+            .books()
+            .create_connection(WsOptions::default())
+            .await
+            .unwrap();
+        assert!(connection.receive().await.unwrap().is_ok());
+        connection
+            .send(&BookParams {
+                book_id: "3".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(connection.receive().await.unwrap().unwrap().id, "3");
+        connection.close().await;
+        assert_eq!(client.books.create_connection.received().len(), 1);
+    }
+}
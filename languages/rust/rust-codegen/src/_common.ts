@@ -1,4 +1,5 @@
 import {
+    type AppDefinition,
     pascalCase,
     removeDisallowedChars,
     Schema,
@@ -151,14 +152,153 @@ export function formatDescriptionComment(
     leading = '',
 ): string {
     return description
-        .split('\n')
+        .split(/\r\n|\r|\n/)
         .map((line) => `${leading}/// ${line}`)
         .join('\n');
 }
 
+/**
+ * Escapes `input` for use as a Rust string literal
+ */
+export function rustStringLiteral(input: string): string {
+    let output = '';
+    for (const char of input) {
+        switch (char) {
+            case '\\':
+                output += '\\\\';
+                break;
+            case '"':
+                output += '\\"';
+                break;
+            case '\n':
+                output += '\\n';
+                break;
+            case '\r':
+                output += '\\r';
+                break;
+            case '\t':
+                output += '\\t';
+                break;
+            default: {
+                const code = char.codePointAt(0) ?? 0;
+                output +=
+                    code < 0x20 || code === 0x7f
+                        ? `\\u{${code.toString(16)}}`
+                        : char;
+            }
+        }
+    }
+    return `"${output}"`;
+}
+
 export function maybeStr(show: boolean, char: string) {
     if (show) {
         return char;
     }
     return '';
 }
+
+const procedureHashKeys = [
+    'transport',
+    'path',
+    'method',
+    'params',
+    'response',
+    'isEventStream',
+];
+
+/**
+ * Hash of the parts of an app definition that affect the wire format.
+ * Must match `arri_client::definition::definition_hash`.
+ */
+export function definitionHash(def: AppDefinition): string {
+    const procedures: Record<string, Record<string, unknown>> = {};
+    for (const [name, procedure] of Object.entries(def.procedures)) {
+        const fields: Record<string, unknown> = {};
+        for (const key of procedureHashKeys) {
+            fields[key] = (procedure as Record<string, unknown>)[key];
+        }
+        procedures[name] = fields;
+    }
+    const definitions: Record<string, unknown> = {};
+    for (const [name, schema] of Object.entries(def.definitions)) {
+        definitions[name] = schemaFingerprint(schema);
+    }
+    return fnv1a(canonicalJson({ definitions, procedures }));
+}
+
+/**
+ * Hash of a type definition without its metadata.
+ * Must match `arri_client::definition::type_definition_hash`.
+ */
+export function typeDefinitionHash(schema: Schema): string {
+    return fnv1a(canonicalJson(schemaFingerprint(schema)));
+}
+
+function schemaFingerprint(schema: unknown): unknown {
+    if (
+        typeof schema !== 'object' ||
+        schema === null ||
+        Array.isArray(schema)
+    ) {
+        return schema;
+    }
+    const output: Record<string, unknown> = {};
+    for (const [key, value] of Object.entries(schema)) {
+        switch (key) {
+            case 'metadata':
+                break;
+            case 'elements':
+            case 'values':
+                output[key] = schemaFingerprint(value);
+                break;
+            case 'properties':
+            case 'optionalProperties':
+            case 'mapping': {
+                if (typeof value !== 'object' || value === null) {
+                    output[key] = value;
+                    break;
+                }
+                const fields: Record<string, unknown> = {};
+                for (const [name, field] of Object.entries(value)) {
+                    fields[name] = schemaFingerprint(field);
+                }
+                output[key] = fields;
+                break;
+            }
+            default:
+                output[key] = value;
+        }
+    }
+    return output;
+}
+
+/**
+ * JSON with sorted keys and no whitespace.
+ * Must match `canonical_json` in `arri_client::definition::hash`.
+ */
+function canonicalJson(input: unknown): string {
+    if (Array.isArray(input)) {
+        return `[${input.map((item) => canonicalJson(item)).join(',')}]`;
+    }
+    if (typeof input === 'object' && input !== null) {
+        const record = input as Record<string, unknown>;
+        const keys = Object.keys(record)
+            .filter((key) => record[key] !== undefined)
+            .sort();
+        return `{${keys.map((key) => `${JSON.stringify(key)}:${canonicalJson(record[key])}`).join(',')}}`;
+    }
+    return JSON.stringify(input);
+}
+
+/**
+ * 64 bit FNV-1a of the UTF-8 bytes as 16 hex characters
+ */
+function fnv1a(input: string): string {
+    let hash = 0xcbf29ce484222325n;
+    for (const byte of new TextEncoder().encode(input)) {
+        hash ^= BigInt(byte);
+        hash = (hash * 0x100000001b3n) & 0xffffffffffffffffn;
+    }
+    return hash.toString(16).padStart(16, '0');
+}
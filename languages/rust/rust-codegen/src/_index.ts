@@ -19,8 +19,10 @@ import {
 import path from 'pathe';
 
 import {
+    definitionHash,
     GeneratorContext,
     RustProperty,
+    typeDefinitionHash,
     validRustIdentifier,
     validRustName,
 } from './_common';
@@ -44,7 +46,15 @@ import {
     rustU32FromSchema,
     rustU64FromSchema,
 } from './primitives';
-import { rustRpcFromSchema, rustServiceFromSchema } from './procedures';
+import {
+    rustProcedureInfoFromSchema,
+    rustProcedureTypeFromSchema,
+    rustRpcApiFromSchema,
+    rustRpcFromSchema,
+    RustRpcApi,
+    rustServiceApiFromParts,
+    rustServiceFromSchema,
+} from './procedures';
 import rustRecordFromSchema from './record';
 import rustRefFromSchema from './ref';
 
@@ -96,6 +106,10 @@ export function createRustClient(
 ): string {
     const services = unflattenProcedures(def.procedures, context.rootService);
     const rpcParts: string[] = [];
+    const procedureTypeParts: string[] = [];
+    const rpcApis: RustRpcApi[] = [];
+    const procedures: string[] = [];
+    const subServiceProcedures: string[] = [];
     const subServices: { name: string; key: string }[] = [];
     const subServiceContent: string[] = [];
     for (const key of Object.keys(services)) {
@@ -116,11 +130,12 @@ export function createRustClient(
                     name: service.name,
                 });
                 subServiceContent.push(service.content);
+                subServiceProcedures.push(...service.procedures);
             }
             continue;
         }
         if (isRpcDefinition(subDef)) {
-            const rpc = rustRpcFromSchema(subDef, {
+            const rpcContext: GeneratorContext = {
                 clientVersion: def.info?.version ?? '',
                 clientName: context.clientName,
                 typeNamePrefix: context.typeNamePrefix,
@@ -128,10 +143,33 @@ export function createRustClient(
                 schemaPath: key,
                 generatedTypes: context.generatedTypes,
                 rootService: context.rootService,
-            });
+            };
+            const rpc = rustRpcFromSchema(subDef, rpcContext);
             if (rpc) {
                 rpcParts.push(rpc);
             }
+            const procedureType = rustProcedureTypeFromSchema(
+                subDef,
+                rpcContext,
+            );
+            if (procedureType) {
+                procedureTypeParts.push(procedureType);
+            }
+            const rpcApi = rustRpcApiFromSchema(
+                subDef,
+                rpcContext,
+                validRustName(context.clientName),
+            );
+            if (rpcApi) {
+                rpcApis.push(rpcApi);
+            }
+            const procedureInfo = rustProcedureInfoFromSchema(
+                subDef,
+                rpcContext,
+            );
+            if (procedureInfo) {
+                procedures.push(procedureInfo);
+            }
             continue;
         }
     }
@@ -165,8 +203,8 @@ use arri_client::{
 use std::collections::{BTreeMap, HashMap};
 ${modelParts.join('\n\n')}`;
     }
+    procedures.push(...subServiceProcedures);
     const clientName = validRustName(context.clientName);
-    const paramSuffix = subServices.length > 0 ? '.clone()' : '';
     return `#![allow(
     dead_code,
     unused_imports,
@@ -175,13 +213,20 @@ ${modelParts.join('\n\n')}`;
     deprecated,
     clippy::all
 )]
+#[cfg(feature = "testing")]
+use arri_client::mock::{MockEventStream, MockProcedure, MockWsProcedure};
 use arri_client::{
     chrono::{DateTime, FixedOffset},
     parsed_arri_request,
+    procedure::{
+        arri_procedure_request, ArriProcedure, ArriProcedureClient, ArriProcedureRegistry,
+        ArriWsProcedure, ProcedureInfo, ProcedureTransport,
+    },
     reqwest::{self, Request},
     serde_json::{self, Map},
-    sse::{parsed_arri_sse_request, ArriParsedSseRequestOptions, SseController, SseEvent},
+    sse::{parsed_arri_sse_request, ArriParsedSseRequestOptions, SseController, SseEvent, SseHandlerOutput, SseOptions},
     utils::{serialize_date_time, serialize_string},
+    ws::{arri_ws_request, ArriParsedWsRequestOptions, WsConnection, WsOptions},
     ArriClientConfig, ArriClientService, ArriEnum, ArriModel, ArriParsedRequestOptions,
     ArriError, EmptyArriModel, InternalArriClientConfig,
 };
@@ -195,10 +240,7 @@ ${subServices.map((service) => `    pub ${service.key}: ${service.name},`).join(
 
 impl ArriClientService for ${clientName} {
     fn create(config: ArriClientConfig) -> Self {
-        Self {
-            _config: InternalArriClientConfig::from(config${paramSuffix}),
-${subServices.map((service, index) => `            ${service.key}: ${service.name}::create(config${index === subServices.length - 1 ? '' : '.clone()'}),`).join('\n')}
-        }
+        Self::_from_config(InternalArriClientConfig::from(config))
     }
     fn update_headers(&self, headers: HashMap<&'static str, String>) {
        let mut unwrapped_headers = self._config.headers.write().unwrap();
@@ -207,10 +249,36 @@ ${subServices.map((service, index) => `        self.${service.key}.update_header
     }
 }
 
+impl ArriProcedureClient for ${clientName} {
+    fn procedure_config(&self) -> &InternalArriClientConfig {
+        &self._config
+    }
+}
+
+impl ArriProcedureRegistry for ${clientName} {
+    const DEFINITION_HASH: &'static str = "${definitionHash(def)}";
+    const PROCEDURES: &'static [ProcedureInfo] = &[
+${procedures.map((procedure) => `        ${procedure},`).join('\n')}
+    ];
+    const TYPE_HASHES: &'static [(&'static str, &'static str)] = &[
+${Object.entries(def.definitions).map(([key, schema]) => `        ("${key}", "${typeDefinitionHash(schema)}"),`).join('\n')}
+    ];
+}
+
 impl ${clientName} {
+    fn _from_config(config: InternalArriClientConfig) -> Self {
+        Self {
+${subServices.map((service) => `            ${service.key}: ${service.name}::_from_config(config.child()),`).join('\n')}
+            _config: config,
+        }
+    }
 ${rpcParts.join('\n')}
 }
 
+${procedureTypeParts.join('\n\n')}
+
+${rustServiceApiFromParts(clientName, rpcApis, subServices)}
+
 ${subServiceContent.join('\n\n')}
 
 ${modelParts.join('\n\n')}`;
@@ -0,0 +1,39 @@
+import { normalizeWhitespace } from '@arrirpc/codegen-utils';
+
+import { rustProcedureInfoFromSchema } from './procedures';
+
+test('Escapes control characters in procedure descriptions', () => {
+    const result = rustProcedureInfoFromSchema(
+        {
+            transport: 'http',
+            path: '/logs/tail',
+            method: 'get',
+            params: undefined,
+            response: 'LogLine',
+            isEventStream: true,
+            description:
+                'Lines are colored with \u001b[31mANSI\u001b[0m codes.\r\nPrefixed with "\\t" or a tab:\t\u0000',
+        },
+        {
+            clientName: 'Client',
+            typeNamePrefix: '',
+            instancePath: 'logs.tail',
+            schemaPath: '',
+            generatedTypes: [],
+            rootService: undefined,
+        },
+    );
+    expect(normalizeWhitespace(result)).toBe(
+        normalizeWhitespace(`ProcedureInfo {
+            name: "logs.tail",
+            path: "/logs/tail",
+            method: Some(reqwest::Method::GET),
+            transport: ProcedureTransport::Http,
+            is_event_stream: true,
+            is_deprecated: false,
+            description: Some("Lines are colored with \\u{1b}[31mANSI\\u{1b}[0m codes.\\r\\nPrefixed with \\"\\\\t\\" or a tab:\\t\\u{0}"),
+            params: None,
+            response: Some("LogLine"),
+        }`),
+    );
+});
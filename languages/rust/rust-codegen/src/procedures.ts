@@ -11,6 +11,7 @@ import assert from 'assert';
 import {
     formatDescriptionComment,
     GeneratorContext,
+    rustStringLiteral,
     validRustIdentifier,
     validRustName,
 } from './_common';
@@ -51,26 +52,29 @@ export function rustHttpRpcFromSchema(
     const response = schema.response
         ? context.typeNamePrefix + validRustName(schema.response)
         : undefined;
+    const procedureName = getProcedureTypeName(context.instancePath, context);
 
     if (schema.isEventStream) {
-        return `${leading}pub async fn ${functionName}<OnEvent>(
+        return `${leading}pub async fn ${functionName}<OnEvent, OnEventOutput>(
             &self,
             ${params ? `params: ${params},` : ''}
             on_event: &mut OnEvent,
-            max_retry_count: Option<u64>,
-            max_retry_interval: Option<u64>,
+            options: SseOptions<${params ?? 'EmptyArriModel'}, ${response ?? 'EmptyArriModel'}>,
         ) where
-            OnEvent: FnMut(SseEvent<${response ? response : 'EmptyArriModel'}>, &mut SseController) + std::marker::Send + std::marker::Sync,
+            OnEvent: FnMut(SseEvent<${response ? response : 'EmptyArriModel'}>, &mut SseController) -> OnEventOutput + std::marker::Send + std::marker::Sync,
+            OnEventOutput: SseHandlerOutput,
         {
+            ${schema.isDeprecated ? `self._config.deprecation.procedure_called(${procedureName}::PATH);` : ''}
             parsed_arri_sse_request(
                 ArriParsedSseRequestOptions {
                     client: &self._config.http_client,
-                    url: format!("{}${schema.path}", &self._config.base_url),
-                    method: reqwest::Method::${schema.method.toUpperCase()},
+                    url: format!("{}{}", &self._config.base_url, ${procedureName}::PATH),
+                    method: ${procedureName}::METHOD,
                     headers: self._config.headers.clone(),
-                    client_version: "${context.clientVersion}".to_string(),
-                    max_retry_count,
-                    max_retry_interval,
+                    client_version: ${procedureName}::CLIENT_VERSION.to_string(),
+                    deprecation: Some(self._config.deprecation.reporter(${procedureName}::PATH)),
+                    version_mismatch: Some(self._config.version_mismatch.clone()),
+                    options,
                 },
                 ${params ? `Some(params)` : 'None::<EmptyArriModel>'},
                 on_event,
@@ -82,29 +86,268 @@ export function rustHttpRpcFromSchema(
         &self,
         ${params ? `params: ${params},` : ''}
     ) -> Result<${response ?? '()'}, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!("{}${schema.path}", &self._config.base_url),
-                method: reqwest::Method::${schema.method.toUpperCase()},
-                headers: self._config.headers.clone(),
-                client_version: "${context.clientVersion}".to_string(),
-            },
-            ${params ? `Some(params)` : 'None::<EmptyArriModel>'},
-            |body| ${response ? `return ${response}::from_json_string(body)` : '{}'},
-        )
-        .await
+        arri_procedure_request::<${procedureName}>(&self._config, ${params ? 'params' : 'EmptyArriModel {}'}).await
     }`;
 }
 
+export function rustProcedureTypeFromSchema(
+    schema: RpcDefinition,
+    context: GeneratorContext,
+): string {
+    if (schema.transport === 'ws') {
+        return rustWsProcedureTypeFromSchema(schema, context);
+    }
+    if (schema.transport !== 'http') {
+        return '';
+    }
+    const procedureName = getProcedureTypeName(context.instancePath, context);
+    const params = schema.params
+        ? context.typeNamePrefix + validRustName(schema.params)
+        : undefined;
+    const response = schema.response
+        ? context.typeNamePrefix + validRustName(schema.response)
+        : undefined;
+    let leading = '';
+    if (schema.description) {
+        leading += formatDescriptionComment(schema.description);
+        leading += '\n';
+    }
+    return `${leading}#[derive(Clone, Copy, Debug)]
+pub struct ${procedureName};
+
+impl ArriProcedure for ${procedureName} {
+    type Params = ${params ?? 'EmptyArriModel'};
+    type Response = ${response ?? '()'};
+    const PATH: &'static str = "${schema.path}";
+    const METHOD: reqwest::Method = reqwest::Method::${schema.method.toUpperCase()};
+    const IS_EVENT_STREAM: bool = ${schema.isEventStream === true};
+    const IS_DEPRECATED: bool = ${schema.isDeprecated === true};
+    const CLIENT_VERSION: &'static str = "${context.clientVersion}";
+
+    fn parse_response(${response ? 'body' : '_'}: String) -> Self::Response {
+        ${response ? `${response}::from_json_string(body)` : ''}
+    }
+}`;
+}
+
+function rustWsProcedureTypeFromSchema(
+    schema: WsRpcDefinition,
+    context: GeneratorContext,
+): string {
+    const procedureName = getProcedureTypeName(context.instancePath, context);
+    const params = schema.params
+        ? context.typeNamePrefix + validRustName(schema.params)
+        : 'EmptyArriModel';
+    const response = schema.response
+        ? context.typeNamePrefix + validRustName(schema.response)
+        : 'EmptyArriModel';
+    let leading = '';
+    if (schema.description) {
+        leading += formatDescriptionComment(schema.description);
+        leading += '\n';
+    }
+    return `${leading}#[derive(Clone, Copy, Debug)]
+pub struct ${procedureName};
+
+impl ArriWsProcedure for ${procedureName} {
+    type Params = ${params};
+    type Response = ${response};
+    const PATH: &'static str = "${schema.path}";
+    const IS_DEPRECATED: bool = ${schema.isDeprecated === true};
+    const CLIENT_VERSION: &'static str = "${context.clientVersion}";
+}`;
+}
+
 export function rustWsRpcFromSchema(
     schema: WsRpcDefinition,
     context: GeneratorContext,
 ): string {
-    console.warn(
-        `[rust-codegen] WS RPCs are not supported at this time. Skipping ${context.instancePath}.`,
-    );
-    return '';
+    const functionName = getFunctionName(context.instancePath);
+    let leading = '';
+    if (schema.description) {
+        leading += formatDescriptionComment(schema.description);
+        leading += '\n';
+    }
+    if (schema.isDeprecated) {
+        leading += '#[deprecated]\n';
+    }
+    const params = schema.params
+        ? context.typeNamePrefix + validRustName(schema.params)
+        : 'EmptyArriModel';
+    const response = schema.response
+        ? context.typeNamePrefix + validRustName(schema.response)
+        : 'EmptyArriModel';
+    const procedureName = getProcedureTypeName(context.instancePath, context);
+    return `${leading}pub async fn ${functionName}(
+        &self,
+        options: WsOptions,
+    ) -> Result<WsConnection<${params}, ${response}>, ArriError> {
+        ${schema.isDeprecated ? `self._config.deprecation.procedure_called(${procedureName}::PATH);` : ''}
+        arri_ws_request(ArriParsedWsRequestOptions {
+            url: format!("{}{}", &self._config.base_url, ${procedureName}::PATH),
+            headers: self._config.headers.clone(),
+            client_version: ${procedureName}::CLIENT_VERSION.to_string(),
+            deprecation: Some(self._config.deprecation.reporter(${procedureName}::PATH)),
+            version_mismatch: Some(self._config.version_mismatch.clone()),
+            options,
+        })
+        .await
+    }`;
+}
+
+export function rustProcedureInfoFromSchema(
+    schema: RpcDefinition,
+    context: GeneratorContext,
+): string {
+    if (schema.transport !== 'http' && schema.transport !== 'ws') {
+        return '';
+    }
+    const name = context.rootService
+        ? `${context.rootService}.${context.instancePath}`
+        : context.instancePath;
+    const isHttp = schema.transport === 'http';
+    return `ProcedureInfo {
+            name: "${name}",
+            path: "${schema.path}",
+            method: ${isHttp ? `Some(reqwest::Method::${schema.method.toUpperCase()})` : 'None'},
+            transport: ProcedureTransport::${isHttp ? 'Http' : 'Ws'},
+            is_event_stream: ${isHttp && schema.isEventStream === true},
+            is_deprecated: ${schema.isDeprecated === true},
+            description: ${schema.description ? `Some(${rustStringLiteral(schema.description)})` : 'None'},
+            params: ${schema.params ? `Some("${schema.params}")` : 'None'},
+            response: ${schema.response ? `Some("${schema.response}")` : 'None'},
+        }`;
+}
+
+export interface RustRpcApi {
+    leading: string;
+    signature: string;
+    clientCall: string;
+    mockCall: string;
+    mockField: string;
+    mockInit: string;
+}
+
+/**
+ * The parts needed to add a procedure to the generated service trait and mock
+ */
+export function rustRpcApiFromSchema(
+    schema: RpcDefinition,
+    context: GeneratorContext,
+    serviceName: string,
+): RustRpcApi | undefined {
+    if (schema.transport !== 'http' && schema.transport !== 'ws') {
+        return undefined;
+    }
+    const functionName = getFunctionName(context.instancePath);
+    let leading = '';
+    if (schema.description) {
+        leading += formatDescriptionComment(schema.description);
+        leading += '\n';
+    }
+    if (schema.isDeprecated) {
+        leading += '#[deprecated]\n';
+    }
+    const params = schema.params
+        ? context.typeNamePrefix + validRustName(schema.params)
+        : undefined;
+    const response = schema.response
+        ? context.typeNamePrefix + validRustName(schema.response)
+        : undefined;
+    if (schema.transport === 'ws') {
+        const wsParams = params ?? 'EmptyArriModel';
+        const wsResponse = response ?? 'EmptyArriModel';
+        return {
+            leading,
+            signature: `fn ${functionName}(
+        &self,
+        options: WsOptions,
+    ) -> impl std::future::Future<Output = Result<WsConnection<${wsParams}, ${wsResponse}>, ArriError>> + std::marker::Send`,
+            clientCall: `${serviceName}::${functionName}(self, options)`,
+            mockCall: `self.${functionName}.call(options)`,
+            mockField: `pub ${functionName}: MockWsProcedure<${wsParams}, ${wsResponse}>,`,
+            mockInit: `${functionName}: MockWsProcedure::new("${schema.path}"),`,
+        };
+    }
+    if (schema.isEventStream) {
+        const message = response ?? 'EmptyArriModel';
+        return {
+            leading,
+            signature: `fn ${functionName}<OnEvent, OnEventOutput>(
+        &self,
+        ${params ? `params: ${params},` : ''}
+        on_event: &mut OnEvent,
+        options: SseOptions<${params ?? 'EmptyArriModel'}, ${message}>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<${message}>, &mut SseController) -> OnEventOutput + std::marker::Send + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput`,
+            clientCall: `${serviceName}::${functionName}(self, ${params ? 'params, ' : ''}on_event, options)`,
+            mockCall: `self.${functionName}.call(${params ? 'params' : 'EmptyArriModel {}'}, on_event)`,
+            mockField: `pub ${functionName}: MockEventStream<${params ?? 'EmptyArriModel'}, ${message}>,`,
+            mockInit: `${functionName}: MockEventStream::new("${schema.path}"),`,
+        };
+    }
+    return {
+        leading,
+        signature: `fn ${functionName}(
+        &self,
+        ${params ? `params: ${params},` : ''}
+    ) -> impl std::future::Future<Output = Result<${response ?? '()'}, ArriError>> + std::marker::Send`,
+        clientCall: `${serviceName}::${functionName}(self, ${params ? 'params' : ''})`,
+        mockCall: `self.${functionName}.call(${params ? 'params' : 'EmptyArriModel {}'})`,
+        mockField: `pub ${functionName}: MockProcedure<${params ?? 'EmptyArriModel'}, ${response ?? '()'}>,`,
+        mockInit: `${functionName}: MockProcedure::new("${schema.path}"),`,
+    };
+}
+
+/**
+ * Generates the `{serviceName}Api` trait, implements it for the service
+ * and generates a `{serviceName}Mock` that implements it as well
+ */
+export function rustServiceApiFromParts(
+    serviceName: string,
+    rpcs: RustRpcApi[],
+    subServices: { key: string; name: string }[],
+): string {
+    const traitName = `${serviceName}Api`;
+    const mockName = `${serviceName}Mock`;
+    const subServiceTypes = subServices.map((service) => ({
+        ...service,
+        typeName: validRustName(service.key),
+    }));
+    return `pub trait ${traitName} {
+${subServiceTypes.map((service) => `    type ${service.typeName}: ${service.name}Api;\n    fn ${service.key}(&self) -> &Self::${service.typeName};`).join('\n')}
+${rpcs.map((rpc) => `${rpc.leading}${rpc.signature};`).join('\n')}
+}
+
+impl ${traitName} for ${serviceName} {
+${subServiceTypes.map((service) => `    type ${service.typeName} = ${service.name};\n    fn ${service.key}(&self) -> &Self::${service.typeName} {\n        &self.${service.key}\n    }`).join('\n')}
+${rpcs.map((rpc) => `${rpc.signature} {\n        ${rpc.clientCall}\n    }`).join('\n')}
+}
+
+#[cfg(feature = "testing")]
+#[derive(Clone)]
+pub struct ${mockName} {
+${rpcs.map((rpc) => `    ${rpc.mockField}`).join('\n')}
+${subServices.map((service) => `    pub ${service.key}: ${service.name}Mock,`).join('\n')}
+}
+
+#[cfg(feature = "testing")]
+impl Default for ${mockName} {
+    fn default() -> Self {
+        Self {
+${rpcs.map((rpc) => `            ${rpc.mockInit}`).join('\n')}
+${subServices.map((service) => `            ${service.key}: ${service.name}Mock::default(),`).join('\n')}
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+impl ${traitName} for ${mockName} {
+${subServiceTypes.map((service) => `    type ${service.typeName} = ${service.name}Mock;\n    fn ${service.key}(&self) -> &Self::${service.typeName} {\n        &self.${service.key}\n    }`).join('\n')}
+${rpcs.map((rpc) => `${rpc.signature} {\n        ${rpc.mockCall}\n    }`).join('\n')}
+}`;
 }
 
 export function getFunctionName(instancePath: string): string {
@@ -122,14 +365,27 @@ export function getServiceName(
     return validRustName(`${context.clientName}_${name}_Service`);
 }
 
+export function getProcedureTypeName(
+    instancePath: string,
+    context: GeneratorContext,
+): string {
+    assert(instancePath.length > 0);
+    const name = instancePath.split('.').join('_');
+    return validRustName(`${context.clientName}_${name}_Procedure`);
+}
+
 export function rustServiceFromSchema(
     schema: ServiceDefinition,
     context: GeneratorContext,
-): { name: string; content: string } {
+): { name: string; content: string; procedures: string[] } {
     const serviceName = getServiceName(context.instancePath, context);
+    const procedures: string[] = [];
+    const subServiceProcedures: string[] = [];
     const subServices: { key: string; name: string }[] = [];
     const subServiceContent: string[] = [];
     const rpcParts: string[] = [];
+    const procedureTypeParts: string[] = [];
+    const rpcApis: RustRpcApi[] = [];
     for (const key of Object.keys(schema)) {
         const subSchema = schema[key]!;
         if (isServiceDefinition(subSchema)) {
@@ -148,11 +404,12 @@ export function rustServiceFromSchema(
                     name: subService.name,
                 });
                 subServiceContent.push(subService.content);
+                subServiceProcedures.push(...subService.procedures);
             }
             continue;
         }
         if (isRpcDefinition(subSchema)) {
-            const rpc = rustRpcFromSchema(subSchema, {
+            const rpcContext: GeneratorContext = {
                 clientVersion: context.clientVersion,
                 clientName: context.clientName,
                 typeNamePrefix: context.typeNamePrefix,
@@ -160,19 +417,43 @@ export function rustServiceFromSchema(
                 schemaPath: `${context.schemaPath}.${key}`,
                 generatedTypes: context.generatedTypes,
                 rootService: context.rootService,
-            });
+            };
+            const rpc = rustRpcFromSchema(subSchema, rpcContext);
             if (rpc) {
                 rpcParts.push(rpc);
             }
+            const procedureType = rustProcedureTypeFromSchema(
+                subSchema,
+                rpcContext,
+            );
+            if (procedureType) {
+                procedureTypeParts.push(procedureType);
+            }
+            const rpcApi = rustRpcApiFromSchema(
+                subSchema,
+                rpcContext,
+                serviceName,
+            );
+            if (rpcApi) {
+                rpcApis.push(rpcApi);
+            }
+            const procedureInfo = rustProcedureInfoFromSchema(
+                subSchema,
+                rpcContext,
+            );
+            if (procedureInfo) {
+                procedures.push(procedureInfo);
+            }
             continue;
         }
         throw new Error(
             `[rust-codegen] Invalid schema at /procedures/${context.instancePath}.`,
         );
     }
-    const paramSuffix = subServices.length > 0 ? '.clone()' : '';
+    procedures.push(...subServiceProcedures);
     return {
         name: serviceName,
+        procedures,
         content: `#[derive(Clone)]
 pub struct ${serviceName} {
     _config: InternalArriClientConfig,
@@ -181,10 +462,7 @@ ${subServices.map((service) => `    pub ${service.key}: ${service.name},`).join(
 
 impl ArriClientService for ${serviceName} {
     fn create(config: ArriClientConfig) -> Self {
-        Self {
-            _config: InternalArriClientConfig::from(config${paramSuffix}),
-${subServices.map((service, index) => `            ${service.key}: ${service.name}::create(config${index === subServices.length - 1 ? '' : '.clone()'}),`).join('\n')}
-        }
+        Self::_from_config(InternalArriClientConfig::from(config))
     }
     fn update_headers(&self, headers: HashMap<&'static str, String>) {
         let mut unwrapped_headers = self._config.headers.write().unwrap();
@@ -193,10 +471,26 @@ ${subServices.map((service, index) => `        self.${service.key}.update_header
     }
 }
 
+impl ArriProcedureClient for ${serviceName} {
+    fn procedure_config(&self) -> &InternalArriClientConfig {
+        &self._config
+    }
+}
+
 impl ${serviceName} {
+    fn _from_config(config: InternalArriClientConfig) -> Self {
+        Self {
+${subServices.map((service) => `            ${service.key}: ${service.name}::_from_config(config.child()),`).join('\n')}
+            _config: config,
+        }
+    }
 ${rpcParts.join('\n')}
 }
 
+${procedureTypeParts.join('\n\n')}
+
+${rustServiceApiFromParts(serviceName, rpcApis, subServices)}
+
 ${subServiceContent.join('\n\n')}
 `,
     };
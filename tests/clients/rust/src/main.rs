@@ -9,7 +9,7 @@ mod tests {
     use arri_client::{
         chrono::{DateTime, Utc},
         reqwest, serde_json,
-        sse::SseEvent,
+        sse::{SseBufferOptions, SseCloseReason, SseEvent, SseOptions, SseOverflowPolicy},
         ArriClientConfig, ArriClientService,
     };
     use rand::{self, Rng};
@@ -538,10 +538,11 @@ mod tests {
                     SseEvent::Open => {
                         open_count += 1;
                     }
-                    SseEvent::Close => {}
+                    SseEvent::Custom(_) => {}
+                    SseEvent::Reconnecting { .. } => {}
+                    SseEvent::Close(_) => {}
                 },
-                None,
-                None,
+                SseOptions::default(),
             )
             .await;
         assert_eq!(open_count, 1);
@@ -549,6 +550,49 @@ mod tests {
         assert_eq!(error_count, 0);
     }
 
+    #[tokio::test]
+    async fn stream_messages_async_handler_test() {
+        let client = TestClient::create(get_config(headers()));
+        let msg_count = Arc::new(Mutex::new(0));
+        let open_count = Arc::new(Mutex::new(0));
+        client
+            .tests
+            .stream_messages(
+                ChatMessageParams {
+                    channel_id: "12345".to_string(),
+                },
+                &mut |event, controller| {
+                    let controller = controller.clone();
+                    let msg_count = Arc::clone(&msg_count);
+                    let open_count = Arc::clone(&open_count);
+                    Box::pin(async move {
+                        // simulate a slow handler
+                        tokio::time::sleep(Duration::from_millis(5)).await;
+                        match event {
+                            SseEvent::Message(_) => {
+                                let mut msg_count = msg_count.lock().unwrap();
+                                *msg_count += 1;
+                                if *msg_count >= 20 {
+                                    controller.abort();
+                                }
+                            }
+                            SseEvent::Open => {
+                                *open_count.lock().unwrap() += 1;
+                            }
+                            _ => {}
+                        }
+                    })
+                },
+                SseOptions {
+                    buffer: Some(SseBufferOptions::new(5, SseOverflowPolicy::Block)),
+                    ..Default::default()
+                },
+            )
+            .await;
+        assert_eq!(*open_count.lock().unwrap(), 1);
+        assert_eq!(*msg_count.lock().unwrap(), 20);
+    }
+
     #[tokio::test]
     async fn stream_messages_multiple_threads_test() {
         let msg_count = Arc::new(Mutex::new(0));
@@ -582,10 +626,11 @@ mod tests {
                                 let mut open_count = open_count_ref.lock().unwrap();
                                 *open_count += 1;
                             }
-                            SseEvent::Close => {}
+                            SseEvent::Custom(_) => {}
+                            SseEvent::Reconnecting { .. } => {}
+                            SseEvent::Close(_) => {}
                         },
-                        None,
-                        None,
+                        SseOptions::default(),
                     )
                     .await;
             });
@@ -607,6 +652,8 @@ mod tests {
         let client = TestClient::create(config);
         let mut open_count = 0;
         let mut msg_count = 0;
+        let mut reconnect_count = 0;
+        let mut close_reason = None;
         client
             .tests
             .stream_auto_reconnect(
@@ -622,14 +669,23 @@ mod tests {
                             controller.abort();
                         }
                     }
-                    SseEvent::Close => {}
+                    SseEvent::Custom(_) => {}
+                    SseEvent::Reconnecting { attempt, .. } => {
+                        // the attempt count resets after every successful connection
+                        assert_eq!(attempt, 1);
+                        reconnect_count += 1;
+                    }
+                    SseEvent::Close(reason) => {
+                        close_reason = Some(reason);
+                    }
                 },
-                None,
-                None,
+                SseOptions::default(),
             )
             .await;
         assert_eq!(open_count, 5);
+        assert_eq!(reconnect_count, 4);
         assert!(msg_count > 10);
+        assert_eq!(close_reason, Some(SseCloseReason::Aborted));
     }
 
     #[tokio::test]
@@ -662,10 +718,11 @@ mod tests {
                     SseEvent::Open => {
                         open_count += 1;
                     }
-                    SseEvent::Close => {}
+                    SseEvent::Custom(_) => {}
+                    SseEvent::Reconnecting { .. } => {}
+                    SseEvent::Close(_) => {}
                 },
-                None,
-                None,
+                SseOptions::default(),
             )
             .await;
         assert_eq!(error_count, 5);
@@ -694,10 +751,11 @@ mod tests {
                     SseEvent::Open => {
                         open_count += 1;
                     }
-                    SseEvent::Close => {}
+                    SseEvent::Custom(_) => {}
+                    SseEvent::Reconnecting { .. } => {}
+                    SseEvent::Close(_) => {}
                 },
-                None,
-                None,
+                SseOptions::default(),
             )
             .await;
         assert_eq!(open_count, 1);
@@ -743,10 +801,11 @@ mod tests {
                         );
                         client.update_headers(headers.clone());
                     }
-                    SseEvent::Close => {}
+                    SseEvent::Custom(_) => {}
+                    SseEvent::Reconnecting { .. } => {}
+                    SseEvent::Close(_) => {}
                 },
-                None,
-                None,
+                SseOptions::default(),
             )
             .await;
         assert_eq!(error_count, 0);
@@ -759,6 +818,7 @@ mod tests {
         let client = TestClient::create(get_config(headers()));
         let mut msg_count = 0;
         let mut open_count = 0;
+        let mut close_reasons = Vec::new();
         client
             .tests
             .stream_ten_events_then_end(
@@ -770,13 +830,15 @@ mod tests {
                     SseEvent::Open => {
                         open_count += 1;
                     }
-                    SseEvent::Close => {}
+                    SseEvent::Custom(_) => {}
+                    SseEvent::Reconnecting { .. } => {}
+                    SseEvent::Close(reason) => close_reasons.push(reason),
                 },
-                None,
-                None,
+                SseOptions::default(),
             )
             .await;
         assert_eq!(msg_count, 10);
         assert_eq!(open_count, 1);
+        assert_eq!(close_reasons, vec![SseCloseReason::Done]);
     }
 }
@@ -6,13 +6,23 @@
     deprecated,
     clippy::all
 )]
+#[cfg(feature = "testing")]
+use arri_client::mock::{MockEventStream, MockProcedure, MockWsProcedure};
 use arri_client::{
     chrono::{DateTime, FixedOffset},
     parsed_arri_request,
+    procedure::{
+        arri_procedure_request, ArriProcedure, ArriProcedureClient, ArriProcedureRegistry,
+        ArriWsProcedure, ProcedureInfo, ProcedureTransport,
+    },
     reqwest::{self, Request},
     serde_json::{self, Map},
-    sse::{parsed_arri_sse_request, ArriParsedSseRequestOptions, SseController, SseEvent},
+    sse::{
+        parsed_arri_sse_request, ArriParsedSseRequestOptions, SseController, SseEvent,
+        SseHandlerOutput, SseOptions,
+    },
     utils::{serialize_date_time, serialize_string},
+    ws::{arri_ws_request, ArriParsedWsRequestOptions, WsConnection, WsOptions},
     ArriClientConfig, ArriClientService, ArriEnum, ArriError, ArriModel, ArriParsedRequestOptions,
     EmptyArriModel, InternalArriClientConfig,
 };
@@ -27,11 +37,7 @@ pub struct TestClient {
 
 impl ArriClientService for TestClient {
     fn create(config: ArriClientConfig) -> Self {
-        Self {
-            _config: InternalArriClientConfig::from(config.clone()),
-            tests: TestClientTestsService::create(config.clone()),
-            users: TestClientUsersService::create(config),
-        }
+        Self::_from_config(InternalArriClientConfig::from(config))
     }
     fn update_headers(&self, headers: HashMap<&'static str, String>) {
         let mut unwrapped_headers = self._config.headers.write().unwrap();
@@ -41,7 +47,315 @@ impl ArriClientService for TestClient {
     }
 }
 
-impl TestClient {}
+impl ArriProcedureClient for TestClient {
+    fn procedure_config(&self) -> &InternalArriClientConfig {
+        &self._config
+    }
+}
+
+impl ArriProcedureRegistry for TestClient {
+    const PROCEDURES: &'static [ProcedureInfo] = &[
+        ProcedureInfo {
+            name: "tests.deprecatedRpc",
+            path: "/rpcs/tests/deprecated-rpc",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: true,
+            description: Some("If the target language supports it. Generated code should mark this procedure as deprecated."),
+            params: Some("DeprecatedRpcParams"),
+            response: None,
+        },
+        ProcedureInfo {
+            name: "tests.sendDiscriminatorWithEmptyObject",
+            path: "/rpcs/tests/send-discriminator-with-empty-object",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("DiscriminatorWithEmptyObject"),
+            response: Some("DiscriminatorWithEmptyObject"),
+        },
+        ProcedureInfo {
+            name: "tests.sendError",
+            path: "/rpcs/tests/send-error",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("SendErrorParams"),
+            response: None,
+        },
+        ProcedureInfo {
+            name: "tests.sendObject",
+            path: "/rpcs/tests/send-object",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("ObjectWithEveryType"),
+            response: Some("ObjectWithEveryType"),
+        },
+        ProcedureInfo {
+            name: "tests.sendObjectWithNullableFields",
+            path: "/rpcs/tests/send-object-with-nullable-fields",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("ObjectWithEveryNullableType"),
+            response: Some("ObjectWithEveryNullableType"),
+        },
+        ProcedureInfo {
+            name: "tests.sendObjectWithPascalCaseKeys",
+            path: "/rpcs/tests/send-object-with-pascal-case-keys",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("ObjectWithPascalCaseKeys"),
+            response: Some("ObjectWithPascalCaseKeys"),
+        },
+        ProcedureInfo {
+            name: "tests.sendObjectWithSnakeCaseKeys",
+            path: "/rpcs/tests/send-object-with-snake-case-keys",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("ObjectWithSnakeCaseKeys"),
+            response: Some("ObjectWithSnakeCaseKeys"),
+        },
+        ProcedureInfo {
+            name: "tests.sendPartialObject",
+            path: "/rpcs/tests/send-partial-object",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("ObjectWithEveryOptionalType"),
+            response: Some("ObjectWithEveryOptionalType"),
+        },
+        ProcedureInfo {
+            name: "tests.sendRecursiveObject",
+            path: "/rpcs/tests/send-recursive-object",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("RecursiveObject"),
+            response: Some("RecursiveObject"),
+        },
+        ProcedureInfo {
+            name: "tests.sendRecursiveUnion",
+            path: "/rpcs/tests/send-recursive-union",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("RecursiveUnion"),
+            response: Some("RecursiveUnion"),
+        },
+        ProcedureInfo {
+            name: "tests.streamAutoReconnect",
+            path: "/rpcs/tests/stream-auto-reconnect",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: true,
+            is_deprecated: false,
+            description: None,
+            params: Some("AutoReconnectParams"),
+            response: Some("AutoReconnectResponse"),
+        },
+        ProcedureInfo {
+            name: "tests.streamConnectionErrorTest",
+            path: "/rpcs/tests/stream-connection-error-test",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: true,
+            is_deprecated: false,
+            description: Some("This route will always return an error. The client should automatically retry with exponential backoff."),
+            params: Some("StreamConnectionErrorTestParams"),
+            response: Some("StreamConnectionErrorTestResponse"),
+        },
+        ProcedureInfo {
+            name: "tests.streamHeartbeatDetectionTest",
+            path: "/rpcs/tests/stream-heartbeat-detection-test",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: true,
+            is_deprecated: false,
+            description: Some("Sends 5 messages quickly then starts sending messages slowly (1s) after that.\nWhen heartbeat is enabled the client should keep the connection alive regardless of the slowdown of messages.\nWhen heartbeat is disabled the client should open a new connection sometime after receiving the 5th message."),
+            params: Some("StreamHeartbeatDetectionTestParams"),
+            response: Some("StreamHeartbeatDetectionTestResponse"),
+        },
+        ProcedureInfo {
+            name: "tests.streamLargeObjects",
+            path: "/rpcs/tests/stream-large-objects",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: true,
+            is_deprecated: false,
+            description: Some("Test to ensure that the client can handle receiving streams of large objects. When objects are large messages will sometimes get sent in chunks. Meaning you have to handle receiving a partial message"),
+            params: None,
+            response: Some("StreamLargeObjectsResponse"),
+        },
+        ProcedureInfo {
+            name: "tests.streamMessages",
+            path: "/rpcs/tests/stream-messages",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: true,
+            is_deprecated: false,
+            description: None,
+            params: Some("ChatMessageParams"),
+            response: Some("ChatMessage"),
+        },
+        ProcedureInfo {
+            name: "tests.streamRetryWithNewCredentials",
+            path: "/rpcs/tests/stream-retry-with-new-credentials",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: true,
+            is_deprecated: false,
+            description: None,
+            params: None,
+            response: Some("TestsStreamRetryWithNewCredentialsResponse"),
+        },
+        ProcedureInfo {
+            name: "tests.streamTenEventsThenEnd",
+            path: "/rpcs/tests/stream-ten-events-then-end",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: true,
+            is_deprecated: false,
+            description: Some("When the client receives the 'done' event, it should close the connection and NOT reconnect"),
+            params: None,
+            response: Some("ChatMessage"),
+        },
+        ProcedureInfo {
+            name: "tests.nested.emptyParamsGetRequest",
+            path: "/rpcs/tests/nested/empty-params-get-request",
+            method: Some(reqwest::Method::GET),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: None,
+            response: Some("DefaultPayload"),
+        },
+        ProcedureInfo {
+            name: "tests.nested.emptyParamsPostRequest",
+            path: "/rpcs/tests/nested/empty-params-post-request",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: None,
+            response: Some("DefaultPayload"),
+        },
+        ProcedureInfo {
+            name: "tests.nested.emptyResponseGetRequest",
+            path: "/rpcs/tests/nested/empty-response-get-request",
+            method: Some(reqwest::Method::GET),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("DefaultPayload"),
+            response: None,
+        },
+        ProcedureInfo {
+            name: "tests.nested.emptyResponsePostRequest",
+            path: "/rpcs/tests/nested/empty-response-post-request",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("DefaultPayload"),
+            response: None,
+        },
+        ProcedureInfo {
+            name: "users.watchUser",
+            path: "/rpcs/users/watch-user",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: true,
+            is_deprecated: false,
+            description: None,
+            params: Some("UsersWatchUserParams"),
+            response: Some("UsersWatchUserResponse"),
+        },
+    ];
+}
+
+impl TestClient {
+    fn _from_config(config: InternalArriClientConfig) -> Self {
+        Self {
+            tests: TestClientTestsService::_from_config(config.child()),
+            users: TestClientUsersService::_from_config(config.child()),
+            _config: config,
+        }
+    }
+}
+
+pub trait TestClientApi {
+    type Tests: TestClientTestsServiceApi;
+    fn tests(&self) -> &Self::Tests;
+    type Users: TestClientUsersServiceApi;
+    fn users(&self) -> &Self::Users;
+}
+
+impl TestClientApi for TestClient {
+    type Tests = TestClientTestsService;
+    fn tests(&self) -> &Self::Tests {
+        &self.tests
+    }
+    type Users = TestClientUsersService;
+    fn users(&self) -> &Self::Users {
+        &self.users
+    }
+}
+
+#[cfg(feature = "testing")]
+#[derive(Clone)]
+pub struct TestClientMock {
+    pub tests: TestClientTestsServiceMock,
+    pub users: TestClientUsersServiceMock,
+}
+
+#[cfg(feature = "testing")]
+impl Default for TestClientMock {
+    fn default() -> Self {
+        Self {
+            tests: TestClientTestsServiceMock::default(),
+            users: TestClientUsersServiceMock::default(),
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+impl TestClientApi for TestClientMock {
+    type Tests = TestClientTestsServiceMock;
+    fn tests(&self) -> &Self::Tests {
+        &self.tests
+    }
+    type Users = TestClientUsersServiceMock;
+    fn users(&self) -> &Self::Users {
+        &self.users
+    }
+}
 
 #[derive(Clone)]
 pub struct TestClientTestsService {
@@ -51,10 +365,7 @@ pub struct TestClientTestsService {
 
 impl ArriClientService for TestClientTestsService {
     fn create(config: ArriClientConfig) -> Self {
-        Self {
-            _config: InternalArriClientConfig::from(config.clone()),
-            nested: TestClientTestsNestedService::create(config),
-        }
+        Self::_from_config(InternalArriClientConfig::from(config))
     }
     fn update_headers(&self, headers: HashMap<&'static str, String>) {
         let mut unwrapped_headers = self._config.headers.write().unwrap();
@@ -63,91 +374,50 @@ impl ArriClientService for TestClientTestsService {
     }
 }
 
+impl ArriProcedureClient for TestClientTestsService {
+    fn procedure_config(&self) -> &InternalArriClientConfig {
+        &self._config
+    }
+}
+
 impl TestClientTestsService {
+    fn _from_config(config: InternalArriClientConfig) -> Self {
+        Self {
+            nested: TestClientTestsNestedService::_from_config(config.child()),
+            _config: config,
+        }
+    }
     /// If the target language supports it. Generated code should mark this procedure as deprecated.
     #[deprecated]
     pub async fn deprecated_rpc(&self, params: DeprecatedRpcParams) -> Result<(), ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!("{}/rpcs/tests/deprecated-rpc", &self._config.base_url),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| {},
-        )
-        .await
+        arri_procedure_request::<TestClientTestsDeprecatedRpcProcedure>(&self._config, params).await
     }
     pub async fn send_discriminator_with_empty_object(
         &self,
         params: DiscriminatorWithEmptyObject,
     ) -> Result<DiscriminatorWithEmptyObject, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/send-discriminator-with-empty-object",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| return DiscriminatorWithEmptyObject::from_json_string(body),
+        arri_procedure_request::<TestClientTestsSendDiscriminatorWithEmptyObjectProcedure>(
+            &self._config,
+            params,
         )
         .await
     }
     pub async fn send_error(&self, params: SendErrorParams) -> Result<(), ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!("{}/rpcs/tests/send-error", &self._config.base_url),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| {},
-        )
-        .await
+        arri_procedure_request::<TestClientTestsSendErrorProcedure>(&self._config, params).await
     }
     pub async fn send_object(
         &self,
         params: ObjectWithEveryType,
     ) -> Result<ObjectWithEveryType, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!("{}/rpcs/tests/send-object", &self._config.base_url),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| return ObjectWithEveryType::from_json_string(body),
-        )
-        .await
+        arri_procedure_request::<TestClientTestsSendObjectProcedure>(&self._config, params).await
     }
     pub async fn send_object_with_nullable_fields(
         &self,
         params: ObjectWithEveryNullableType,
     ) -> Result<ObjectWithEveryNullableType, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/send-object-with-nullable-fields",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| return ObjectWithEveryNullableType::from_json_string(body),
+        arri_procedure_request::<TestClientTestsSendObjectWithNullableFieldsProcedure>(
+            &self._config,
+            params,
         )
         .await
     }
@@ -155,19 +425,9 @@ impl TestClientTestsService {
         &self,
         params: ObjectWithPascalCaseKeys,
     ) -> Result<ObjectWithPascalCaseKeys, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/send-object-with-pascal-case-keys",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| return ObjectWithPascalCaseKeys::from_json_string(body),
+        arri_procedure_request::<TestClientTestsSendObjectWithPascalCaseKeysProcedure>(
+            &self._config,
+            params,
         )
         .await
     }
@@ -175,19 +435,9 @@ impl TestClientTestsService {
         &self,
         params: ObjectWithSnakeCaseKeys,
     ) -> Result<ObjectWithSnakeCaseKeys, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/send-object-with-snake-case-keys",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| return ObjectWithSnakeCaseKeys::from_json_string(body),
+        arri_procedure_request::<TestClientTestsSendObjectWithSnakeCaseKeysProcedure>(
+            &self._config,
+            params,
         )
         .await
     }
@@ -195,79 +445,53 @@ impl TestClientTestsService {
         &self,
         params: ObjectWithEveryOptionalType,
     ) -> Result<ObjectWithEveryOptionalType, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!("{}/rpcs/tests/send-partial-object", &self._config.base_url),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| return ObjectWithEveryOptionalType::from_json_string(body),
-        )
-        .await
+        arri_procedure_request::<TestClientTestsSendPartialObjectProcedure>(&self._config, params)
+            .await
     }
     pub async fn send_recursive_object(
         &self,
         params: RecursiveObject,
     ) -> Result<RecursiveObject, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/send-recursive-object",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| return RecursiveObject::from_json_string(body),
-        )
-        .await
+        arri_procedure_request::<TestClientTestsSendRecursiveObjectProcedure>(&self._config, params)
+            .await
     }
     pub async fn send_recursive_union(
         &self,
         params: RecursiveUnion,
     ) -> Result<RecursiveUnion, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!("{}/rpcs/tests/send-recursive-union", &self._config.base_url),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| return RecursiveUnion::from_json_string(body),
-        )
-        .await
+        arri_procedure_request::<TestClientTestsSendRecursiveUnionProcedure>(&self._config, params)
+            .await
     }
-    pub async fn stream_auto_reconnect<OnEvent>(
+    pub async fn stream_auto_reconnect<OnEvent, OnEventOutput>(
         &self,
         params: AutoReconnectParams,
         on_event: &mut OnEvent,
-        max_retry_count: Option<u64>,
-        max_retry_interval: Option<u64>,
+        options: SseOptions<AutoReconnectParams, AutoReconnectResponse>,
     ) where
-        OnEvent: FnMut(SseEvent<AutoReconnectResponse>, &mut SseController)
+        OnEvent: FnMut(SseEvent<AutoReconnectResponse>, &mut SseController) -> OnEventOutput
             + std::marker::Send
             + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
     {
         parsed_arri_sse_request(
             ArriParsedSseRequestOptions {
                 client: &self._config.http_client,
                 url: format!(
-                    "{}/rpcs/tests/stream-auto-reconnect",
-                    &self._config.base_url
+                    "{}{}",
+                    &self._config.base_url,
+                    TestClientTestsStreamAutoReconnectProcedure::PATH
                 ),
-                method: reqwest::Method::POST,
+                method: TestClientTestsStreamAutoReconnectProcedure::METHOD,
                 headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-                max_retry_count,
-                max_retry_interval,
+                client_version: TestClientTestsStreamAutoReconnectProcedure::CLIENT_VERSION
+                    .to_string(),
+                deprecation: Some(
+                    self._config
+                        .deprecation
+                        .reporter(TestClientTestsStreamAutoReconnectProcedure::PATH),
+                ),
+                version_mismatch: Some(self._config.version_mismatch.clone()),
+                options,
             },
             Some(params),
             on_event,
@@ -275,29 +499,36 @@ impl TestClientTestsService {
         .await;
     }
     /// This route will always return an error. The client should automatically retry with exponential backoff.
-    pub async fn stream_connection_error_test<OnEvent>(
+    pub async fn stream_connection_error_test<OnEvent, OnEventOutput>(
         &self,
         params: StreamConnectionErrorTestParams,
         on_event: &mut OnEvent,
-        max_retry_count: Option<u64>,
-        max_retry_interval: Option<u64>,
+        options: SseOptions<StreamConnectionErrorTestParams, StreamConnectionErrorTestResponse>,
     ) where
-        OnEvent: FnMut(SseEvent<StreamConnectionErrorTestResponse>, &mut SseController)
+        OnEvent: FnMut(SseEvent<StreamConnectionErrorTestResponse>, &mut SseController) -> OnEventOutput
             + std::marker::Send
             + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
     {
         parsed_arri_sse_request(
             ArriParsedSseRequestOptions {
                 client: &self._config.http_client,
                 url: format!(
-                    "{}/rpcs/tests/stream-connection-error-test",
-                    &self._config.base_url
+                    "{}{}",
+                    &self._config.base_url,
+                    TestClientTestsStreamConnectionErrorTestProcedure::PATH
                 ),
-                method: reqwest::Method::POST,
+                method: TestClientTestsStreamConnectionErrorTestProcedure::METHOD,
                 headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-                max_retry_count,
-                max_retry_interval,
+                client_version: TestClientTestsStreamConnectionErrorTestProcedure::CLIENT_VERSION
+                    .to_string(),
+                deprecation: Some(
+                    self._config
+                        .deprecation
+                        .reporter(TestClientTestsStreamConnectionErrorTestProcedure::PATH),
+                ),
+                version_mismatch: Some(self._config.version_mismatch.clone()),
+                options,
             },
             Some(params),
             on_event,
@@ -307,29 +538,33 @@ impl TestClientTestsService {
     /// Sends 5 messages quickly then starts sending messages slowly (1s) after that.
     /// When heartbeat is enabled the client should keep the connection alive regardless of the slowdown of messages.
     /// When heartbeat is disabled the client should open a new connection sometime after receiving the 5th message.
-    pub async fn stream_heartbeat_detection_test<OnEvent>(
+    pub async fn stream_heartbeat_detection_test<OnEvent, OnEventOutput>(
         &self,
         params: StreamHeartbeatDetectionTestParams,
         on_event: &mut OnEvent,
-        max_retry_count: Option<u64>,
-        max_retry_interval: Option<u64>,
+        options: SseOptions<
+            StreamHeartbeatDetectionTestParams,
+            StreamHeartbeatDetectionTestResponse,
+        >,
     ) where
-        OnEvent: FnMut(SseEvent<StreamHeartbeatDetectionTestResponse>, &mut SseController)
+        OnEvent: FnMut(
+                SseEvent<StreamHeartbeatDetectionTestResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
             + std::marker::Send
             + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
     {
         parsed_arri_sse_request(
             ArriParsedSseRequestOptions {
                 client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/stream-heartbeat-detection-test",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::POST,
+                url: format!("{}{}", &self._config.base_url, TestClientTestsStreamHeartbeatDetectionTestProcedure::PATH),
+                method: TestClientTestsStreamHeartbeatDetectionTestProcedure::METHOD,
                 headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-                max_retry_count,
-                max_retry_interval,
+                client_version: TestClientTestsStreamHeartbeatDetectionTestProcedure::CLIENT_VERSION.to_string(),
+                deprecation: Some(self._config.deprecation.reporter(TestClientTestsStreamHeartbeatDetectionTestProcedure::PATH)),
+                version_mismatch: Some(self._config.version_mismatch.clone()),
+                options,
             },
             Some(params),
             on_event,
@@ -337,81 +572,111 @@ impl TestClientTestsService {
         .await;
     }
     /// Test to ensure that the client can handle receiving streams of large objects. When objects are large messages will sometimes get sent in chunks. Meaning you have to handle receiving a partial message
-    pub async fn stream_large_objects<OnEvent>(
+    pub async fn stream_large_objects<OnEvent, OnEventOutput>(
         &self,
-
         on_event: &mut OnEvent,
-        max_retry_count: Option<u64>,
-        max_retry_interval: Option<u64>,
+        options: SseOptions<EmptyArriModel, StreamLargeObjectsResponse>,
     ) where
-        OnEvent: FnMut(SseEvent<StreamLargeObjectsResponse>, &mut SseController)
+        OnEvent: FnMut(SseEvent<StreamLargeObjectsResponse>, &mut SseController) -> OnEventOutput
             + std::marker::Send
             + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
     {
         parsed_arri_sse_request(
             ArriParsedSseRequestOptions {
                 client: &self._config.http_client,
-                url: format!("{}/rpcs/tests/stream-large-objects", &self._config.base_url),
-                method: reqwest::Method::POST,
+                url: format!(
+                    "{}{}",
+                    &self._config.base_url,
+                    TestClientTestsStreamLargeObjectsProcedure::PATH
+                ),
+                method: TestClientTestsStreamLargeObjectsProcedure::METHOD,
                 headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-                max_retry_count,
-                max_retry_interval,
+                client_version: TestClientTestsStreamLargeObjectsProcedure::CLIENT_VERSION
+                    .to_string(),
+                deprecation: Some(
+                    self._config
+                        .deprecation
+                        .reporter(TestClientTestsStreamLargeObjectsProcedure::PATH),
+                ),
+                version_mismatch: Some(self._config.version_mismatch.clone()),
+                options,
             },
             None::<EmptyArriModel>,
             on_event,
         )
         .await;
     }
-    pub async fn stream_messages<OnEvent>(
+    pub async fn stream_messages<OnEvent, OnEventOutput>(
         &self,
         params: ChatMessageParams,
         on_event: &mut OnEvent,
-        max_retry_count: Option<u64>,
-        max_retry_interval: Option<u64>,
+        options: SseOptions<ChatMessageParams, ChatMessage>,
     ) where
-        OnEvent: FnMut(SseEvent<ChatMessage>, &mut SseController)
+        OnEvent: FnMut(SseEvent<ChatMessage>, &mut SseController) -> OnEventOutput
             + std::marker::Send
             + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
     {
         parsed_arri_sse_request(
             ArriParsedSseRequestOptions {
                 client: &self._config.http_client,
-                url: format!("{}/rpcs/tests/stream-messages", &self._config.base_url),
-                method: reqwest::Method::POST,
+                url: format!(
+                    "{}{}",
+                    &self._config.base_url,
+                    TestClientTestsStreamMessagesProcedure::PATH
+                ),
+                method: TestClientTestsStreamMessagesProcedure::METHOD,
                 headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-                max_retry_count,
-                max_retry_interval,
+                client_version: TestClientTestsStreamMessagesProcedure::CLIENT_VERSION.to_string(),
+                deprecation: Some(
+                    self._config
+                        .deprecation
+                        .reporter(TestClientTestsStreamMessagesProcedure::PATH),
+                ),
+                version_mismatch: Some(self._config.version_mismatch.clone()),
+                options,
             },
             Some(params),
             on_event,
         )
         .await;
     }
-    pub async fn stream_retry_with_new_credentials<OnEvent>(
+    pub async fn stream_retry_with_new_credentials<OnEvent, OnEventOutput>(
         &self,
 
         on_event: &mut OnEvent,
-        max_retry_count: Option<u64>,
-        max_retry_interval: Option<u64>,
+
+        options: SseOptions<EmptyArriModel, TestsStreamRetryWithNewCredentialsResponse>,
     ) where
-        OnEvent: FnMut(SseEvent<TestsStreamRetryWithNewCredentialsResponse>, &mut SseController)
+        OnEvent: FnMut(
+                SseEvent<TestsStreamRetryWithNewCredentialsResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
             + std::marker::Send
             + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
     {
         parsed_arri_sse_request(
             ArriParsedSseRequestOptions {
                 client: &self._config.http_client,
                 url: format!(
-                    "{}/rpcs/tests/stream-retry-with-new-credentials",
-                    &self._config.base_url
+                    "{}{}",
+                    &self._config.base_url,
+                    TestClientTestsStreamRetryWithNewCredentialsProcedure::PATH
                 ),
-                method: reqwest::Method::POST,
+                method: TestClientTestsStreamRetryWithNewCredentialsProcedure::METHOD,
                 headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-                max_retry_count,
-                max_retry_interval,
+                client_version:
+                    TestClientTestsStreamRetryWithNewCredentialsProcedure::CLIENT_VERSION
+                        .to_string(),
+                deprecation: Some(
+                    self._config
+                        .deprecation
+                        .reporter(TestClientTestsStreamRetryWithNewCredentialsProcedure::PATH),
+                ),
+                version_mismatch: Some(self._config.version_mismatch.clone()),
+                options,
             },
             None::<EmptyArriModel>,
             on_event,
@@ -419,29 +684,37 @@ impl TestClientTestsService {
         .await;
     }
     /// When the client receives the 'done' event, it should close the connection and NOT reconnect
-    pub async fn stream_ten_events_then_end<OnEvent>(
+    pub async fn stream_ten_events_then_end<OnEvent, OnEventOutput>(
         &self,
 
         on_event: &mut OnEvent,
-        max_retry_count: Option<u64>,
-        max_retry_interval: Option<u64>,
+
+        options: SseOptions<EmptyArriModel, ChatMessage>,
     ) where
-        OnEvent: FnMut(SseEvent<ChatMessage>, &mut SseController)
+        OnEvent: FnMut(SseEvent<ChatMessage>, &mut SseController) -> OnEventOutput
             + std::marker::Send
             + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
     {
         parsed_arri_sse_request(
             ArriParsedSseRequestOptions {
                 client: &self._config.http_client,
                 url: format!(
-                    "{}/rpcs/tests/stream-ten-events-then-end",
-                    &self._config.base_url
+                    "{}{}",
+                    &self._config.base_url,
+                    TestClientTestsStreamTenEventsThenEndProcedure::PATH
                 ),
-                method: reqwest::Method::POST,
+                method: TestClientTestsStreamTenEventsThenEndProcedure::METHOD,
                 headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-                max_retry_count,
-                max_retry_interval,
+                client_version: TestClientTestsStreamTenEventsThenEndProcedure::CLIENT_VERSION
+                    .to_string(),
+                deprecation: Some(
+                    self._config
+                        .deprecation
+                        .reporter(TestClientTestsStreamTenEventsThenEndProcedure::PATH),
+                ),
+                version_mismatch: Some(self._config.version_mismatch.clone()),
+                options,
             },
             None::<EmptyArriModel>,
             on_event,
@@ -450,138 +723,1151 @@ impl TestClientTestsService {
     }
 }
 
-#[derive(Clone)]
-pub struct TestClientTestsNestedService {
-    _config: InternalArriClientConfig,
+/// If the target language supports it. Generated code should mark this procedure as deprecated.
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsDeprecatedRpcProcedure;
+
+impl ArriProcedure for TestClientTestsDeprecatedRpcProcedure {
+    type Params = DeprecatedRpcParams;
+    type Response = ();
+    const PATH: &'static str = "/rpcs/tests/deprecated-rpc";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = true;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(_: String) -> Self::Response {}
 }
 
-impl ArriClientService for TestClientTestsNestedService {
-    fn create(config: ArriClientConfig) -> Self {
-        Self {
-            _config: InternalArriClientConfig::from(config),
-        }
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsSendDiscriminatorWithEmptyObjectProcedure;
+
+impl ArriProcedure for TestClientTestsSendDiscriminatorWithEmptyObjectProcedure {
+    type Params = DiscriminatorWithEmptyObject;
+    type Response = DiscriminatorWithEmptyObject;
+    const PATH: &'static str = "/rpcs/tests/send-discriminator-with-empty-object";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        DiscriminatorWithEmptyObject::from_json_string(body)
     }
-    fn update_headers(&self, headers: HashMap<&'static str, String>) {
-        let mut unwrapped_headers = self._config.headers.write().unwrap();
-        *unwrapped_headers = headers.clone();
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsSendErrorProcedure;
+
+impl ArriProcedure for TestClientTestsSendErrorProcedure {
+    type Params = SendErrorParams;
+    type Response = ();
+    const PATH: &'static str = "/rpcs/tests/send-error";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(_: String) -> Self::Response {}
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsSendObjectProcedure;
+
+impl ArriProcedure for TestClientTestsSendObjectProcedure {
+    type Params = ObjectWithEveryType;
+    type Response = ObjectWithEveryType;
+    const PATH: &'static str = "/rpcs/tests/send-object";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        ObjectWithEveryType::from_json_string(body)
     }
 }
 
-impl TestClientTestsNestedService {
-    pub async fn empty_params_get_request(&self) -> Result<DefaultPayload, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/nested/empty-params-get-request",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::GET,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            None::<EmptyArriModel>,
-            |body| return DefaultPayload::from_json_string(body),
-        )
-        .await
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsSendObjectWithNullableFieldsProcedure;
+
+impl ArriProcedure for TestClientTestsSendObjectWithNullableFieldsProcedure {
+    type Params = ObjectWithEveryNullableType;
+    type Response = ObjectWithEveryNullableType;
+    const PATH: &'static str = "/rpcs/tests/send-object-with-nullable-fields";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        ObjectWithEveryNullableType::from_json_string(body)
     }
-    pub async fn empty_params_post_request(&self) -> Result<DefaultPayload, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/nested/empty-params-post-request",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            None::<EmptyArriModel>,
-            |body| return DefaultPayload::from_json_string(body),
-        )
-        .await
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsSendObjectWithPascalCaseKeysProcedure;
+
+impl ArriProcedure for TestClientTestsSendObjectWithPascalCaseKeysProcedure {
+    type Params = ObjectWithPascalCaseKeys;
+    type Response = ObjectWithPascalCaseKeys;
+    const PATH: &'static str = "/rpcs/tests/send-object-with-pascal-case-keys";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        ObjectWithPascalCaseKeys::from_json_string(body)
     }
-    pub async fn empty_response_get_request(
-        &self,
-        params: DefaultPayload,
-    ) -> Result<(), ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/nested/empty-response-get-request",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::GET,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| {},
-        )
-        .await
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsSendObjectWithSnakeCaseKeysProcedure;
+
+impl ArriProcedure for TestClientTestsSendObjectWithSnakeCaseKeysProcedure {
+    type Params = ObjectWithSnakeCaseKeys;
+    type Response = ObjectWithSnakeCaseKeys;
+    const PATH: &'static str = "/rpcs/tests/send-object-with-snake-case-keys";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        ObjectWithSnakeCaseKeys::from_json_string(body)
     }
-    pub async fn empty_response_post_request(
-        &self,
-        params: DefaultPayload,
-    ) -> Result<(), ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/nested/empty-response-post-request",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| {},
-        )
-        .await
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsSendPartialObjectProcedure;
+
+impl ArriProcedure for TestClientTestsSendPartialObjectProcedure {
+    type Params = ObjectWithEveryOptionalType;
+    type Response = ObjectWithEveryOptionalType;
+    const PATH: &'static str = "/rpcs/tests/send-partial-object";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        ObjectWithEveryOptionalType::from_json_string(body)
     }
 }
 
-#[derive(Clone)]
-pub struct TestClientUsersService {
-    _config: InternalArriClientConfig,
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsSendRecursiveObjectProcedure;
+
+impl ArriProcedure for TestClientTestsSendRecursiveObjectProcedure {
+    type Params = RecursiveObject;
+    type Response = RecursiveObject;
+    const PATH: &'static str = "/rpcs/tests/send-recursive-object";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        RecursiveObject::from_json_string(body)
+    }
 }
 
-impl ArriClientService for TestClientUsersService {
-    fn create(config: ArriClientConfig) -> Self {
-        Self {
-            _config: InternalArriClientConfig::from(config),
-        }
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsSendRecursiveUnionProcedure;
+
+impl ArriProcedure for TestClientTestsSendRecursiveUnionProcedure {
+    type Params = RecursiveUnion;
+    type Response = RecursiveUnion;
+    const PATH: &'static str = "/rpcs/tests/send-recursive-union";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        RecursiveUnion::from_json_string(body)
     }
-    fn update_headers(&self, headers: HashMap<&'static str, String>) {
-        let mut unwrapped_headers = self._config.headers.write().unwrap();
-        *unwrapped_headers = headers.clone();
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsStreamAutoReconnectProcedure;
+
+impl ArriProcedure for TestClientTestsStreamAutoReconnectProcedure {
+    type Params = AutoReconnectParams;
+    type Response = AutoReconnectResponse;
+    const PATH: &'static str = "/rpcs/tests/stream-auto-reconnect";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = true;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        AutoReconnectResponse::from_json_string(body)
     }
 }
 
-impl TestClientUsersService {
-    pub async fn watch_user<OnEvent>(
+/// This route will always return an error. The client should automatically retry with exponential backoff.
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsStreamConnectionErrorTestProcedure;
+
+impl ArriProcedure for TestClientTestsStreamConnectionErrorTestProcedure {
+    type Params = StreamConnectionErrorTestParams;
+    type Response = StreamConnectionErrorTestResponse;
+    const PATH: &'static str = "/rpcs/tests/stream-connection-error-test";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = true;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        StreamConnectionErrorTestResponse::from_json_string(body)
+    }
+}
+
+/// Sends 5 messages quickly then starts sending messages slowly (1s) after that.
+/// When heartbeat is enabled the client should keep the connection alive regardless of the slowdown of messages.
+/// When heartbeat is disabled the client should open a new connection sometime after receiving the 5th message.
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsStreamHeartbeatDetectionTestProcedure;
+
+impl ArriProcedure for TestClientTestsStreamHeartbeatDetectionTestProcedure {
+    type Params = StreamHeartbeatDetectionTestParams;
+    type Response = StreamHeartbeatDetectionTestResponse;
+    const PATH: &'static str = "/rpcs/tests/stream-heartbeat-detection-test";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = true;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        StreamHeartbeatDetectionTestResponse::from_json_string(body)
+    }
+}
+
+/// Test to ensure that the client can handle receiving streams of large objects. When objects are large messages will sometimes get sent in chunks. Meaning you have to handle receiving a partial message
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsStreamLargeObjectsProcedure;
+
+impl ArriProcedure for TestClientTestsStreamLargeObjectsProcedure {
+    type Params = EmptyArriModel;
+    type Response = StreamLargeObjectsResponse;
+    const PATH: &'static str = "/rpcs/tests/stream-large-objects";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = true;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        StreamLargeObjectsResponse::from_json_string(body)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsStreamMessagesProcedure;
+
+impl ArriProcedure for TestClientTestsStreamMessagesProcedure {
+    type Params = ChatMessageParams;
+    type Response = ChatMessage;
+    const PATH: &'static str = "/rpcs/tests/stream-messages";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = true;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        ChatMessage::from_json_string(body)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsStreamRetryWithNewCredentialsProcedure;
+
+impl ArriProcedure for TestClientTestsStreamRetryWithNewCredentialsProcedure {
+    type Params = EmptyArriModel;
+    type Response = TestsStreamRetryWithNewCredentialsResponse;
+    const PATH: &'static str = "/rpcs/tests/stream-retry-with-new-credentials";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = true;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        TestsStreamRetryWithNewCredentialsResponse::from_json_string(body)
+    }
+}
+
+/// When the client receives the 'done' event, it should close the connection and NOT reconnect
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsStreamTenEventsThenEndProcedure;
+
+impl ArriProcedure for TestClientTestsStreamTenEventsThenEndProcedure {
+    type Params = EmptyArriModel;
+    type Response = ChatMessage;
+    const PATH: &'static str = "/rpcs/tests/stream-ten-events-then-end";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = true;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        ChatMessage::from_json_string(body)
+    }
+}
+
+pub trait TestClientTestsServiceApi {
+    type Nested: TestClientTestsNestedServiceApi;
+    fn nested(&self) -> &Self::Nested;
+    /// If the target language supports it. Generated code should mark this procedure as deprecated.
+    #[deprecated]
+    fn deprecated_rpc(
         &self,
-        params: UsersWatchUserParams,
+        params: DeprecatedRpcParams,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send;
+    fn send_discriminator_with_empty_object(
+        &self,
+        params: DiscriminatorWithEmptyObject,
+    ) -> impl std::future::Future<Output = Result<DiscriminatorWithEmptyObject, ArriError>>
+           + std::marker::Send;
+    fn send_error(
+        &self,
+        params: SendErrorParams,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send;
+    fn send_object(
+        &self,
+        params: ObjectWithEveryType,
+    ) -> impl std::future::Future<Output = Result<ObjectWithEveryType, ArriError>> + std::marker::Send;
+    fn send_object_with_nullable_fields(
+        &self,
+        params: ObjectWithEveryNullableType,
+    ) -> impl std::future::Future<Output = Result<ObjectWithEveryNullableType, ArriError>>
+           + std::marker::Send;
+    fn send_object_with_pascal_case_keys(
+        &self,
+        params: ObjectWithPascalCaseKeys,
+    ) -> impl std::future::Future<Output = Result<ObjectWithPascalCaseKeys, ArriError>> + std::marker::Send;
+    fn send_object_with_snake_case_keys(
+        &self,
+        params: ObjectWithSnakeCaseKeys,
+    ) -> impl std::future::Future<Output = Result<ObjectWithSnakeCaseKeys, ArriError>> + std::marker::Send;
+    fn send_partial_object(
+        &self,
+        params: ObjectWithEveryOptionalType,
+    ) -> impl std::future::Future<Output = Result<ObjectWithEveryOptionalType, ArriError>>
+           + std::marker::Send;
+    fn send_recursive_object(
+        &self,
+        params: RecursiveObject,
+    ) -> impl std::future::Future<Output = Result<RecursiveObject, ArriError>> + std::marker::Send;
+    fn send_recursive_union(
+        &self,
+        params: RecursiveUnion,
+    ) -> impl std::future::Future<Output = Result<RecursiveUnion, ArriError>> + std::marker::Send;
+    fn stream_auto_reconnect<OnEvent, OnEventOutput>(
+        &self,
+        params: AutoReconnectParams,
         on_event: &mut OnEvent,
-        max_retry_count: Option<u64>,
-        max_retry_interval: Option<u64>,
-    ) where
-        OnEvent: FnMut(SseEvent<UsersWatchUserResponse>, &mut SseController)
+        options: SseOptions<AutoReconnectParams, AutoReconnectResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<AutoReconnectResponse>, &mut SseController) -> OnEventOutput
             + std::marker::Send
             + std::marker::Sync,
-    {
+        OnEventOutput: SseHandlerOutput;
+    /// This route will always return an error. The client should automatically retry with exponential backoff.
+    fn stream_connection_error_test<OnEvent, OnEventOutput>(
+        &self,
+        params: StreamConnectionErrorTestParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<StreamConnectionErrorTestParams, StreamConnectionErrorTestResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<StreamConnectionErrorTestResponse>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput;
+    /// Sends 5 messages quickly then starts sending messages slowly (1s) after that.
+    /// When heartbeat is enabled the client should keep the connection alive regardless of the slowdown of messages.
+    /// When heartbeat is disabled the client should open a new connection sometime after receiving the 5th message.
+    fn stream_heartbeat_detection_test<OnEvent, OnEventOutput>(
+        &self,
+        params: StreamHeartbeatDetectionTestParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<
+            StreamHeartbeatDetectionTestParams,
+            StreamHeartbeatDetectionTestResponse,
+        >,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(
+                SseEvent<StreamHeartbeatDetectionTestResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput;
+    /// Test to ensure that the client can handle receiving streams of large objects. When objects are large messages will sometimes get sent in chunks. Meaning you have to handle receiving a partial message
+    fn stream_large_objects<OnEvent, OnEventOutput>(
+        &self,
+        on_event: &mut OnEvent,
+        options: SseOptions<EmptyArriModel, StreamLargeObjectsResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<StreamLargeObjectsResponse>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput;
+    fn stream_messages<OnEvent, OnEventOutput>(
+        &self,
+        params: ChatMessageParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<ChatMessageParams, ChatMessage>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<ChatMessage>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput;
+    fn stream_retry_with_new_credentials<OnEvent, OnEventOutput>(
+        &self,
+        on_event: &mut OnEvent,
+        options: SseOptions<EmptyArriModel, TestsStreamRetryWithNewCredentialsResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(
+                SseEvent<TestsStreamRetryWithNewCredentialsResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput;
+    /// When the client receives the 'done' event, it should close the connection and NOT reconnect
+    fn stream_ten_events_then_end<OnEvent, OnEventOutput>(
+        &self,
+        on_event: &mut OnEvent,
+        options: SseOptions<EmptyArriModel, ChatMessage>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<ChatMessage>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput;
+}
+
+impl TestClientTestsServiceApi for TestClientTestsService {
+    type Nested = TestClientTestsNestedService;
+    fn nested(&self) -> &Self::Nested {
+        &self.nested
+    }
+    fn deprecated_rpc(
+        &self,
+        params: DeprecatedRpcParams,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send {
+        TestClientTestsService::deprecated_rpc(self, params)
+    }
+    fn send_discriminator_with_empty_object(
+        &self,
+        params: DiscriminatorWithEmptyObject,
+    ) -> impl std::future::Future<Output = Result<DiscriminatorWithEmptyObject, ArriError>>
+           + std::marker::Send {
+        TestClientTestsService::send_discriminator_with_empty_object(self, params)
+    }
+    fn send_error(
+        &self,
+        params: SendErrorParams,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send {
+        TestClientTestsService::send_error(self, params)
+    }
+    fn send_object(
+        &self,
+        params: ObjectWithEveryType,
+    ) -> impl std::future::Future<Output = Result<ObjectWithEveryType, ArriError>> + std::marker::Send
+    {
+        TestClientTestsService::send_object(self, params)
+    }
+    fn send_object_with_nullable_fields(
+        &self,
+        params: ObjectWithEveryNullableType,
+    ) -> impl std::future::Future<Output = Result<ObjectWithEveryNullableType, ArriError>>
+           + std::marker::Send {
+        TestClientTestsService::send_object_with_nullable_fields(self, params)
+    }
+    fn send_object_with_pascal_case_keys(
+        &self,
+        params: ObjectWithPascalCaseKeys,
+    ) -> impl std::future::Future<Output = Result<ObjectWithPascalCaseKeys, ArriError>> + std::marker::Send
+    {
+        TestClientTestsService::send_object_with_pascal_case_keys(self, params)
+    }
+    fn send_object_with_snake_case_keys(
+        &self,
+        params: ObjectWithSnakeCaseKeys,
+    ) -> impl std::future::Future<Output = Result<ObjectWithSnakeCaseKeys, ArriError>> + std::marker::Send
+    {
+        TestClientTestsService::send_object_with_snake_case_keys(self, params)
+    }
+    fn send_partial_object(
+        &self,
+        params: ObjectWithEveryOptionalType,
+    ) -> impl std::future::Future<Output = Result<ObjectWithEveryOptionalType, ArriError>>
+           + std::marker::Send {
+        TestClientTestsService::send_partial_object(self, params)
+    }
+    fn send_recursive_object(
+        &self,
+        params: RecursiveObject,
+    ) -> impl std::future::Future<Output = Result<RecursiveObject, ArriError>> + std::marker::Send
+    {
+        TestClientTestsService::send_recursive_object(self, params)
+    }
+    fn send_recursive_union(
+        &self,
+        params: RecursiveUnion,
+    ) -> impl std::future::Future<Output = Result<RecursiveUnion, ArriError>> + std::marker::Send
+    {
+        TestClientTestsService::send_recursive_union(self, params)
+    }
+    fn stream_auto_reconnect<OnEvent, OnEventOutput>(
+        &self,
+        params: AutoReconnectParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<AutoReconnectParams, AutoReconnectResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<AutoReconnectResponse>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        TestClientTestsService::stream_auto_reconnect(self, params, on_event, options)
+    }
+    fn stream_connection_error_test<OnEvent, OnEventOutput>(
+        &self,
+        params: StreamConnectionErrorTestParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<StreamConnectionErrorTestParams, StreamConnectionErrorTestResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<StreamConnectionErrorTestResponse>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        TestClientTestsService::stream_connection_error_test(self, params, on_event, options)
+    }
+    fn stream_heartbeat_detection_test<OnEvent, OnEventOutput>(
+        &self,
+        params: StreamHeartbeatDetectionTestParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<
+            StreamHeartbeatDetectionTestParams,
+            StreamHeartbeatDetectionTestResponse,
+        >,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(
+                SseEvent<StreamHeartbeatDetectionTestResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        TestClientTestsService::stream_heartbeat_detection_test(self, params, on_event, options)
+    }
+    fn stream_large_objects<OnEvent, OnEventOutput>(
+        &self,
+        on_event: &mut OnEvent,
+        options: SseOptions<EmptyArriModel, StreamLargeObjectsResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<StreamLargeObjectsResponse>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        TestClientTestsService::stream_large_objects(self, on_event, options)
+    }
+    fn stream_messages<OnEvent, OnEventOutput>(
+        &self,
+        params: ChatMessageParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<ChatMessageParams, ChatMessage>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<ChatMessage>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        TestClientTestsService::stream_messages(self, params, on_event, options)
+    }
+    fn stream_retry_with_new_credentials<OnEvent, OnEventOutput>(
+        &self,
+        on_event: &mut OnEvent,
+        options: SseOptions<EmptyArriModel, TestsStreamRetryWithNewCredentialsResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(
+                SseEvent<TestsStreamRetryWithNewCredentialsResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        TestClientTestsService::stream_retry_with_new_credentials(self, on_event, options)
+    }
+    fn stream_ten_events_then_end<OnEvent, OnEventOutput>(
+        &self,
+        on_event: &mut OnEvent,
+        options: SseOptions<EmptyArriModel, ChatMessage>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<ChatMessage>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        TestClientTestsService::stream_ten_events_then_end(self, on_event, options)
+    }
+}
+
+#[cfg(feature = "testing")]
+#[derive(Clone)]
+pub struct TestClientTestsServiceMock {
+    pub deprecated_rpc: MockProcedure<DeprecatedRpcParams, ()>,
+    pub send_discriminator_with_empty_object:
+        MockProcedure<DiscriminatorWithEmptyObject, DiscriminatorWithEmptyObject>,
+    pub send_error: MockProcedure<SendErrorParams, ()>,
+    pub send_object: MockProcedure<ObjectWithEveryType, ObjectWithEveryType>,
+    pub send_object_with_nullable_fields:
+        MockProcedure<ObjectWithEveryNullableType, ObjectWithEveryNullableType>,
+    pub send_object_with_pascal_case_keys:
+        MockProcedure<ObjectWithPascalCaseKeys, ObjectWithPascalCaseKeys>,
+    pub send_object_with_snake_case_keys:
+        MockProcedure<ObjectWithSnakeCaseKeys, ObjectWithSnakeCaseKeys>,
+    pub send_partial_object:
+        MockProcedure<ObjectWithEveryOptionalType, ObjectWithEveryOptionalType>,
+    pub send_recursive_object: MockProcedure<RecursiveObject, RecursiveObject>,
+    pub send_recursive_union: MockProcedure<RecursiveUnion, RecursiveUnion>,
+    pub stream_auto_reconnect: MockEventStream<AutoReconnectParams, AutoReconnectResponse>,
+    pub stream_connection_error_test:
+        MockEventStream<StreamConnectionErrorTestParams, StreamConnectionErrorTestResponse>,
+    pub stream_heartbeat_detection_test:
+        MockEventStream<StreamHeartbeatDetectionTestParams, StreamHeartbeatDetectionTestResponse>,
+    pub stream_large_objects: MockEventStream<EmptyArriModel, StreamLargeObjectsResponse>,
+    pub stream_messages: MockEventStream<ChatMessageParams, ChatMessage>,
+    pub stream_retry_with_new_credentials:
+        MockEventStream<EmptyArriModel, TestsStreamRetryWithNewCredentialsResponse>,
+    pub stream_ten_events_then_end: MockEventStream<EmptyArriModel, ChatMessage>,
+    pub nested: TestClientTestsNestedServiceMock,
+}
+
+#[cfg(feature = "testing")]
+impl Default for TestClientTestsServiceMock {
+    fn default() -> Self {
+        Self {
+            deprecated_rpc: MockProcedure::new("/rpcs/tests/deprecated-rpc"),
+            send_discriminator_with_empty_object: MockProcedure::new(
+                "/rpcs/tests/send-discriminator-with-empty-object",
+            ),
+            send_error: MockProcedure::new("/rpcs/tests/send-error"),
+            send_object: MockProcedure::new("/rpcs/tests/send-object"),
+            send_object_with_nullable_fields: MockProcedure::new(
+                "/rpcs/tests/send-object-with-nullable-fields",
+            ),
+            send_object_with_pascal_case_keys: MockProcedure::new(
+                "/rpcs/tests/send-object-with-pascal-case-keys",
+            ),
+            send_object_with_snake_case_keys: MockProcedure::new(
+                "/rpcs/tests/send-object-with-snake-case-keys",
+            ),
+            send_partial_object: MockProcedure::new("/rpcs/tests/send-partial-object"),
+            send_recursive_object: MockProcedure::new("/rpcs/tests/send-recursive-object"),
+            send_recursive_union: MockProcedure::new("/rpcs/tests/send-recursive-union"),
+            stream_auto_reconnect: MockEventStream::new("/rpcs/tests/stream-auto-reconnect"),
+            stream_connection_error_test: MockEventStream::new(
+                "/rpcs/tests/stream-connection-error-test",
+            ),
+            stream_heartbeat_detection_test: MockEventStream::new(
+                "/rpcs/tests/stream-heartbeat-detection-test",
+            ),
+            stream_large_objects: MockEventStream::new("/rpcs/tests/stream-large-objects"),
+            stream_messages: MockEventStream::new("/rpcs/tests/stream-messages"),
+            stream_retry_with_new_credentials: MockEventStream::new(
+                "/rpcs/tests/stream-retry-with-new-credentials",
+            ),
+            stream_ten_events_then_end: MockEventStream::new(
+                "/rpcs/tests/stream-ten-events-then-end",
+            ),
+            nested: TestClientTestsNestedServiceMock::default(),
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+impl TestClientTestsServiceApi for TestClientTestsServiceMock {
+    type Nested = TestClientTestsNestedServiceMock;
+    fn nested(&self) -> &Self::Nested {
+        &self.nested
+    }
+    fn deprecated_rpc(
+        &self,
+        params: DeprecatedRpcParams,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send {
+        self.deprecated_rpc.call(params)
+    }
+    fn send_discriminator_with_empty_object(
+        &self,
+        params: DiscriminatorWithEmptyObject,
+    ) -> impl std::future::Future<Output = Result<DiscriminatorWithEmptyObject, ArriError>>
+           + std::marker::Send {
+        self.send_discriminator_with_empty_object.call(params)
+    }
+    fn send_error(
+        &self,
+        params: SendErrorParams,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send {
+        self.send_error.call(params)
+    }
+    fn send_object(
+        &self,
+        params: ObjectWithEveryType,
+    ) -> impl std::future::Future<Output = Result<ObjectWithEveryType, ArriError>> + std::marker::Send
+    {
+        self.send_object.call(params)
+    }
+    fn send_object_with_nullable_fields(
+        &self,
+        params: ObjectWithEveryNullableType,
+    ) -> impl std::future::Future<Output = Result<ObjectWithEveryNullableType, ArriError>>
+           + std::marker::Send {
+        self.send_object_with_nullable_fields.call(params)
+    }
+    fn send_object_with_pascal_case_keys(
+        &self,
+        params: ObjectWithPascalCaseKeys,
+    ) -> impl std::future::Future<Output = Result<ObjectWithPascalCaseKeys, ArriError>> + std::marker::Send
+    {
+        self.send_object_with_pascal_case_keys.call(params)
+    }
+    fn send_object_with_snake_case_keys(
+        &self,
+        params: ObjectWithSnakeCaseKeys,
+    ) -> impl std::future::Future<Output = Result<ObjectWithSnakeCaseKeys, ArriError>> + std::marker::Send
+    {
+        self.send_object_with_snake_case_keys.call(params)
+    }
+    fn send_partial_object(
+        &self,
+        params: ObjectWithEveryOptionalType,
+    ) -> impl std::future::Future<Output = Result<ObjectWithEveryOptionalType, ArriError>>
+           + std::marker::Send {
+        self.send_partial_object.call(params)
+    }
+    fn send_recursive_object(
+        &self,
+        params: RecursiveObject,
+    ) -> impl std::future::Future<Output = Result<RecursiveObject, ArriError>> + std::marker::Send
+    {
+        self.send_recursive_object.call(params)
+    }
+    fn send_recursive_union(
+        &self,
+        params: RecursiveUnion,
+    ) -> impl std::future::Future<Output = Result<RecursiveUnion, ArriError>> + std::marker::Send
+    {
+        self.send_recursive_union.call(params)
+    }
+    fn stream_auto_reconnect<OnEvent, OnEventOutput>(
+        &self,
+        params: AutoReconnectParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<AutoReconnectParams, AutoReconnectResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<AutoReconnectResponse>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        self.stream_auto_reconnect.call(params, on_event)
+    }
+    fn stream_connection_error_test<OnEvent, OnEventOutput>(
+        &self,
+        params: StreamConnectionErrorTestParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<StreamConnectionErrorTestParams, StreamConnectionErrorTestResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<StreamConnectionErrorTestResponse>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        self.stream_connection_error_test.call(params, on_event)
+    }
+    fn stream_heartbeat_detection_test<OnEvent, OnEventOutput>(
+        &self,
+        params: StreamHeartbeatDetectionTestParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<
+            StreamHeartbeatDetectionTestParams,
+            StreamHeartbeatDetectionTestResponse,
+        >,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(
+                SseEvent<StreamHeartbeatDetectionTestResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        self.stream_heartbeat_detection_test.call(params, on_event)
+    }
+    fn stream_large_objects<OnEvent, OnEventOutput>(
+        &self,
+        on_event: &mut OnEvent,
+        options: SseOptions<EmptyArriModel, StreamLargeObjectsResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<StreamLargeObjectsResponse>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        self.stream_large_objects.call(EmptyArriModel {}, on_event)
+    }
+    fn stream_messages<OnEvent, OnEventOutput>(
+        &self,
+        params: ChatMessageParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<ChatMessageParams, ChatMessage>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<ChatMessage>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        self.stream_messages.call(params, on_event)
+    }
+    fn stream_retry_with_new_credentials<OnEvent, OnEventOutput>(
+        &self,
+        on_event: &mut OnEvent,
+        options: SseOptions<EmptyArriModel, TestsStreamRetryWithNewCredentialsResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(
+                SseEvent<TestsStreamRetryWithNewCredentialsResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        self.stream_retry_with_new_credentials
+            .call(EmptyArriModel {}, on_event)
+    }
+    fn stream_ten_events_then_end<OnEvent, OnEventOutput>(
+        &self,
+        on_event: &mut OnEvent,
+        options: SseOptions<EmptyArriModel, ChatMessage>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<ChatMessage>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        self.stream_ten_events_then_end
+            .call(EmptyArriModel {}, on_event)
+    }
+}
+
+#[derive(Clone)]
+pub struct TestClientTestsNestedService {
+    _config: InternalArriClientConfig,
+}
+
+impl ArriClientService for TestClientTestsNestedService {
+    fn create(config: ArriClientConfig) -> Self {
+        Self::_from_config(InternalArriClientConfig::from(config))
+    }
+    fn update_headers(&self, headers: HashMap<&'static str, String>) {
+        let mut unwrapped_headers = self._config.headers.write().unwrap();
+        *unwrapped_headers = headers.clone();
+    }
+}
+
+impl ArriProcedureClient for TestClientTestsNestedService {
+    fn procedure_config(&self) -> &InternalArriClientConfig {
+        &self._config
+    }
+}
+
+impl TestClientTestsNestedService {
+    fn _from_config(config: InternalArriClientConfig) -> Self {
+        Self { _config: config }
+    }
+    pub async fn empty_params_get_request(&self) -> Result<DefaultPayload, ArriError> {
+        arri_procedure_request::<TestClientTestsNestedEmptyParamsGetRequestProcedure>(
+            &self._config,
+            EmptyArriModel {},
+        )
+        .await
+    }
+    pub async fn empty_params_post_request(&self) -> Result<DefaultPayload, ArriError> {
+        arri_procedure_request::<TestClientTestsNestedEmptyParamsPostRequestProcedure>(
+            &self._config,
+            EmptyArriModel {},
+        )
+        .await
+    }
+    pub async fn empty_response_get_request(
+        &self,
+        params: DefaultPayload,
+    ) -> Result<(), ArriError> {
+        arri_procedure_request::<TestClientTestsNestedEmptyResponseGetRequestProcedure>(
+            &self._config,
+            params,
+        )
+        .await
+    }
+    pub async fn empty_response_post_request(
+        &self,
+        params: DefaultPayload,
+    ) -> Result<(), ArriError> {
+        arri_procedure_request::<TestClientTestsNestedEmptyResponsePostRequestProcedure>(
+            &self._config,
+            params,
+        )
+        .await
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsNestedEmptyParamsGetRequestProcedure;
+
+impl ArriProcedure for TestClientTestsNestedEmptyParamsGetRequestProcedure {
+    type Params = EmptyArriModel;
+    type Response = DefaultPayload;
+    const PATH: &'static str = "/rpcs/tests/nested/empty-params-get-request";
+    const METHOD: reqwest::Method = reqwest::Method::GET;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        DefaultPayload::from_json_string(body)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsNestedEmptyParamsPostRequestProcedure;
+
+impl ArriProcedure for TestClientTestsNestedEmptyParamsPostRequestProcedure {
+    type Params = EmptyArriModel;
+    type Response = DefaultPayload;
+    const PATH: &'static str = "/rpcs/tests/nested/empty-params-post-request";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        DefaultPayload::from_json_string(body)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsNestedEmptyResponseGetRequestProcedure;
+
+impl ArriProcedure for TestClientTestsNestedEmptyResponseGetRequestProcedure {
+    type Params = DefaultPayload;
+    type Response = ();
+    const PATH: &'static str = "/rpcs/tests/nested/empty-response-get-request";
+    const METHOD: reqwest::Method = reqwest::Method::GET;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(_: String) -> Self::Response {}
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientTestsNestedEmptyResponsePostRequestProcedure;
+
+impl ArriProcedure for TestClientTestsNestedEmptyResponsePostRequestProcedure {
+    type Params = DefaultPayload;
+    type Response = ();
+    const PATH: &'static str = "/rpcs/tests/nested/empty-response-post-request";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(_: String) -> Self::Response {}
+}
+
+pub trait TestClientTestsNestedServiceApi {
+    fn empty_params_get_request(
+        &self,
+    ) -> impl std::future::Future<Output = Result<DefaultPayload, ArriError>> + std::marker::Send;
+    fn empty_params_post_request(
+        &self,
+    ) -> impl std::future::Future<Output = Result<DefaultPayload, ArriError>> + std::marker::Send;
+    fn empty_response_get_request(
+        &self,
+        params: DefaultPayload,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send;
+    fn empty_response_post_request(
+        &self,
+        params: DefaultPayload,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send;
+}
+
+impl TestClientTestsNestedServiceApi for TestClientTestsNestedService {
+    fn empty_params_get_request(
+        &self,
+    ) -> impl std::future::Future<Output = Result<DefaultPayload, ArriError>> + std::marker::Send
+    {
+        TestClientTestsNestedService::empty_params_get_request(self)
+    }
+    fn empty_params_post_request(
+        &self,
+    ) -> impl std::future::Future<Output = Result<DefaultPayload, ArriError>> + std::marker::Send
+    {
+        TestClientTestsNestedService::empty_params_post_request(self)
+    }
+    fn empty_response_get_request(
+        &self,
+        params: DefaultPayload,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send {
+        TestClientTestsNestedService::empty_response_get_request(self, params)
+    }
+    fn empty_response_post_request(
+        &self,
+        params: DefaultPayload,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send {
+        TestClientTestsNestedService::empty_response_post_request(self, params)
+    }
+}
+
+#[cfg(feature = "testing")]
+#[derive(Clone)]
+pub struct TestClientTestsNestedServiceMock {
+    pub empty_params_get_request: MockProcedure<EmptyArriModel, DefaultPayload>,
+    pub empty_params_post_request: MockProcedure<EmptyArriModel, DefaultPayload>,
+    pub empty_response_get_request: MockProcedure<DefaultPayload, ()>,
+    pub empty_response_post_request: MockProcedure<DefaultPayload, ()>,
+}
+
+#[cfg(feature = "testing")]
+impl Default for TestClientTestsNestedServiceMock {
+    fn default() -> Self {
+        Self {
+            empty_params_get_request: MockProcedure::new(
+                "/rpcs/tests/nested/empty-params-get-request",
+            ),
+            empty_params_post_request: MockProcedure::new(
+                "/rpcs/tests/nested/empty-params-post-request",
+            ),
+            empty_response_get_request: MockProcedure::new(
+                "/rpcs/tests/nested/empty-response-get-request",
+            ),
+            empty_response_post_request: MockProcedure::new(
+                "/rpcs/tests/nested/empty-response-post-request",
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+impl TestClientTestsNestedServiceApi for TestClientTestsNestedServiceMock {
+    fn empty_params_get_request(
+        &self,
+    ) -> impl std::future::Future<Output = Result<DefaultPayload, ArriError>> + std::marker::Send
+    {
+        self.empty_params_get_request.call(EmptyArriModel {})
+    }
+    fn empty_params_post_request(
+        &self,
+    ) -> impl std::future::Future<Output = Result<DefaultPayload, ArriError>> + std::marker::Send
+    {
+        self.empty_params_post_request.call(EmptyArriModel {})
+    }
+    fn empty_response_get_request(
+        &self,
+        params: DefaultPayload,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send {
+        self.empty_response_get_request.call(params)
+    }
+    fn empty_response_post_request(
+        &self,
+        params: DefaultPayload,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send {
+        self.empty_response_post_request.call(params)
+    }
+}
+
+#[derive(Clone)]
+pub struct TestClientUsersService {
+    _config: InternalArriClientConfig,
+}
+
+impl ArriClientService for TestClientUsersService {
+    fn create(config: ArriClientConfig) -> Self {
+        Self::_from_config(InternalArriClientConfig::from(config))
+    }
+    fn update_headers(&self, headers: HashMap<&'static str, String>) {
+        let mut unwrapped_headers = self._config.headers.write().unwrap();
+        *unwrapped_headers = headers.clone();
+    }
+}
+
+impl ArriProcedureClient for TestClientUsersService {
+    fn procedure_config(&self) -> &InternalArriClientConfig {
+        &self._config
+    }
+}
+
+impl TestClientUsersService {
+    fn _from_config(config: InternalArriClientConfig) -> Self {
+        Self { _config: config }
+    }
+    pub async fn watch_user<OnEvent, OnEventOutput>(
+        &self,
+        params: UsersWatchUserParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<UsersWatchUserParams, UsersWatchUserResponse>,
+    ) where
+        OnEvent: FnMut(SseEvent<UsersWatchUserResponse>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
         parsed_arri_sse_request(
             ArriParsedSseRequestOptions {
                 client: &self._config.http_client,
-                url: format!("{}/rpcs/users/watch-user", &self._config.base_url),
-                method: reqwest::Method::POST,
+                url: format!(
+                    "{}{}",
+                    &self._config.base_url,
+                    TestClientUsersWatchUserProcedure::PATH
+                ),
+                method: TestClientUsersWatchUserProcedure::METHOD,
                 headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-                max_retry_count,
-                max_retry_interval,
+                client_version: TestClientUsersWatchUserProcedure::CLIENT_VERSION.to_string(),
+                deprecation: Some(
+                    self._config
+                        .deprecation
+                        .reporter(TestClientUsersWatchUserProcedure::PATH),
+                ),
+                version_mismatch: Some(self._config.version_mismatch.clone()),
+                options,
             },
             Some(params),
             on_event,
@@ -590,6 +1876,87 @@ impl TestClientUsersService {
     }
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientUsersWatchUserProcedure;
+
+impl ArriProcedure for TestClientUsersWatchUserProcedure {
+    type Params = UsersWatchUserParams;
+    type Response = UsersWatchUserResponse;
+    const PATH: &'static str = "/rpcs/users/watch-user";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = true;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        UsersWatchUserResponse::from_json_string(body)
+    }
+}
+
+pub trait TestClientUsersServiceApi {
+    fn watch_user<OnEvent, OnEventOutput>(
+        &self,
+        params: UsersWatchUserParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<UsersWatchUserParams, UsersWatchUserResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<UsersWatchUserResponse>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput;
+}
+
+impl TestClientUsersServiceApi for TestClientUsersService {
+    fn watch_user<OnEvent, OnEventOutput>(
+        &self,
+        params: UsersWatchUserParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<UsersWatchUserParams, UsersWatchUserResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<UsersWatchUserResponse>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        TestClientUsersService::watch_user(self, params, on_event, options)
+    }
+}
+
+#[cfg(feature = "testing")]
+#[derive(Clone)]
+pub struct TestClientUsersServiceMock {
+    pub watch_user: MockEventStream<UsersWatchUserParams, UsersWatchUserResponse>,
+}
+
+#[cfg(feature = "testing")]
+impl Default for TestClientUsersServiceMock {
+    fn default() -> Self {
+        Self {
+            watch_user: MockEventStream::new("/rpcs/users/watch-user"),
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+impl TestClientUsersServiceApi for TestClientUsersServiceMock {
+    fn watch_user<OnEvent, OnEventOutput>(
+        &self,
+        params: UsersWatchUserParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<UsersWatchUserParams, UsersWatchUserResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<UsersWatchUserResponse>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        self.watch_user.call(params, on_event)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct ManuallyAddedModel {
     pub hello: String,
@@ -6,13 +6,23 @@
     deprecated,
     clippy::all
 )]
+#[cfg(feature = "testing")]
+use arri_client::mock::{MockEventStream, MockProcedure, MockWsProcedure};
 use arri_client::{
     chrono::{DateTime, FixedOffset},
     parsed_arri_request,
+    procedure::{
+        arri_procedure_request, ArriProcedure, ArriProcedureClient, ArriProcedureRegistry,
+        ArriWsProcedure, ProcedureInfo, ProcedureTransport,
+    },
     reqwest::{self, Request},
     serde_json::{self, Map},
-    sse::{parsed_arri_sse_request, ArriParsedSseRequestOptions, SseController, SseEvent},
+    sse::{
+        parsed_arri_sse_request, ArriParsedSseRequestOptions, SseController, SseEvent,
+        SseHandlerOutput, SseOptions,
+    },
     utils::{serialize_date_time, serialize_string},
+    ws::{arri_ws_request, ArriParsedWsRequestOptions, WsConnection, WsOptions},
     ArriClientConfig, ArriClientService, ArriEnum, ArriError, ArriModel, ArriParsedRequestOptions,
     EmptyArriModel, InternalArriClientConfig,
 };
@@ -26,10 +36,7 @@ pub struct TestClientPrefixed {
 
 impl ArriClientService for TestClientPrefixed {
     fn create(config: ArriClientConfig) -> Self {
-        Self {
-            _config: InternalArriClientConfig::from(config.clone()),
-            nested: TestClientPrefixedNestedService::create(config),
-        }
+        Self::_from_config(InternalArriClientConfig::from(config))
     }
     fn update_headers(&self, headers: HashMap<&'static str, String>) {
         let mut unwrapped_headers = self._config.headers.write().unwrap();
@@ -38,91 +45,287 @@ impl ArriClientService for TestClientPrefixed {
     }
 }
 
+impl ArriProcedureClient for TestClientPrefixed {
+    fn procedure_config(&self) -> &InternalArriClientConfig {
+        &self._config
+    }
+}
+
+impl ArriProcedureRegistry for TestClientPrefixed {
+    const PROCEDURES: &'static [ProcedureInfo] = &[
+        ProcedureInfo {
+            name: "tests.deprecatedRpc",
+            path: "/rpcs/tests/deprecated-rpc",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: true,
+            description: Some("If the target language supports it. Generated code should mark this procedure as deprecated."),
+            params: Some("DeprecatedRpcParams"),
+            response: None,
+        },
+        ProcedureInfo {
+            name: "tests.sendDiscriminatorWithEmptyObject",
+            path: "/rpcs/tests/send-discriminator-with-empty-object",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("DiscriminatorWithEmptyObject"),
+            response: Some("DiscriminatorWithEmptyObject"),
+        },
+        ProcedureInfo {
+            name: "tests.sendError",
+            path: "/rpcs/tests/send-error",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("SendErrorParams"),
+            response: None,
+        },
+        ProcedureInfo {
+            name: "tests.sendObject",
+            path: "/rpcs/tests/send-object",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("ObjectWithEveryType"),
+            response: Some("ObjectWithEveryType"),
+        },
+        ProcedureInfo {
+            name: "tests.sendObjectWithNullableFields",
+            path: "/rpcs/tests/send-object-with-nullable-fields",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("ObjectWithEveryNullableType"),
+            response: Some("ObjectWithEveryNullableType"),
+        },
+        ProcedureInfo {
+            name: "tests.sendObjectWithPascalCaseKeys",
+            path: "/rpcs/tests/send-object-with-pascal-case-keys",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("ObjectWithPascalCaseKeys"),
+            response: Some("ObjectWithPascalCaseKeys"),
+        },
+        ProcedureInfo {
+            name: "tests.sendObjectWithSnakeCaseKeys",
+            path: "/rpcs/tests/send-object-with-snake-case-keys",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("ObjectWithSnakeCaseKeys"),
+            response: Some("ObjectWithSnakeCaseKeys"),
+        },
+        ProcedureInfo {
+            name: "tests.sendPartialObject",
+            path: "/rpcs/tests/send-partial-object",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("ObjectWithEveryOptionalType"),
+            response: Some("ObjectWithEveryOptionalType"),
+        },
+        ProcedureInfo {
+            name: "tests.sendRecursiveObject",
+            path: "/rpcs/tests/send-recursive-object",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("RecursiveObject"),
+            response: Some("RecursiveObject"),
+        },
+        ProcedureInfo {
+            name: "tests.sendRecursiveUnion",
+            path: "/rpcs/tests/send-recursive-union",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("RecursiveUnion"),
+            response: Some("RecursiveUnion"),
+        },
+        ProcedureInfo {
+            name: "tests.streamAutoReconnect",
+            path: "/rpcs/tests/stream-auto-reconnect",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: true,
+            is_deprecated: false,
+            description: None,
+            params: Some("AutoReconnectParams"),
+            response: Some("AutoReconnectResponse"),
+        },
+        ProcedureInfo {
+            name: "tests.streamConnectionErrorTest",
+            path: "/rpcs/tests/stream-connection-error-test",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: true,
+            is_deprecated: false,
+            description: Some("This route will always return an error. The client should automatically retry with exponential backoff."),
+            params: Some("StreamConnectionErrorTestParams"),
+            response: Some("StreamConnectionErrorTestResponse"),
+        },
+        ProcedureInfo {
+            name: "tests.streamHeartbeatDetectionTest",
+            path: "/rpcs/tests/stream-heartbeat-detection-test",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: true,
+            is_deprecated: false,
+            description: Some("Sends 5 messages quickly then starts sending messages slowly (1s) after that.\nWhen heartbeat is enabled the client should keep the connection alive regardless of the slowdown of messages.\nWhen heartbeat is disabled the client should open a new connection sometime after receiving the 5th message."),
+            params: Some("StreamHeartbeatDetectionTestParams"),
+            response: Some("StreamHeartbeatDetectionTestResponse"),
+        },
+        ProcedureInfo {
+            name: "tests.streamLargeObjects",
+            path: "/rpcs/tests/stream-large-objects",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: true,
+            is_deprecated: false,
+            description: Some("Test to ensure that the client can handle receiving streams of large objects. When objects are large messages will sometimes get sent in chunks. Meaning you have to handle receiving a partial message"),
+            params: None,
+            response: Some("StreamLargeObjectsResponse"),
+        },
+        ProcedureInfo {
+            name: "tests.streamMessages",
+            path: "/rpcs/tests/stream-messages",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: true,
+            is_deprecated: false,
+            description: None,
+            params: Some("ChatMessageParams"),
+            response: Some("ChatMessage"),
+        },
+        ProcedureInfo {
+            name: "tests.streamRetryWithNewCredentials",
+            path: "/rpcs/tests/stream-retry-with-new-credentials",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: true,
+            is_deprecated: false,
+            description: None,
+            params: None,
+            response: Some("TestsStreamRetryWithNewCredentialsResponse"),
+        },
+        ProcedureInfo {
+            name: "tests.streamTenEventsThenEnd",
+            path: "/rpcs/tests/stream-ten-events-then-end",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: true,
+            is_deprecated: false,
+            description: Some("When the client receives the 'done' event, it should close the connection and NOT reconnect"),
+            params: None,
+            response: Some("ChatMessage"),
+        },
+        ProcedureInfo {
+            name: "tests.nested.emptyParamsGetRequest",
+            path: "/rpcs/tests/nested/empty-params-get-request",
+            method: Some(reqwest::Method::GET),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: None,
+            response: Some("DefaultPayload"),
+        },
+        ProcedureInfo {
+            name: "tests.nested.emptyParamsPostRequest",
+            path: "/rpcs/tests/nested/empty-params-post-request",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: None,
+            response: Some("DefaultPayload"),
+        },
+        ProcedureInfo {
+            name: "tests.nested.emptyResponseGetRequest",
+            path: "/rpcs/tests/nested/empty-response-get-request",
+            method: Some(reqwest::Method::GET),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("DefaultPayload"),
+            response: None,
+        },
+        ProcedureInfo {
+            name: "tests.nested.emptyResponsePostRequest",
+            path: "/rpcs/tests/nested/empty-response-post-request",
+            method: Some(reqwest::Method::POST),
+            transport: ProcedureTransport::Http,
+            is_event_stream: false,
+            is_deprecated: false,
+            description: None,
+            params: Some("DefaultPayload"),
+            response: None,
+        },
+    ];
+}
+
 impl TestClientPrefixed {
+    fn _from_config(config: InternalArriClientConfig) -> Self {
+        Self {
+            nested: TestClientPrefixedNestedService::_from_config(config.child()),
+            _config: config,
+        }
+    }
     /// If the target language supports it. Generated code should mark this procedure as deprecated.
     #[deprecated]
     pub async fn deprecated_rpc(&self, params: FooDeprecatedRpcParams) -> Result<(), ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!("{}/rpcs/tests/deprecated-rpc", &self._config.base_url),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| {},
-        )
-        .await
+        arri_procedure_request::<TestClientPrefixedDeprecatedRpcProcedure>(&self._config, params)
+            .await
     }
     pub async fn send_discriminator_with_empty_object(
         &self,
         params: FooDiscriminatorWithEmptyObject,
     ) -> Result<FooDiscriminatorWithEmptyObject, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/send-discriminator-with-empty-object",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| return FooDiscriminatorWithEmptyObject::from_json_string(body),
+        arri_procedure_request::<TestClientPrefixedSendDiscriminatorWithEmptyObjectProcedure>(
+            &self._config,
+            params,
         )
         .await
     }
     pub async fn send_error(&self, params: FooSendErrorParams) -> Result<(), ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!("{}/rpcs/tests/send-error", &self._config.base_url),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| {},
-        )
-        .await
+        arri_procedure_request::<TestClientPrefixedSendErrorProcedure>(&self._config, params).await
     }
     pub async fn send_object(
         &self,
         params: FooObjectWithEveryType,
     ) -> Result<FooObjectWithEveryType, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!("{}/rpcs/tests/send-object", &self._config.base_url),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| return FooObjectWithEveryType::from_json_string(body),
-        )
-        .await
+        arri_procedure_request::<TestClientPrefixedSendObjectProcedure>(&self._config, params).await
     }
     pub async fn send_object_with_nullable_fields(
         &self,
         params: FooObjectWithEveryNullableType,
     ) -> Result<FooObjectWithEveryNullableType, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/send-object-with-nullable-fields",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| return FooObjectWithEveryNullableType::from_json_string(body),
+        arri_procedure_request::<TestClientPrefixedSendObjectWithNullableFieldsProcedure>(
+            &self._config,
+            params,
         )
         .await
     }
@@ -130,19 +333,9 @@ impl TestClientPrefixed {
         &self,
         params: FooObjectWithPascalCaseKeys,
     ) -> Result<FooObjectWithPascalCaseKeys, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/send-object-with-pascal-case-keys",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| return FooObjectWithPascalCaseKeys::from_json_string(body),
+        arri_procedure_request::<TestClientPrefixedSendObjectWithPascalCaseKeysProcedure>(
+            &self._config,
+            params,
         )
         .await
     }
@@ -150,19 +343,9 @@ impl TestClientPrefixed {
         &self,
         params: FooObjectWithSnakeCaseKeys,
     ) -> Result<FooObjectWithSnakeCaseKeys, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/send-object-with-snake-case-keys",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| return FooObjectWithSnakeCaseKeys::from_json_string(body),
+        arri_procedure_request::<TestClientPrefixedSendObjectWithSnakeCaseKeysProcedure>(
+            &self._config,
+            params,
         )
         .await
     }
@@ -170,16 +353,9 @@ impl TestClientPrefixed {
         &self,
         params: FooObjectWithEveryOptionalType,
     ) -> Result<FooObjectWithEveryOptionalType, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!("{}/rpcs/tests/send-partial-object", &self._config.base_url),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| return FooObjectWithEveryOptionalType::from_json_string(body),
+        arri_procedure_request::<TestClientPrefixedSendPartialObjectProcedure>(
+            &self._config,
+            params,
         )
         .await
     }
@@ -187,19 +363,9 @@ impl TestClientPrefixed {
         &self,
         params: FooRecursiveObject,
     ) -> Result<FooRecursiveObject, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/send-recursive-object",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| return FooRecursiveObject::from_json_string(body),
+        arri_procedure_request::<TestClientPrefixedSendRecursiveObjectProcedure>(
+            &self._config,
+            params,
         )
         .await
     }
@@ -207,42 +373,42 @@ impl TestClientPrefixed {
         &self,
         params: FooRecursiveUnion,
     ) -> Result<FooRecursiveUnion, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!("{}/rpcs/tests/send-recursive-union", &self._config.base_url),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| return FooRecursiveUnion::from_json_string(body),
+        arri_procedure_request::<TestClientPrefixedSendRecursiveUnionProcedure>(
+            &self._config,
+            params,
         )
         .await
     }
-    pub async fn stream_auto_reconnect<OnEvent>(
+    pub async fn stream_auto_reconnect<OnEvent, OnEventOutput>(
         &self,
         params: FooAutoReconnectParams,
         on_event: &mut OnEvent,
-        max_retry_count: Option<u64>,
-        max_retry_interval: Option<u64>,
+        options: SseOptions<FooAutoReconnectParams, FooAutoReconnectResponse>,
     ) where
-        OnEvent: FnMut(SseEvent<FooAutoReconnectResponse>, &mut SseController)
+        OnEvent: FnMut(SseEvent<FooAutoReconnectResponse>, &mut SseController) -> OnEventOutput
             + std::marker::Send
             + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
     {
         parsed_arri_sse_request(
             ArriParsedSseRequestOptions {
                 client: &self._config.http_client,
                 url: format!(
-                    "{}/rpcs/tests/stream-auto-reconnect",
-                    &self._config.base_url
+                    "{}{}",
+                    &self._config.base_url,
+                    TestClientPrefixedStreamAutoReconnectProcedure::PATH
                 ),
-                method: reqwest::Method::POST,
+                method: TestClientPrefixedStreamAutoReconnectProcedure::METHOD,
                 headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-                max_retry_count,
-                max_retry_interval,
+                client_version: TestClientPrefixedStreamAutoReconnectProcedure::CLIENT_VERSION
+                    .to_string(),
+                deprecation: Some(
+                    self._config
+                        .deprecation
+                        .reporter(TestClientPrefixedStreamAutoReconnectProcedure::PATH),
+                ),
+                version_mismatch: Some(self._config.version_mismatch.clone()),
+                options,
             },
             Some(params),
             on_event,
@@ -250,29 +416,33 @@ impl TestClientPrefixed {
         .await;
     }
     /// This route will always return an error. The client should automatically retry with exponential backoff.
-    pub async fn stream_connection_error_test<OnEvent>(
+    pub async fn stream_connection_error_test<OnEvent, OnEventOutput>(
         &self,
         params: FooStreamConnectionErrorTestParams,
         on_event: &mut OnEvent,
-        max_retry_count: Option<u64>,
-        max_retry_interval: Option<u64>,
+        options: SseOptions<
+            FooStreamConnectionErrorTestParams,
+            FooStreamConnectionErrorTestResponse,
+        >,
     ) where
-        OnEvent: FnMut(SseEvent<FooStreamConnectionErrorTestResponse>, &mut SseController)
+        OnEvent: FnMut(
+                SseEvent<FooStreamConnectionErrorTestResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
             + std::marker::Send
             + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
     {
         parsed_arri_sse_request(
             ArriParsedSseRequestOptions {
                 client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/stream-connection-error-test",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::POST,
+                url: format!("{}{}", &self._config.base_url, TestClientPrefixedStreamConnectionErrorTestProcedure::PATH),
+                method: TestClientPrefixedStreamConnectionErrorTestProcedure::METHOD,
                 headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-                max_retry_count,
-                max_retry_interval,
+                client_version: TestClientPrefixedStreamConnectionErrorTestProcedure::CLIENT_VERSION.to_string(),
+                deprecation: Some(self._config.deprecation.reporter(TestClientPrefixedStreamConnectionErrorTestProcedure::PATH)),
+                version_mismatch: Some(self._config.version_mismatch.clone()),
+                options,
             },
             Some(params),
             on_event,
@@ -282,29 +452,43 @@ impl TestClientPrefixed {
     /// Sends 5 messages quickly then starts sending messages slowly (1s) after that.
     /// When heartbeat is enabled the client should keep the connection alive regardless of the slowdown of messages.
     /// When heartbeat is disabled the client should open a new connection sometime after receiving the 5th message.
-    pub async fn stream_heartbeat_detection_test<OnEvent>(
+    pub async fn stream_heartbeat_detection_test<OnEvent, OnEventOutput>(
         &self,
         params: FooStreamHeartbeatDetectionTestParams,
         on_event: &mut OnEvent,
-        max_retry_count: Option<u64>,
-        max_retry_interval: Option<u64>,
+        options: SseOptions<
+            FooStreamHeartbeatDetectionTestParams,
+            FooStreamHeartbeatDetectionTestResponse,
+        >,
     ) where
-        OnEvent: FnMut(SseEvent<FooStreamHeartbeatDetectionTestResponse>, &mut SseController)
+        OnEvent: FnMut(
+                SseEvent<FooStreamHeartbeatDetectionTestResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
             + std::marker::Send
             + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
     {
         parsed_arri_sse_request(
             ArriParsedSseRequestOptions {
                 client: &self._config.http_client,
                 url: format!(
-                    "{}/rpcs/tests/stream-heartbeat-detection-test",
-                    &self._config.base_url
+                    "{}{}",
+                    &self._config.base_url,
+                    TestClientPrefixedStreamHeartbeatDetectionTestProcedure::PATH
                 ),
-                method: reqwest::Method::POST,
+                method: TestClientPrefixedStreamHeartbeatDetectionTestProcedure::METHOD,
                 headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-                max_retry_count,
-                max_retry_interval,
+                client_version:
+                    TestClientPrefixedStreamHeartbeatDetectionTestProcedure::CLIENT_VERSION
+                        .to_string(),
+                deprecation: Some(
+                    self._config
+                        .deprecation
+                        .reporter(TestClientPrefixedStreamHeartbeatDetectionTestProcedure::PATH),
+                ),
+                version_mismatch: Some(self._config.version_mismatch.clone()),
+                options,
             },
             Some(params),
             on_event,
@@ -312,81 +496,112 @@ impl TestClientPrefixed {
         .await;
     }
     /// Test to ensure that the client can handle receiving streams of large objects. When objects are large messages will sometimes get sent in chunks. Meaning you have to handle receiving a partial message
-    pub async fn stream_large_objects<OnEvent>(
+    pub async fn stream_large_objects<OnEvent, OnEventOutput>(
         &self,
-
         on_event: &mut OnEvent,
-        max_retry_count: Option<u64>,
-        max_retry_interval: Option<u64>,
+        options: SseOptions<EmptyArriModel, FooStreamLargeObjectsResponse>,
     ) where
-        OnEvent: FnMut(SseEvent<FooStreamLargeObjectsResponse>, &mut SseController)
+        OnEvent: FnMut(SseEvent<FooStreamLargeObjectsResponse>, &mut SseController) -> OnEventOutput
             + std::marker::Send
             + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
     {
         parsed_arri_sse_request(
             ArriParsedSseRequestOptions {
                 client: &self._config.http_client,
-                url: format!("{}/rpcs/tests/stream-large-objects", &self._config.base_url),
-                method: reqwest::Method::POST,
+                url: format!(
+                    "{}{}",
+                    &self._config.base_url,
+                    TestClientPrefixedStreamLargeObjectsProcedure::PATH
+                ),
+                method: TestClientPrefixedStreamLargeObjectsProcedure::METHOD,
                 headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-                max_retry_count,
-                max_retry_interval,
+                client_version: TestClientPrefixedStreamLargeObjectsProcedure::CLIENT_VERSION
+                    .to_string(),
+                deprecation: Some(
+                    self._config
+                        .deprecation
+                        .reporter(TestClientPrefixedStreamLargeObjectsProcedure::PATH),
+                ),
+                version_mismatch: Some(self._config.version_mismatch.clone()),
+                options,
             },
             None::<EmptyArriModel>,
             on_event,
         )
         .await;
     }
-    pub async fn stream_messages<OnEvent>(
+    pub async fn stream_messages<OnEvent, OnEventOutput>(
         &self,
         params: FooChatMessageParams,
         on_event: &mut OnEvent,
-        max_retry_count: Option<u64>,
-        max_retry_interval: Option<u64>,
+        options: SseOptions<FooChatMessageParams, FooChatMessage>,
     ) where
-        OnEvent: FnMut(SseEvent<FooChatMessage>, &mut SseController)
+        OnEvent: FnMut(SseEvent<FooChatMessage>, &mut SseController) -> OnEventOutput
             + std::marker::Send
             + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
     {
         parsed_arri_sse_request(
             ArriParsedSseRequestOptions {
                 client: &self._config.http_client,
-                url: format!("{}/rpcs/tests/stream-messages", &self._config.base_url),
-                method: reqwest::Method::POST,
+                url: format!(
+                    "{}{}",
+                    &self._config.base_url,
+                    TestClientPrefixedStreamMessagesProcedure::PATH
+                ),
+                method: TestClientPrefixedStreamMessagesProcedure::METHOD,
                 headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-                max_retry_count,
-                max_retry_interval,
+                client_version: TestClientPrefixedStreamMessagesProcedure::CLIENT_VERSION
+                    .to_string(),
+                deprecation: Some(
+                    self._config
+                        .deprecation
+                        .reporter(TestClientPrefixedStreamMessagesProcedure::PATH),
+                ),
+                version_mismatch: Some(self._config.version_mismatch.clone()),
+                options,
             },
             Some(params),
             on_event,
         )
         .await;
     }
-    pub async fn stream_retry_with_new_credentials<OnEvent>(
+    pub async fn stream_retry_with_new_credentials<OnEvent, OnEventOutput>(
         &self,
 
         on_event: &mut OnEvent,
-        max_retry_count: Option<u64>,
-        max_retry_interval: Option<u64>,
+
+        options: SseOptions<EmptyArriModel, FooTestsStreamRetryWithNewCredentialsResponse>,
     ) where
-        OnEvent: FnMut(SseEvent<FooTestsStreamRetryWithNewCredentialsResponse>, &mut SseController)
+        OnEvent: FnMut(
+                SseEvent<FooTestsStreamRetryWithNewCredentialsResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
             + std::marker::Send
             + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
     {
         parsed_arri_sse_request(
             ArriParsedSseRequestOptions {
                 client: &self._config.http_client,
                 url: format!(
-                    "{}/rpcs/tests/stream-retry-with-new-credentials",
-                    &self._config.base_url
+                    "{}{}",
+                    &self._config.base_url,
+                    TestClientPrefixedStreamRetryWithNewCredentialsProcedure::PATH
                 ),
-                method: reqwest::Method::POST,
+                method: TestClientPrefixedStreamRetryWithNewCredentialsProcedure::METHOD,
                 headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-                max_retry_count,
-                max_retry_interval,
+                client_version:
+                    TestClientPrefixedStreamRetryWithNewCredentialsProcedure::CLIENT_VERSION
+                        .to_string(),
+                deprecation: Some(
+                    self._config
+                        .deprecation
+                        .reporter(TestClientPrefixedStreamRetryWithNewCredentialsProcedure::PATH),
+                ),
+                version_mismatch: Some(self._config.version_mismatch.clone()),
+                options,
             },
             None::<EmptyArriModel>,
             on_event,
@@ -394,29 +609,37 @@ impl TestClientPrefixed {
         .await;
     }
     /// When the client receives the 'done' event, it should close the connection and NOT reconnect
-    pub async fn stream_ten_events_then_end<OnEvent>(
+    pub async fn stream_ten_events_then_end<OnEvent, OnEventOutput>(
         &self,
 
         on_event: &mut OnEvent,
-        max_retry_count: Option<u64>,
-        max_retry_interval: Option<u64>,
+
+        options: SseOptions<EmptyArriModel, FooChatMessage>,
     ) where
-        OnEvent: FnMut(SseEvent<FooChatMessage>, &mut SseController)
+        OnEvent: FnMut(SseEvent<FooChatMessage>, &mut SseController) -> OnEventOutput
             + std::marker::Send
             + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
     {
         parsed_arri_sse_request(
             ArriParsedSseRequestOptions {
                 client: &self._config.http_client,
                 url: format!(
-                    "{}/rpcs/tests/stream-ten-events-then-end",
-                    &self._config.base_url
+                    "{}{}",
+                    &self._config.base_url,
+                    TestClientPrefixedStreamTenEventsThenEndProcedure::PATH
                 ),
-                method: reqwest::Method::POST,
+                method: TestClientPrefixedStreamTenEventsThenEndProcedure::METHOD,
                 headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-                max_retry_count,
-                max_retry_interval,
+                client_version: TestClientPrefixedStreamTenEventsThenEndProcedure::CLIENT_VERSION
+                    .to_string(),
+                deprecation: Some(
+                    self._config
+                        .deprecation
+                        .reporter(TestClientPrefixedStreamTenEventsThenEndProcedure::PATH),
+                ),
+                version_mismatch: Some(self._config.version_mismatch.clone()),
+                options,
             },
             None::<EmptyArriModel>,
             on_event,
@@ -425,6 +648,893 @@ impl TestClientPrefixed {
     }
 }
 
+/// If the target language supports it. Generated code should mark this procedure as deprecated.
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedDeprecatedRpcProcedure;
+
+impl ArriProcedure for TestClientPrefixedDeprecatedRpcProcedure {
+    type Params = FooDeprecatedRpcParams;
+    type Response = ();
+    const PATH: &'static str = "/rpcs/tests/deprecated-rpc";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = true;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(_: String) -> Self::Response {}
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedSendDiscriminatorWithEmptyObjectProcedure;
+
+impl ArriProcedure for TestClientPrefixedSendDiscriminatorWithEmptyObjectProcedure {
+    type Params = FooDiscriminatorWithEmptyObject;
+    type Response = FooDiscriminatorWithEmptyObject;
+    const PATH: &'static str = "/rpcs/tests/send-discriminator-with-empty-object";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        FooDiscriminatorWithEmptyObject::from_json_string(body)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedSendErrorProcedure;
+
+impl ArriProcedure for TestClientPrefixedSendErrorProcedure {
+    type Params = FooSendErrorParams;
+    type Response = ();
+    const PATH: &'static str = "/rpcs/tests/send-error";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(_: String) -> Self::Response {}
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedSendObjectProcedure;
+
+impl ArriProcedure for TestClientPrefixedSendObjectProcedure {
+    type Params = FooObjectWithEveryType;
+    type Response = FooObjectWithEveryType;
+    const PATH: &'static str = "/rpcs/tests/send-object";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        FooObjectWithEveryType::from_json_string(body)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedSendObjectWithNullableFieldsProcedure;
+
+impl ArriProcedure for TestClientPrefixedSendObjectWithNullableFieldsProcedure {
+    type Params = FooObjectWithEveryNullableType;
+    type Response = FooObjectWithEveryNullableType;
+    const PATH: &'static str = "/rpcs/tests/send-object-with-nullable-fields";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        FooObjectWithEveryNullableType::from_json_string(body)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedSendObjectWithPascalCaseKeysProcedure;
+
+impl ArriProcedure for TestClientPrefixedSendObjectWithPascalCaseKeysProcedure {
+    type Params = FooObjectWithPascalCaseKeys;
+    type Response = FooObjectWithPascalCaseKeys;
+    const PATH: &'static str = "/rpcs/tests/send-object-with-pascal-case-keys";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        FooObjectWithPascalCaseKeys::from_json_string(body)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedSendObjectWithSnakeCaseKeysProcedure;
+
+impl ArriProcedure for TestClientPrefixedSendObjectWithSnakeCaseKeysProcedure {
+    type Params = FooObjectWithSnakeCaseKeys;
+    type Response = FooObjectWithSnakeCaseKeys;
+    const PATH: &'static str = "/rpcs/tests/send-object-with-snake-case-keys";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        FooObjectWithSnakeCaseKeys::from_json_string(body)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedSendPartialObjectProcedure;
+
+impl ArriProcedure for TestClientPrefixedSendPartialObjectProcedure {
+    type Params = FooObjectWithEveryOptionalType;
+    type Response = FooObjectWithEveryOptionalType;
+    const PATH: &'static str = "/rpcs/tests/send-partial-object";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        FooObjectWithEveryOptionalType::from_json_string(body)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedSendRecursiveObjectProcedure;
+
+impl ArriProcedure for TestClientPrefixedSendRecursiveObjectProcedure {
+    type Params = FooRecursiveObject;
+    type Response = FooRecursiveObject;
+    const PATH: &'static str = "/rpcs/tests/send-recursive-object";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        FooRecursiveObject::from_json_string(body)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedSendRecursiveUnionProcedure;
+
+impl ArriProcedure for TestClientPrefixedSendRecursiveUnionProcedure {
+    type Params = FooRecursiveUnion;
+    type Response = FooRecursiveUnion;
+    const PATH: &'static str = "/rpcs/tests/send-recursive-union";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        FooRecursiveUnion::from_json_string(body)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedStreamAutoReconnectProcedure;
+
+impl ArriProcedure for TestClientPrefixedStreamAutoReconnectProcedure {
+    type Params = FooAutoReconnectParams;
+    type Response = FooAutoReconnectResponse;
+    const PATH: &'static str = "/rpcs/tests/stream-auto-reconnect";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = true;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        FooAutoReconnectResponse::from_json_string(body)
+    }
+}
+
+/// This route will always return an error. The client should automatically retry with exponential backoff.
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedStreamConnectionErrorTestProcedure;
+
+impl ArriProcedure for TestClientPrefixedStreamConnectionErrorTestProcedure {
+    type Params = FooStreamConnectionErrorTestParams;
+    type Response = FooStreamConnectionErrorTestResponse;
+    const PATH: &'static str = "/rpcs/tests/stream-connection-error-test";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = true;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        FooStreamConnectionErrorTestResponse::from_json_string(body)
+    }
+}
+
+/// Sends 5 messages quickly then starts sending messages slowly (1s) after that.
+/// When heartbeat is enabled the client should keep the connection alive regardless of the slowdown of messages.
+/// When heartbeat is disabled the client should open a new connection sometime after receiving the 5th message.
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedStreamHeartbeatDetectionTestProcedure;
+
+impl ArriProcedure for TestClientPrefixedStreamHeartbeatDetectionTestProcedure {
+    type Params = FooStreamHeartbeatDetectionTestParams;
+    type Response = FooStreamHeartbeatDetectionTestResponse;
+    const PATH: &'static str = "/rpcs/tests/stream-heartbeat-detection-test";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = true;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        FooStreamHeartbeatDetectionTestResponse::from_json_string(body)
+    }
+}
+
+/// Test to ensure that the client can handle receiving streams of large objects. When objects are large messages will sometimes get sent in chunks. Meaning you have to handle receiving a partial message
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedStreamLargeObjectsProcedure;
+
+impl ArriProcedure for TestClientPrefixedStreamLargeObjectsProcedure {
+    type Params = EmptyArriModel;
+    type Response = FooStreamLargeObjectsResponse;
+    const PATH: &'static str = "/rpcs/tests/stream-large-objects";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = true;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        FooStreamLargeObjectsResponse::from_json_string(body)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedStreamMessagesProcedure;
+
+impl ArriProcedure for TestClientPrefixedStreamMessagesProcedure {
+    type Params = FooChatMessageParams;
+    type Response = FooChatMessage;
+    const PATH: &'static str = "/rpcs/tests/stream-messages";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = true;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        FooChatMessage::from_json_string(body)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedStreamRetryWithNewCredentialsProcedure;
+
+impl ArriProcedure for TestClientPrefixedStreamRetryWithNewCredentialsProcedure {
+    type Params = EmptyArriModel;
+    type Response = FooTestsStreamRetryWithNewCredentialsResponse;
+    const PATH: &'static str = "/rpcs/tests/stream-retry-with-new-credentials";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = true;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        FooTestsStreamRetryWithNewCredentialsResponse::from_json_string(body)
+    }
+}
+
+/// When the client receives the 'done' event, it should close the connection and NOT reconnect
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedStreamTenEventsThenEndProcedure;
+
+impl ArriProcedure for TestClientPrefixedStreamTenEventsThenEndProcedure {
+    type Params = EmptyArriModel;
+    type Response = FooChatMessage;
+    const PATH: &'static str = "/rpcs/tests/stream-ten-events-then-end";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = true;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        FooChatMessage::from_json_string(body)
+    }
+}
+
+pub trait TestClientPrefixedApi {
+    type Nested: TestClientPrefixedNestedServiceApi;
+    fn nested(&self) -> &Self::Nested;
+    /// If the target language supports it. Generated code should mark this procedure as deprecated.
+    #[deprecated]
+    fn deprecated_rpc(
+        &self,
+        params: FooDeprecatedRpcParams,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send;
+    fn send_discriminator_with_empty_object(
+        &self,
+        params: FooDiscriminatorWithEmptyObject,
+    ) -> impl std::future::Future<Output = Result<FooDiscriminatorWithEmptyObject, ArriError>>
+           + std::marker::Send;
+    fn send_error(
+        &self,
+        params: FooSendErrorParams,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send;
+    fn send_object(
+        &self,
+        params: FooObjectWithEveryType,
+    ) -> impl std::future::Future<Output = Result<FooObjectWithEveryType, ArriError>> + std::marker::Send;
+    fn send_object_with_nullable_fields(
+        &self,
+        params: FooObjectWithEveryNullableType,
+    ) -> impl std::future::Future<Output = Result<FooObjectWithEveryNullableType, ArriError>>
+           + std::marker::Send;
+    fn send_object_with_pascal_case_keys(
+        &self,
+        params: FooObjectWithPascalCaseKeys,
+    ) -> impl std::future::Future<Output = Result<FooObjectWithPascalCaseKeys, ArriError>>
+           + std::marker::Send;
+    fn send_object_with_snake_case_keys(
+        &self,
+        params: FooObjectWithSnakeCaseKeys,
+    ) -> impl std::future::Future<Output = Result<FooObjectWithSnakeCaseKeys, ArriError>>
+           + std::marker::Send;
+    fn send_partial_object(
+        &self,
+        params: FooObjectWithEveryOptionalType,
+    ) -> impl std::future::Future<Output = Result<FooObjectWithEveryOptionalType, ArriError>>
+           + std::marker::Send;
+    fn send_recursive_object(
+        &self,
+        params: FooRecursiveObject,
+    ) -> impl std::future::Future<Output = Result<FooRecursiveObject, ArriError>> + std::marker::Send;
+    fn send_recursive_union(
+        &self,
+        params: FooRecursiveUnion,
+    ) -> impl std::future::Future<Output = Result<FooRecursiveUnion, ArriError>> + std::marker::Send;
+    fn stream_auto_reconnect<OnEvent, OnEventOutput>(
+        &self,
+        params: FooAutoReconnectParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<FooAutoReconnectParams, FooAutoReconnectResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<FooAutoReconnectResponse>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput;
+    /// This route will always return an error. The client should automatically retry with exponential backoff.
+    fn stream_connection_error_test<OnEvent, OnEventOutput>(
+        &self,
+        params: FooStreamConnectionErrorTestParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<
+            FooStreamConnectionErrorTestParams,
+            FooStreamConnectionErrorTestResponse,
+        >,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(
+                SseEvent<FooStreamConnectionErrorTestResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput;
+    /// Sends 5 messages quickly then starts sending messages slowly (1s) after that.
+    /// When heartbeat is enabled the client should keep the connection alive regardless of the slowdown of messages.
+    /// When heartbeat is disabled the client should open a new connection sometime after receiving the 5th message.
+    fn stream_heartbeat_detection_test<OnEvent, OnEventOutput>(
+        &self,
+        params: FooStreamHeartbeatDetectionTestParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<
+            FooStreamHeartbeatDetectionTestParams,
+            FooStreamHeartbeatDetectionTestResponse,
+        >,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(
+                SseEvent<FooStreamHeartbeatDetectionTestResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput;
+    /// Test to ensure that the client can handle receiving streams of large objects. When objects are large messages will sometimes get sent in chunks. Meaning you have to handle receiving a partial message
+    fn stream_large_objects<OnEvent, OnEventOutput>(
+        &self,
+        on_event: &mut OnEvent,
+        options: SseOptions<EmptyArriModel, FooStreamLargeObjectsResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<FooStreamLargeObjectsResponse>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput;
+    fn stream_messages<OnEvent, OnEventOutput>(
+        &self,
+        params: FooChatMessageParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<FooChatMessageParams, FooChatMessage>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<FooChatMessage>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput;
+    fn stream_retry_with_new_credentials<OnEvent, OnEventOutput>(
+        &self,
+        on_event: &mut OnEvent,
+        options: SseOptions<EmptyArriModel, FooTestsStreamRetryWithNewCredentialsResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(
+                SseEvent<FooTestsStreamRetryWithNewCredentialsResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput;
+    /// When the client receives the 'done' event, it should close the connection and NOT reconnect
+    fn stream_ten_events_then_end<OnEvent, OnEventOutput>(
+        &self,
+        on_event: &mut OnEvent,
+        options: SseOptions<EmptyArriModel, FooChatMessage>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<FooChatMessage>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput;
+}
+
+impl TestClientPrefixedApi for TestClientPrefixed {
+    type Nested = TestClientPrefixedNestedService;
+    fn nested(&self) -> &Self::Nested {
+        &self.nested
+    }
+    fn deprecated_rpc(
+        &self,
+        params: FooDeprecatedRpcParams,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send {
+        TestClientPrefixed::deprecated_rpc(self, params)
+    }
+    fn send_discriminator_with_empty_object(
+        &self,
+        params: FooDiscriminatorWithEmptyObject,
+    ) -> impl std::future::Future<Output = Result<FooDiscriminatorWithEmptyObject, ArriError>>
+           + std::marker::Send {
+        TestClientPrefixed::send_discriminator_with_empty_object(self, params)
+    }
+    fn send_error(
+        &self,
+        params: FooSendErrorParams,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send {
+        TestClientPrefixed::send_error(self, params)
+    }
+    fn send_object(
+        &self,
+        params: FooObjectWithEveryType,
+    ) -> impl std::future::Future<Output = Result<FooObjectWithEveryType, ArriError>> + std::marker::Send
+    {
+        TestClientPrefixed::send_object(self, params)
+    }
+    fn send_object_with_nullable_fields(
+        &self,
+        params: FooObjectWithEveryNullableType,
+    ) -> impl std::future::Future<Output = Result<FooObjectWithEveryNullableType, ArriError>>
+           + std::marker::Send {
+        TestClientPrefixed::send_object_with_nullable_fields(self, params)
+    }
+    fn send_object_with_pascal_case_keys(
+        &self,
+        params: FooObjectWithPascalCaseKeys,
+    ) -> impl std::future::Future<Output = Result<FooObjectWithPascalCaseKeys, ArriError>>
+           + std::marker::Send {
+        TestClientPrefixed::send_object_with_pascal_case_keys(self, params)
+    }
+    fn send_object_with_snake_case_keys(
+        &self,
+        params: FooObjectWithSnakeCaseKeys,
+    ) -> impl std::future::Future<Output = Result<FooObjectWithSnakeCaseKeys, ArriError>>
+           + std::marker::Send {
+        TestClientPrefixed::send_object_with_snake_case_keys(self, params)
+    }
+    fn send_partial_object(
+        &self,
+        params: FooObjectWithEveryOptionalType,
+    ) -> impl std::future::Future<Output = Result<FooObjectWithEveryOptionalType, ArriError>>
+           + std::marker::Send {
+        TestClientPrefixed::send_partial_object(self, params)
+    }
+    fn send_recursive_object(
+        &self,
+        params: FooRecursiveObject,
+    ) -> impl std::future::Future<Output = Result<FooRecursiveObject, ArriError>> + std::marker::Send
+    {
+        TestClientPrefixed::send_recursive_object(self, params)
+    }
+    fn send_recursive_union(
+        &self,
+        params: FooRecursiveUnion,
+    ) -> impl std::future::Future<Output = Result<FooRecursiveUnion, ArriError>> + std::marker::Send
+    {
+        TestClientPrefixed::send_recursive_union(self, params)
+    }
+    fn stream_auto_reconnect<OnEvent, OnEventOutput>(
+        &self,
+        params: FooAutoReconnectParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<FooAutoReconnectParams, FooAutoReconnectResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<FooAutoReconnectResponse>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        TestClientPrefixed::stream_auto_reconnect(self, params, on_event, options)
+    }
+    fn stream_connection_error_test<OnEvent, OnEventOutput>(
+        &self,
+        params: FooStreamConnectionErrorTestParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<
+            FooStreamConnectionErrorTestParams,
+            FooStreamConnectionErrorTestResponse,
+        >,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(
+                SseEvent<FooStreamConnectionErrorTestResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        TestClientPrefixed::stream_connection_error_test(self, params, on_event, options)
+    }
+    fn stream_heartbeat_detection_test<OnEvent, OnEventOutput>(
+        &self,
+        params: FooStreamHeartbeatDetectionTestParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<
+            FooStreamHeartbeatDetectionTestParams,
+            FooStreamHeartbeatDetectionTestResponse,
+        >,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(
+                SseEvent<FooStreamHeartbeatDetectionTestResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        TestClientPrefixed::stream_heartbeat_detection_test(self, params, on_event, options)
+    }
+    fn stream_large_objects<OnEvent, OnEventOutput>(
+        &self,
+        on_event: &mut OnEvent,
+        options: SseOptions<EmptyArriModel, FooStreamLargeObjectsResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<FooStreamLargeObjectsResponse>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        TestClientPrefixed::stream_large_objects(self, on_event, options)
+    }
+    fn stream_messages<OnEvent, OnEventOutput>(
+        &self,
+        params: FooChatMessageParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<FooChatMessageParams, FooChatMessage>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<FooChatMessage>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        TestClientPrefixed::stream_messages(self, params, on_event, options)
+    }
+    fn stream_retry_with_new_credentials<OnEvent, OnEventOutput>(
+        &self,
+        on_event: &mut OnEvent,
+        options: SseOptions<EmptyArriModel, FooTestsStreamRetryWithNewCredentialsResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(
+                SseEvent<FooTestsStreamRetryWithNewCredentialsResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        TestClientPrefixed::stream_retry_with_new_credentials(self, on_event, options)
+    }
+    fn stream_ten_events_then_end<OnEvent, OnEventOutput>(
+        &self,
+        on_event: &mut OnEvent,
+        options: SseOptions<EmptyArriModel, FooChatMessage>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<FooChatMessage>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        TestClientPrefixed::stream_ten_events_then_end(self, on_event, options)
+    }
+}
+
+#[cfg(feature = "testing")]
+#[derive(Clone)]
+pub struct TestClientPrefixedMock {
+    pub deprecated_rpc: MockProcedure<FooDeprecatedRpcParams, ()>,
+    pub send_discriminator_with_empty_object:
+        MockProcedure<FooDiscriminatorWithEmptyObject, FooDiscriminatorWithEmptyObject>,
+    pub send_error: MockProcedure<FooSendErrorParams, ()>,
+    pub send_object: MockProcedure<FooObjectWithEveryType, FooObjectWithEveryType>,
+    pub send_object_with_nullable_fields:
+        MockProcedure<FooObjectWithEveryNullableType, FooObjectWithEveryNullableType>,
+    pub send_object_with_pascal_case_keys:
+        MockProcedure<FooObjectWithPascalCaseKeys, FooObjectWithPascalCaseKeys>,
+    pub send_object_with_snake_case_keys:
+        MockProcedure<FooObjectWithSnakeCaseKeys, FooObjectWithSnakeCaseKeys>,
+    pub send_partial_object:
+        MockProcedure<FooObjectWithEveryOptionalType, FooObjectWithEveryOptionalType>,
+    pub send_recursive_object: MockProcedure<FooRecursiveObject, FooRecursiveObject>,
+    pub send_recursive_union: MockProcedure<FooRecursiveUnion, FooRecursiveUnion>,
+    pub stream_auto_reconnect: MockEventStream<FooAutoReconnectParams, FooAutoReconnectResponse>,
+    pub stream_connection_error_test:
+        MockEventStream<FooStreamConnectionErrorTestParams, FooStreamConnectionErrorTestResponse>,
+    pub stream_heartbeat_detection_test: MockEventStream<
+        FooStreamHeartbeatDetectionTestParams,
+        FooStreamHeartbeatDetectionTestResponse,
+    >,
+    pub stream_large_objects: MockEventStream<EmptyArriModel, FooStreamLargeObjectsResponse>,
+    pub stream_messages: MockEventStream<FooChatMessageParams, FooChatMessage>,
+    pub stream_retry_with_new_credentials:
+        MockEventStream<EmptyArriModel, FooTestsStreamRetryWithNewCredentialsResponse>,
+    pub stream_ten_events_then_end: MockEventStream<EmptyArriModel, FooChatMessage>,
+    pub nested: TestClientPrefixedNestedServiceMock,
+}
+
+#[cfg(feature = "testing")]
+impl Default for TestClientPrefixedMock {
+    fn default() -> Self {
+        Self {
+            deprecated_rpc: MockProcedure::new("/rpcs/tests/deprecated-rpc"),
+            send_discriminator_with_empty_object: MockProcedure::new(
+                "/rpcs/tests/send-discriminator-with-empty-object",
+            ),
+            send_error: MockProcedure::new("/rpcs/tests/send-error"),
+            send_object: MockProcedure::new("/rpcs/tests/send-object"),
+            send_object_with_nullable_fields: MockProcedure::new(
+                "/rpcs/tests/send-object-with-nullable-fields",
+            ),
+            send_object_with_pascal_case_keys: MockProcedure::new(
+                "/rpcs/tests/send-object-with-pascal-case-keys",
+            ),
+            send_object_with_snake_case_keys: MockProcedure::new(
+                "/rpcs/tests/send-object-with-snake-case-keys",
+            ),
+            send_partial_object: MockProcedure::new("/rpcs/tests/send-partial-object"),
+            send_recursive_object: MockProcedure::new("/rpcs/tests/send-recursive-object"),
+            send_recursive_union: MockProcedure::new("/rpcs/tests/send-recursive-union"),
+            stream_auto_reconnect: MockEventStream::new("/rpcs/tests/stream-auto-reconnect"),
+            stream_connection_error_test: MockEventStream::new(
+                "/rpcs/tests/stream-connection-error-test",
+            ),
+            stream_heartbeat_detection_test: MockEventStream::new(
+                "/rpcs/tests/stream-heartbeat-detection-test",
+            ),
+            stream_large_objects: MockEventStream::new("/rpcs/tests/stream-large-objects"),
+            stream_messages: MockEventStream::new("/rpcs/tests/stream-messages"),
+            stream_retry_with_new_credentials: MockEventStream::new(
+                "/rpcs/tests/stream-retry-with-new-credentials",
+            ),
+            stream_ten_events_then_end: MockEventStream::new(
+                "/rpcs/tests/stream-ten-events-then-end",
+            ),
+            nested: TestClientPrefixedNestedServiceMock::default(),
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+impl TestClientPrefixedApi for TestClientPrefixedMock {
+    type Nested = TestClientPrefixedNestedServiceMock;
+    fn nested(&self) -> &Self::Nested {
+        &self.nested
+    }
+    fn deprecated_rpc(
+        &self,
+        params: FooDeprecatedRpcParams,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send {
+        self.deprecated_rpc.call(params)
+    }
+    fn send_discriminator_with_empty_object(
+        &self,
+        params: FooDiscriminatorWithEmptyObject,
+    ) -> impl std::future::Future<Output = Result<FooDiscriminatorWithEmptyObject, ArriError>>
+           + std::marker::Send {
+        self.send_discriminator_with_empty_object.call(params)
+    }
+    fn send_error(
+        &self,
+        params: FooSendErrorParams,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send {
+        self.send_error.call(params)
+    }
+    fn send_object(
+        &self,
+        params: FooObjectWithEveryType,
+    ) -> impl std::future::Future<Output = Result<FooObjectWithEveryType, ArriError>> + std::marker::Send
+    {
+        self.send_object.call(params)
+    }
+    fn send_object_with_nullable_fields(
+        &self,
+        params: FooObjectWithEveryNullableType,
+    ) -> impl std::future::Future<Output = Result<FooObjectWithEveryNullableType, ArriError>>
+           + std::marker::Send {
+        self.send_object_with_nullable_fields.call(params)
+    }
+    fn send_object_with_pascal_case_keys(
+        &self,
+        params: FooObjectWithPascalCaseKeys,
+    ) -> impl std::future::Future<Output = Result<FooObjectWithPascalCaseKeys, ArriError>>
+           + std::marker::Send {
+        self.send_object_with_pascal_case_keys.call(params)
+    }
+    fn send_object_with_snake_case_keys(
+        &self,
+        params: FooObjectWithSnakeCaseKeys,
+    ) -> impl std::future::Future<Output = Result<FooObjectWithSnakeCaseKeys, ArriError>>
+           + std::marker::Send {
+        self.send_object_with_snake_case_keys.call(params)
+    }
+    fn send_partial_object(
+        &self,
+        params: FooObjectWithEveryOptionalType,
+    ) -> impl std::future::Future<Output = Result<FooObjectWithEveryOptionalType, ArriError>>
+           + std::marker::Send {
+        self.send_partial_object.call(params)
+    }
+    fn send_recursive_object(
+        &self,
+        params: FooRecursiveObject,
+    ) -> impl std::future::Future<Output = Result<FooRecursiveObject, ArriError>> + std::marker::Send
+    {
+        self.send_recursive_object.call(params)
+    }
+    fn send_recursive_union(
+        &self,
+        params: FooRecursiveUnion,
+    ) -> impl std::future::Future<Output = Result<FooRecursiveUnion, ArriError>> + std::marker::Send
+    {
+        self.send_recursive_union.call(params)
+    }
+    fn stream_auto_reconnect<OnEvent, OnEventOutput>(
+        &self,
+        params: FooAutoReconnectParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<FooAutoReconnectParams, FooAutoReconnectResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<FooAutoReconnectResponse>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        self.stream_auto_reconnect.call(params, on_event)
+    }
+    fn stream_connection_error_test<OnEvent, OnEventOutput>(
+        &self,
+        params: FooStreamConnectionErrorTestParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<
+            FooStreamConnectionErrorTestParams,
+            FooStreamConnectionErrorTestResponse,
+        >,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(
+                SseEvent<FooStreamConnectionErrorTestResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        self.stream_connection_error_test.call(params, on_event)
+    }
+    fn stream_heartbeat_detection_test<OnEvent, OnEventOutput>(
+        &self,
+        params: FooStreamHeartbeatDetectionTestParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<
+            FooStreamHeartbeatDetectionTestParams,
+            FooStreamHeartbeatDetectionTestResponse,
+        >,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(
+                SseEvent<FooStreamHeartbeatDetectionTestResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        self.stream_heartbeat_detection_test.call(params, on_event)
+    }
+    fn stream_large_objects<OnEvent, OnEventOutput>(
+        &self,
+        on_event: &mut OnEvent,
+        options: SseOptions<EmptyArriModel, FooStreamLargeObjectsResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<FooStreamLargeObjectsResponse>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        self.stream_large_objects.call(EmptyArriModel {}, on_event)
+    }
+    fn stream_messages<OnEvent, OnEventOutput>(
+        &self,
+        params: FooChatMessageParams,
+        on_event: &mut OnEvent,
+        options: SseOptions<FooChatMessageParams, FooChatMessage>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<FooChatMessage>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        self.stream_messages.call(params, on_event)
+    }
+    fn stream_retry_with_new_credentials<OnEvent, OnEventOutput>(
+        &self,
+        on_event: &mut OnEvent,
+        options: SseOptions<EmptyArriModel, FooTestsStreamRetryWithNewCredentialsResponse>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(
+                SseEvent<FooTestsStreamRetryWithNewCredentialsResponse>,
+                &mut SseController,
+            ) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        self.stream_retry_with_new_credentials
+            .call(EmptyArriModel {}, on_event)
+    }
+    fn stream_ten_events_then_end<OnEvent, OnEventOutput>(
+        &self,
+        on_event: &mut OnEvent,
+        options: SseOptions<EmptyArriModel, FooChatMessage>,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send
+    where
+        OnEvent: FnMut(SseEvent<FooChatMessage>, &mut SseController) -> OnEventOutput
+            + std::marker::Send
+            + std::marker::Sync,
+        OnEventOutput: SseHandlerOutput,
+    {
+        self.stream_ten_events_then_end
+            .call(EmptyArriModel {}, on_event)
+    }
+}
+
 #[derive(Clone)]
 pub struct TestClientPrefixedNestedService {
     _config: InternalArriClientConfig,
@@ -432,9 +1542,7 @@ pub struct TestClientPrefixedNestedService {
 
 impl ArriClientService for TestClientPrefixedNestedService {
     fn create(config: ArriClientConfig) -> Self {
-        Self {
-            _config: InternalArriClientConfig::from(config),
-        }
+        Self::_from_config(InternalArriClientConfig::from(config))
     }
     fn update_headers(&self, headers: HashMap<&'static str, String>) {
         let mut unwrapped_headers = self._config.headers.write().unwrap();
@@ -442,38 +1550,27 @@ impl ArriClientService for TestClientPrefixedNestedService {
     }
 }
 
+impl ArriProcedureClient for TestClientPrefixedNestedService {
+    fn procedure_config(&self) -> &InternalArriClientConfig {
+        &self._config
+    }
+}
+
 impl TestClientPrefixedNestedService {
+    fn _from_config(config: InternalArriClientConfig) -> Self {
+        Self { _config: config }
+    }
     pub async fn empty_params_get_request(&self) -> Result<FooDefaultPayload, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/nested/empty-params-get-request",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::GET,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            None::<EmptyArriModel>,
-            |body| return FooDefaultPayload::from_json_string(body),
+        arri_procedure_request::<TestClientPrefixedNestedEmptyParamsGetRequestProcedure>(
+            &self._config,
+            EmptyArriModel {},
         )
         .await
     }
     pub async fn empty_params_post_request(&self) -> Result<FooDefaultPayload, ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/nested/empty-params-post-request",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            None::<EmptyArriModel>,
-            |body| return FooDefaultPayload::from_json_string(body),
+        arri_procedure_request::<TestClientPrefixedNestedEmptyParamsPostRequestProcedure>(
+            &self._config,
+            EmptyArriModel {},
         )
         .await
     }
@@ -481,19 +1578,9 @@ impl TestClientPrefixedNestedService {
         &self,
         params: FooDefaultPayload,
     ) -> Result<(), ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/nested/empty-response-get-request",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::GET,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| {},
+        arri_procedure_request::<TestClientPrefixedNestedEmptyResponseGetRequestProcedure>(
+            &self._config,
+            params,
         )
         .await
     }
@@ -501,24 +1588,179 @@ impl TestClientPrefixedNestedService {
         &self,
         params: FooDefaultPayload,
     ) -> Result<(), ArriError> {
-        parsed_arri_request(
-            ArriParsedRequestOptions {
-                http_client: &self._config.http_client,
-                url: format!(
-                    "{}/rpcs/tests/nested/empty-response-post-request",
-                    &self._config.base_url
-                ),
-                method: reqwest::Method::POST,
-                headers: self._config.headers.clone(),
-                client_version: "10".to_string(),
-            },
-            Some(params),
-            |body| {},
+        arri_procedure_request::<TestClientPrefixedNestedEmptyResponsePostRequestProcedure>(
+            &self._config,
+            params,
         )
         .await
     }
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedNestedEmptyParamsGetRequestProcedure;
+
+impl ArriProcedure for TestClientPrefixedNestedEmptyParamsGetRequestProcedure {
+    type Params = EmptyArriModel;
+    type Response = FooDefaultPayload;
+    const PATH: &'static str = "/rpcs/tests/nested/empty-params-get-request";
+    const METHOD: reqwest::Method = reqwest::Method::GET;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        FooDefaultPayload::from_json_string(body)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedNestedEmptyParamsPostRequestProcedure;
+
+impl ArriProcedure for TestClientPrefixedNestedEmptyParamsPostRequestProcedure {
+    type Params = EmptyArriModel;
+    type Response = FooDefaultPayload;
+    const PATH: &'static str = "/rpcs/tests/nested/empty-params-post-request";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(body: String) -> Self::Response {
+        FooDefaultPayload::from_json_string(body)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedNestedEmptyResponseGetRequestProcedure;
+
+impl ArriProcedure for TestClientPrefixedNestedEmptyResponseGetRequestProcedure {
+    type Params = FooDefaultPayload;
+    type Response = ();
+    const PATH: &'static str = "/rpcs/tests/nested/empty-response-get-request";
+    const METHOD: reqwest::Method = reqwest::Method::GET;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(_: String) -> Self::Response {}
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestClientPrefixedNestedEmptyResponsePostRequestProcedure;
+
+impl ArriProcedure for TestClientPrefixedNestedEmptyResponsePostRequestProcedure {
+    type Params = FooDefaultPayload;
+    type Response = ();
+    const PATH: &'static str = "/rpcs/tests/nested/empty-response-post-request";
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+    const IS_EVENT_STREAM: bool = false;
+    const IS_DEPRECATED: bool = false;
+    const CLIENT_VERSION: &'static str = "10";
+
+    fn parse_response(_: String) -> Self::Response {}
+}
+
+pub trait TestClientPrefixedNestedServiceApi {
+    fn empty_params_get_request(
+        &self,
+    ) -> impl std::future::Future<Output = Result<FooDefaultPayload, ArriError>> + std::marker::Send;
+    fn empty_params_post_request(
+        &self,
+    ) -> impl std::future::Future<Output = Result<FooDefaultPayload, ArriError>> + std::marker::Send;
+    fn empty_response_get_request(
+        &self,
+        params: FooDefaultPayload,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send;
+    fn empty_response_post_request(
+        &self,
+        params: FooDefaultPayload,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send;
+}
+
+impl TestClientPrefixedNestedServiceApi for TestClientPrefixedNestedService {
+    fn empty_params_get_request(
+        &self,
+    ) -> impl std::future::Future<Output = Result<FooDefaultPayload, ArriError>> + std::marker::Send
+    {
+        TestClientPrefixedNestedService::empty_params_get_request(self)
+    }
+    fn empty_params_post_request(
+        &self,
+    ) -> impl std::future::Future<Output = Result<FooDefaultPayload, ArriError>> + std::marker::Send
+    {
+        TestClientPrefixedNestedService::empty_params_post_request(self)
+    }
+    fn empty_response_get_request(
+        &self,
+        params: FooDefaultPayload,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send {
+        TestClientPrefixedNestedService::empty_response_get_request(self, params)
+    }
+    fn empty_response_post_request(
+        &self,
+        params: FooDefaultPayload,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send {
+        TestClientPrefixedNestedService::empty_response_post_request(self, params)
+    }
+}
+
+#[cfg(feature = "testing")]
+#[derive(Clone)]
+pub struct TestClientPrefixedNestedServiceMock {
+    pub empty_params_get_request: MockProcedure<EmptyArriModel, FooDefaultPayload>,
+    pub empty_params_post_request: MockProcedure<EmptyArriModel, FooDefaultPayload>,
+    pub empty_response_get_request: MockProcedure<FooDefaultPayload, ()>,
+    pub empty_response_post_request: MockProcedure<FooDefaultPayload, ()>,
+}
+
+#[cfg(feature = "testing")]
+impl Default for TestClientPrefixedNestedServiceMock {
+    fn default() -> Self {
+        Self {
+            empty_params_get_request: MockProcedure::new(
+                "/rpcs/tests/nested/empty-params-get-request",
+            ),
+            empty_params_post_request: MockProcedure::new(
+                "/rpcs/tests/nested/empty-params-post-request",
+            ),
+            empty_response_get_request: MockProcedure::new(
+                "/rpcs/tests/nested/empty-response-get-request",
+            ),
+            empty_response_post_request: MockProcedure::new(
+                "/rpcs/tests/nested/empty-response-post-request",
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+impl TestClientPrefixedNestedServiceApi for TestClientPrefixedNestedServiceMock {
+    fn empty_params_get_request(
+        &self,
+    ) -> impl std::future::Future<Output = Result<FooDefaultPayload, ArriError>> + std::marker::Send
+    {
+        self.empty_params_get_request.call(EmptyArriModel {})
+    }
+    fn empty_params_post_request(
+        &self,
+    ) -> impl std::future::Future<Output = Result<FooDefaultPayload, ArriError>> + std::marker::Send
+    {
+        self.empty_params_post_request.call(EmptyArriModel {})
+    }
+    fn empty_response_get_request(
+        &self,
+        params: FooDefaultPayload,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send {
+        self.empty_response_get_request.call(params)
+    }
+    fn empty_response_post_request(
+        &self,
+        params: FooDefaultPayload,
+    ) -> impl std::future::Future<Output = Result<(), ArriError>> + std::marker::Send {
+        self.empty_response_post_request.call(params)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct FooManuallyAddedModel {
     pub hello: String,